@@ -1,3 +1,4 @@
+use crate::error::{AppError, AppResult};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
 use std::collections::HashMap;
@@ -47,7 +48,7 @@ impl ChromaClient {
         }
     }
 
-    pub async fn create_collection(&self, name: &str) -> Result<ChromaCollection, String> {
+    pub async fn create_collection(&self, name: &str) -> AppResult<ChromaCollection> {
         let url = format!("{}/api/v1/collections", self.base_url);
         let payload = json!({
             "name": name,
@@ -60,18 +61,18 @@ impl ChromaClient {
             .json(&payload)
             .send()
             .await
-            .map_err(|e| format!("请求失败: {}", e))?;
+            .map_err(|e| AppError::network("请求 ChromaDB 失败").with_details(e))?;
 
         if response.status().is_success() {
-            let collection: ChromaCollection = response
-                .json()
-                .await
-                .map_err(|e| format!("解析响应失败: {}", e))?;
+            let collection: ChromaCollection = response.json().await?;
             Ok(collection)
         } else {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            Err(format!("创建集合失败: {} - {}", status, text))
+            Err(AppError::from_status(
+                status,
+                format!("创建集合失败: {}", text),
+            ))
         }
     }
 
@@ -79,8 +80,11 @@ impl ChromaClient {
         &self,
         collection_name: &str,
         request: AddDocumentsRequest,
-    ) -> Result<(), String> {
-        let url = format!("{}/api/v1/collections/{}/add", self.base_url, collection_name);
+    ) -> AppResult<()> {
+        let url = format!(
+            "{}/api/v1/collections/{}/add",
+            self.base_url, collection_name
+        );
         let payload = json!({
             "ids": request.ids,
             "documents": request.documents,
@@ -94,14 +98,17 @@ impl ChromaClient {
             .json(&payload)
             .send()
             .await
-            .map_err(|e| format!("请求失败: {}", e))?;
+            .map_err(|e| AppError::network("请求 ChromaDB 失败").with_details(e))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            Err(format!("添加文档失败: {} - {}", status, text))
+            Err(AppError::from_status(
+                status,
+                format!("添加文档失败: {}", text),
+            ))
         }
     }
 
@@ -109,10 +116,13 @@ impl ChromaClient {
         &self,
         collection_name: &str,
         request: QueryRequest,
-    ) -> Result<QueryResult, String> {
-        let url = format!("{}/api/v1/collections/{}/query", self.base_url, collection_name);
+    ) -> AppResult<QueryResult> {
+        let url = format!(
+            "{}/api/v1/collections/{}/query",
+            self.base_url, collection_name
+        );
         let mut payload = serde_json::Map::new();
-        
+
         if let Some(query_texts) = request.query_texts {
             payload.insert("query_texts".to_string(), json!(query_texts));
         }
@@ -132,37 +142,36 @@ impl ChromaClient {
             .json(&payload)
             .send()
             .await
-            .map_err(|e| format!("请求失败: {}", e))?;
+            .map_err(|e| AppError::network("请求 ChromaDB 失败").with_details(e))?;
 
         if response.status().is_success() {
-            let result: QueryResult = response
-                .json()
-                .await
-                .map_err(|e| format!("解析响应失败: {}", e))?;
+            let result: QueryResult = response.json().await?;
             Ok(result)
         } else {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            Err(format!("查询失败: {} - {}", status, text))
+            Err(AppError::from_status(status, format!("查询失败: {}", text)))
         }
     }
 
-    pub async fn delete_collection(&self, collection_name: &str) -> Result<(), String> {
+    pub async fn delete_collection(&self, collection_name: &str) -> AppResult<()> {
         let url = format!("{}/api/v1/collections/{}", self.base_url, collection_name);
         let response = self
             .client
             .delete(&url)
             .send()
             .await
-            .map_err(|e| format!("请求失败: {}", e))?;
+            .map_err(|e| AppError::network("请求 ChromaDB 失败").with_details(e))?;
 
         if response.status().is_success() {
             Ok(())
         } else {
             let status = response.status();
             let text = response.text().await.unwrap_or_default();
-            Err(format!("删除集合失败: {} - {}", status, text))
+            Err(AppError::from_status(
+                status,
+                format!("删除集合失败: {}", text),
+            ))
         }
     }
 }
-
@@ -1,3 +1,5 @@
+use crate::error::{AppError, AppResult};
+use crate::workspace::Workspaces;
 use std::path::PathBuf;
 use std::process::{Child, Command, Stdio};
 use std::sync::{Arc, Mutex};
@@ -10,15 +12,11 @@ pub struct ChromaServer {
 }
 
 impl ChromaServer {
-    pub fn new(app: &AppHandle, port: u16) -> Result<Self, String> {
-        let app_data_dir = app
-            .path()
-            .resolve("app_data", tauri::path::BaseDirectory::AppData)
-            .map_err(|e| format!("获取应用数据目录失败: {}", e))?;
-        
-        let data_path = app_data_dir.join("chromadb");
+    pub fn new(app: &AppHandle, port: u16) -> AppResult<Self> {
+        // 每个工作区使用独立的向量数据目录
+        let data_path = app.state::<Workspaces>().chroma_dir();
         std::fs::create_dir_all(&data_path)
-            .map_err(|e| format!("创建数据目录失败: {}", e))?;
+            .map_err(|e| AppError::io("创建数据目录失败").with_details(e))?;
 
         Ok(Self {
             process: Arc::new(Mutex::new(None)),
@@ -27,7 +25,7 @@ impl ChromaServer {
         })
     }
 
-    pub fn start(&self) -> Result<(), String> {
+    pub fn start(&self) -> AppResult<()> {
         let mut process_guard = self.process.lock().unwrap();
         
         if process_guard.is_some() {
@@ -49,17 +47,18 @@ impl ChromaServer {
             .stderr(Stdio::piped());
 
         let child = cmd.spawn().map_err(|e| {
-            format!("启动 ChromaDB 服务器失败: {}. 请确保已安装 Python 和 chromadb: pip install chromadb", e)
+            AppError::internal("启动 ChromaDB 服务器失败，请确保已安装 Python 和 chromadb: pip install chromadb")
+                .with_details(e)
         })?;
 
         *process_guard = Some(child);
         Ok(())
     }
 
-    pub fn stop(&self) -> Result<(), String> {
+    pub fn stop(&self) -> AppResult<()> {
         let mut process_guard = self.process.lock().unwrap();
         if let Some(mut child) = process_guard.take() {
-            child.kill().map_err(|e| format!("停止 ChromaDB 服务器失败: {}", e))?;
+            child.kill().map_err(|e| AppError::internal("停止 ChromaDB 服务器失败").with_details(e))?;
             let _ = child.wait();
         }
         Ok(())
@@ -69,7 +68,7 @@ impl ChromaServer {
         format!("http://localhost:{}", self.port)
     }
 
-    fn find_python(&self) -> Result<String, String> {
+    fn find_python(&self) -> AppResult<String> {
         for cmd in &["python3", "python"] {
             if Command::new(cmd)
                 .arg("--version")
@@ -81,10 +80,10 @@ impl ChromaServer {
                 return Ok(cmd.to_string());
             }
         }
-        Err("未找到 Python。请安装 Python 3.7+".to_string())
+        Err(AppError::not_found("未找到 Python，请安装 Python 3.7+"))
     }
 
-    fn ensure_chromadb_installed(&self, python_cmd: &str) -> Result<(), String> {
+    fn ensure_chromadb_installed(&self, python_cmd: &str) -> AppResult<()> {
         let check_cmd = Command::new(python_cmd)
             .arg("-c")
             .arg("import chromadb; print('ok')")
@@ -106,9 +105,9 @@ impl ChromaServer {
                     Ok(output) if output.status.success() => Ok(()),
                     Ok(output) => {
                         let stderr = String::from_utf8_lossy(&output.stderr);
-                        Err(format!("安装 chromadb 失败: {}", stderr))
+                        Err(AppError::internal("安装 chromadb 失败").with_details(stderr))
                     }
-                    Err(e) => Err(format!("执行 pip install 失败: {}", e)),
+                    Err(e) => Err(AppError::internal("执行 pip install 失败").with_details(e)),
                 }
             }
         }
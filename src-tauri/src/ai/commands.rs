@@ -1,3 +1,5 @@
+use crate::error::AppResult;
+use crate::settings::Settings;
 use crate::ai::chromadb::{ChromaClient, AddDocumentsRequest, QueryRequest};
 use crate::ai::chromadb_server::ChromaServer;
 use serde_json::Value;
@@ -23,10 +25,12 @@ async fn get_embedded_base_url(server_state: &State<'_, ChromaServerState>) -> O
 pub async fn chroma_start_server(
     app: AppHandle,
     server_state: State<'_, ChromaServerState>,
-) -> Result<String, String> {
+    settings: State<'_, Settings>,
+) -> AppResult<String> {
     let mut state = server_state.lock().await;
     if state.is_none() {
-        let server = Arc::new(ChromaServer::new(&app, 8000)?);
+        let port = settings.get().chroma.port;
+        let server = Arc::new(ChromaServer::new(&app, port)?);
         server.start()?;
         tokio::time::sleep(tokio::time::Duration::from_millis(1000)).await;
         *state = Some(server.clone());
@@ -39,7 +43,7 @@ pub async fn chroma_start_server(
 #[tauri::command]
 pub async fn chroma_stop_server(
     server_state: State<'_, ChromaServerState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let mut state = server_state.lock().await;
     if let Some(server) = state.take() {
         server.stop()?;
@@ -52,7 +56,7 @@ pub async fn chroma_create_collection(
     name: String,
     base_url: Option<String>,
     server_state: State<'_, ChromaServerState>,
-) -> Result<serde_json::Value, String> {
+) -> AppResult<serde_json::Value> {
     let embedded_url = get_embedded_base_url(&server_state).await;
     let url = base_url.or(embedded_url);
     let client = get_client(url);
@@ -72,7 +76,7 @@ pub async fn chroma_add_documents(
     embeddings: Option<Vec<Vec<f32>>>,
     base_url: Option<String>,
     server_state: State<'_, ChromaServerState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let embedded_url = get_embedded_base_url(&server_state).await;
     let url = base_url.or(embedded_url);
     let client = get_client(url);
@@ -94,7 +98,7 @@ pub async fn chroma_query(
     where_metadata: Option<HashMap<String, Value>>,
     base_url: Option<String>,
     server_state: State<'_, ChromaServerState>,
-) -> Result<serde_json::Value, String> {
+) -> AppResult<serde_json::Value> {
     let embedded_url = get_embedded_base_url(&server_state).await;
     let url = base_url.or(embedded_url);
     let client = get_client(url);
@@ -118,7 +122,7 @@ pub async fn chroma_delete_collection(
     collection_name: String,
     base_url: Option<String>,
     server_state: State<'_, ChromaServerState>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     let embedded_url = get_embedded_base_url(&server_state).await;
     let url = base_url.or(embedded_url);
     let client = get_client(url);
@@ -1,19 +1,41 @@
-use crate::database::{self, Message};
+use crate::database::secrets::Vault;
+use crate::database::{self, Database, Message};
+use crate::error::{AppError, AppResult, ErrorCode};
+use rusqlite::OptionalExtension;
 use serde::{Deserialize, Serialize};
 use tauri::AppHandle;
 use tauri::Runtime;
+use tauri::State;
 
 #[derive(Serialize, Deserialize, Debug)]
-struct ChatRequestMessage {
+pub(crate) struct ChatRequestMessage {
     role: String,
     content: String,
 }
 
+impl ChatRequestMessage {
+    pub(crate) fn system(content: impl Into<String>) -> Self {
+        Self {
+            role: "system".to_string(),
+            content: content.into(),
+        }
+    }
+
+    pub(crate) fn user(content: impl Into<String>) -> Self {
+        Self {
+            role: "user".to_string(),
+            content: content.into(),
+        }
+    }
+}
+
 #[derive(Serialize, Deserialize, Debug)]
 struct ChatRequest {
     model: String,
     messages: Vec<ChatRequestMessage>,
     stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
 }
 
 #[derive(Deserialize, Debug)]
@@ -47,15 +69,17 @@ struct StreamPayload {
 #[tauri::command]
 pub async fn chat<R: Runtime>(
     app: AppHandle<R>,
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
     conversation_id: String,
     model_id: String,
     messages: Vec<Message>,
-) -> Result<(), String> {
+) -> AppResult<()> {
     use futures_util::StreamExt;
     use tauri::Emitter;
 
     // 1. Get model & provider info
-    let model_info = database::get_model_with_provider(&app, &model_id)?;
+    let model_info = database::get_model_with_provider(&db, &vault, &model_id).await?;
 
     // 2. Prepare request
     let client = reqwest::Client::new();
@@ -76,6 +100,7 @@ pub async fn chat<R: Runtime>(
         model: model_info.model_key,
         messages: api_messages,
         stream: true,
+        temperature: None,
     };
 
     // 3. Call API
@@ -88,17 +113,19 @@ pub async fn chat<R: Runtime>(
         .json(&request_body)
         .send()
         .await
-        .map_err(|e| format!("Request failed: {}", e))?;
+        .map_err(|e| AppError::network("请求模型服务失败").with_details(e))?;
 
     if !res.status().is_success() {
-        return Err(format!("API Error: {}", res.status()));
+        let status = res.status();
+        let body = res.text().await.unwrap_or_default();
+        return Err(AppError::from_status(status, body));
     }
 
     let mut stream = res.bytes_stream();
     let event_name = format!("chat-stream://{}", conversation_id);
 
     while let Some(item) = stream.next().await {
-        let chunk = item.map_err(|e| format!("Stream error: {}", e))?;
+        let chunk = item.map_err(|e| AppError::network("读取响应流失败").with_details(e))?;
         let chunk_str = String::from_utf8_lossy(&chunk);
 
         for line in chunk_str.lines() {
@@ -114,12 +141,10 @@ pub async fn chat<R: Runtime>(
                         chunk: "".to_string(),
                         done: true,
                     },
-                )
-                .map_err(|e| e.to_string())?;
+                )?;
                 break;
             }
-            if line.starts_with("data: ") {
-                let json_str = &line[6..];
+            if let Some(json_str) = line.strip_prefix("data: ") {
                 if let Ok(response) = serde_json::from_str::<ChatResponse>(json_str) {
                     if let Some(choice) = response.choices.first() {
                         // Some providers use `delta` instead of `message` for streaming
@@ -140,8 +165,7 @@ pub async fn chat<R: Runtime>(
                                     chunk: content.clone(),
                                     done: false,
                                 },
-                            )
-                            .map_err(|e| e.to_string())?;
+                            )?;
                         }
                     }
                 }
@@ -151,3 +175,68 @@ pub async fn chat<R: Runtime>(
 
     Ok(())
 }
+
+/// 未指定模型时使用当前激活的模型
+pub(crate) async fn resolve_model_id(db: &Database, model_id: Option<String>) -> AppResult<String> {
+    if let Some(model_id) = model_id {
+        return Ok(model_id);
+    }
+    db.run(|conn| {
+        conn.query_row(
+            "SELECT id FROM models WHERE is_active = 1 LIMIT 1",
+            [],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::invalid_input("未选择模型，请先设置当前使用的模型"))
+    })
+    .await
+}
+
+/// 非流式请求，返回完整的回复内容，供翻译等后台任务使用
+pub(crate) async fn complete(
+    db: &Database,
+    vault: &Vault,
+    model_id: &str,
+    messages: Vec<ChatRequestMessage>,
+    temperature: Option<f32>,
+) -> AppResult<String> {
+    let model_info = database::get_model_with_provider(db, vault, model_id).await?;
+
+    let url = format!(
+        "{}/chat/completions",
+        model_info.provider_url.trim_end_matches('/')
+    );
+    let request_body = ChatRequest {
+        model: model_info.model_key,
+        messages,
+        stream: false,
+        temperature,
+    };
+
+    let res = reqwest::Client::new()
+        .post(&url)
+        .header(
+            "Authorization",
+            format!("Bearer {}", model_info.provider_key),
+        )
+        .json(&request_body)
+        .send()
+        .await
+        .map_err(|e| AppError::network("请求模型服务失败").with_details(e))?;
+
+    if !res.status().is_success() {
+        let status = res.status();
+        let body = res.text().await.unwrap_or_default();
+        return Err(AppError::from_status(status, body));
+    }
+
+    let response: ChatResponse = res.json().await?;
+    response
+        .choices
+        .into_iter()
+        .next()
+        .and_then(|choice| choice.message)
+        .map(|message| message.content)
+        .ok_or_else(|| AppError::new(ErrorCode::Provider, "模型没有返回任何内容"))
+}
@@ -0,0 +1,309 @@
+//! 使用统计
+//!
+//! 按日期范围聚合消息和会话，回收站中的会话不计入。
+//! 回复耗时按同一会话中助手消息与紧邻的上一条用户消息的时间差计算。
+
+use super::Database;
+use crate::error::{AppError, AppResult};
+use chrono::NaiveDate;
+use rusqlite::{named_params, Connection};
+use serde::Serialize;
+use tauri::State;
+
+const DEFAULT_TOP_LIMIT: u32 = 10;
+const MAX_TOP_LIMIT: u32 = 100;
+// 超过该间隔的不算作回复耗时，例如导入的对话或隔天继续的对话
+const MAX_LATENCY_MS: f64 = 10.0 * 60.0 * 1000.0;
+
+const RANGE_FILTER: &str = "c.deleted_at IS NULL
+    AND (:from IS NULL OR m.timestamp >= :from)
+    AND (:to IS NULL OR m.timestamp < :to)";
+
+#[derive(Serialize, Debug)]
+pub struct UsageTotals {
+    pub messages: i64,
+    pub user_messages: i64,
+    pub assistant_messages: i64,
+    /// 范围内有消息的会话数
+    pub active_conversations: i64,
+    /// 范围内新建的会话数
+    pub new_conversations: i64,
+    /// 助手回复的平均字符数
+    pub avg_response_chars: Option<f64>,
+    pub avg_latency_ms: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct DailyUsage {
+    /// `YYYY-MM-DD`（UTC）
+    pub date: String,
+    pub messages: i64,
+    pub user_messages: i64,
+    pub assistant_messages: i64,
+    pub active_conversations: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ModelUsage {
+    pub model_id: String,
+    /// 模型已被删除时为空
+    pub model_name: Option<String>,
+    pub responses: i64,
+    pub avg_response_chars: Option<f64>,
+    pub avg_latency_ms: Option<f64>,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ConversationUsage {
+    pub id: String,
+    pub title: String,
+    pub messages: i64,
+    pub total_chars: i64,
+}
+
+#[derive(Serialize, Debug)]
+pub struct UsageStats {
+    pub from: Option<String>,
+    pub to: Option<String>,
+    pub totals: UsageTotals,
+    pub daily: Vec<DailyUsage>,
+    pub top_models: Vec<ModelUsage>,
+    pub heaviest_conversations: Vec<ConversationUsage>,
+}
+
+pub(crate) fn create_indexes(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_messages_timestamp ON messages(timestamp);
+         CREATE INDEX IF NOT EXISTS idx_conversations_created ON conversations(created_at);",
+    )
+    .map_err(AppError::from)
+}
+
+/// 范围内的助手回复及其耗时；窗口函数只在范围内计算，范围起点的第一条回复可能没有耗时
+fn replies_cte() -> String {
+    format!(
+        "WITH ranged AS (
+            SELECT m.role, m.content, m.timestamp, m.model_id,
+                   LAG(m.role) OVER w AS prev_role,
+                   LAG(m.timestamp) OVER w AS prev_timestamp
+            FROM messages m JOIN conversations c ON c.id = m.conversation_id
+            WHERE {}
+            WINDOW w AS (PARTITION BY m.conversation_id ORDER BY m.timestamp, m.id)
+        ),
+        replies AS (
+            SELECT model_id, length(content) AS chars,
+                   CASE WHEN prev_role = 'user' THEN
+                       (julianday(timestamp) - julianday(prev_timestamp)) * 86400000.0
+                   END AS latency_ms
+            FROM ranged WHERE role = 'assistant'
+        )",
+        RANGE_FILTER
+    )
+}
+
+/// 日期为 `YYYY-MM-DD`，结束日期包含在范围内
+fn parse_range(
+    from: Option<&str>,
+    to: Option<&str>,
+) -> AppResult<(Option<String>, Option<String>)> {
+    let parse = |value: &str| {
+        NaiveDate::parse_from_str(value.trim(), "%Y-%m-%d")
+            .map_err(|e| AppError::invalid_input(format!("无效的日期: {}", value)).with_details(e))
+    };
+    let from = from.map(parse).transpose()?;
+    let to = to.map(parse).transpose()?;
+
+    if let (Some(from), Some(to)) = (from, to) {
+        if from > to {
+            return Err(AppError::invalid_input("开始日期不能晚于结束日期"));
+        }
+    }
+
+    // 时间戳是 RFC 3339 字符串，可以直接与日期前缀比较
+    Ok((
+        from.map(|d| d.format("%Y-%m-%d").to_string()),
+        to.and_then(|d| d.succ_opt())
+            .map(|d| d.format("%Y-%m-%d").to_string()),
+    ))
+}
+
+fn query_totals(
+    conn: &Connection,
+    from: &Option<String>,
+    to: &Option<String>,
+) -> AppResult<UsageTotals> {
+    let (messages, user_messages, assistant_messages, active_conversations) = conn.query_row(
+        &format!(
+            "SELECT COUNT(*),
+                    COALESCE(SUM(m.role = 'user'), 0),
+                    COALESCE(SUM(m.role = 'assistant'), 0),
+                    COUNT(DISTINCT m.conversation_id)
+             FROM messages m JOIN conversations c ON c.id = m.conversation_id
+             WHERE {}",
+            RANGE_FILTER
+        ),
+        named_params! { ":from": from, ":to": to },
+        |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?, row.get(3)?)),
+    )?;
+
+    let new_conversations = conn.query_row(
+        "SELECT COUNT(*) FROM conversations
+         WHERE deleted_at IS NULL
+           AND (:from IS NULL OR created_at >= :from)
+           AND (:to IS NULL OR created_at < :to)",
+        named_params! { ":from": from, ":to": to },
+        |row| row.get(0),
+    )?;
+
+    let (avg_response_chars, avg_latency_ms) = conn.query_row(
+        &format!(
+            "{}
+             SELECT AVG(chars),
+                    AVG(CASE WHEN latency_ms BETWEEN 0 AND :max_latency THEN latency_ms END)
+             FROM replies",
+            replies_cte()
+        ),
+        named_params! { ":from": from, ":to": to, ":max_latency": MAX_LATENCY_MS },
+        |row| Ok((row.get(0)?, row.get(1)?)),
+    )?;
+
+    Ok(UsageTotals {
+        messages,
+        user_messages,
+        assistant_messages,
+        active_conversations,
+        new_conversations,
+        avg_response_chars,
+        avg_latency_ms,
+    })
+}
+
+fn query_daily(
+    conn: &Connection,
+    from: &Option<String>,
+    to: &Option<String>,
+) -> AppResult<Vec<DailyUsage>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT date(m.timestamp) AS day,
+                COUNT(*),
+                COALESCE(SUM(m.role = 'user'), 0),
+                COALESCE(SUM(m.role = 'assistant'), 0),
+                COUNT(DISTINCT m.conversation_id)
+         FROM messages m JOIN conversations c ON c.id = m.conversation_id
+         WHERE {}
+         GROUP BY day ORDER BY day ASC",
+        RANGE_FILTER
+    ))?;
+    let daily = stmt
+        .query_map(named_params! { ":from": from, ":to": to }, |row| {
+            Ok(DailyUsage {
+                date: row.get(0)?,
+                messages: row.get(1)?,
+                user_messages: row.get(2)?,
+                assistant_messages: row.get(3)?,
+                active_conversations: row.get(4)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(daily)
+}
+
+fn query_top_models(
+    conn: &Connection,
+    from: &Option<String>,
+    to: &Option<String>,
+    limit: u32,
+) -> AppResult<Vec<ModelUsage>> {
+    let mut stmt = conn.prepare(&format!(
+        "{}
+         SELECT r.model_id, mo.name, COUNT(*) AS responses, AVG(r.chars),
+                AVG(CASE WHEN r.latency_ms BETWEEN 0 AND :max_latency THEN r.latency_ms END)
+         FROM replies r LEFT JOIN models mo ON mo.id = r.model_id
+         WHERE r.model_id IS NOT NULL
+         GROUP BY r.model_id
+         ORDER BY responses DESC
+         LIMIT :limit",
+        replies_cte()
+    ))?;
+    let models = stmt
+        .query_map(
+            named_params! {
+                ":from": from,
+                ":to": to,
+                ":max_latency": MAX_LATENCY_MS,
+                ":limit": limit,
+            },
+            |row| {
+                Ok(ModelUsage {
+                    model_id: row.get(0)?,
+                    model_name: row.get(1)?,
+                    responses: row.get(2)?,
+                    avg_response_chars: row.get(3)?,
+                    avg_latency_ms: row.get(4)?,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(models)
+}
+
+fn query_heaviest_conversations(
+    conn: &Connection,
+    from: &Option<String>,
+    to: &Option<String>,
+    limit: u32,
+) -> AppResult<Vec<ConversationUsage>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT c.id, c.title, COUNT(*) AS messages, COALESCE(SUM(length(m.content)), 0) AS chars
+         FROM messages m JOIN conversations c ON c.id = m.conversation_id
+         WHERE {}
+         GROUP BY c.id
+         ORDER BY messages DESC, chars DESC
+         LIMIT :limit",
+        RANGE_FILTER
+    ))?;
+    let conversations = stmt
+        .query_map(
+            named_params! { ":from": from, ":to": to, ":limit": limit },
+            |row| {
+                Ok(ConversationUsage {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    messages: row.get(2)?,
+                    total_chars: row.get(3)?,
+                })
+            },
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(conversations)
+}
+
+/// 统计 `from` 到 `to`（含）之间的使用情况，日期为空表示不限制；
+/// `limit` 控制模型和会话排行的条数
+#[tauri::command]
+pub async fn get_usage_stats(
+    db: State<'_, Database>,
+    from: Option<String>,
+    to: Option<String>,
+    limit: Option<u32>,
+) -> AppResult<UsageStats> {
+    let (from_bound, to_bound) = parse_range(from.as_deref(), to.as_deref())?;
+    let limit = limit.unwrap_or(DEFAULT_TOP_LIMIT).clamp(1, MAX_TOP_LIMIT);
+
+    db.run(move |conn| {
+        Ok(UsageStats {
+            totals: query_totals(conn, &from_bound, &to_bound)?,
+            daily: query_daily(conn, &from_bound, &to_bound)?,
+            top_models: query_top_models(conn, &from_bound, &to_bound, limit)?,
+            heaviest_conversations: query_heaviest_conversations(
+                conn,
+                &from_bound,
+                &to_bound,
+                limit,
+            )?,
+            from,
+            to,
+        })
+    })
+    .await
+}
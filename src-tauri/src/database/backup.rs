@@ -0,0 +1,432 @@
+//! 数据库备份、恢复与自动快照
+//!
+//! 备份使用 SQLite 在线备份 API，不需要停止写入；结果以 gzip 压缩保存。
+//! 加密数据库的备份使用相同密码加密。
+
+use super::encryption::is_encrypted_file;
+use super::secrets::{self, Vault};
+use super::{create_schema, get_workspace_dir, Database};
+use crate::error::{AppError, AppResult};
+use crate::settings::Settings;
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use rusqlite::backup::Backup;
+use rusqlite::Connection;
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::{self, BufReader, BufWriter};
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+const BACKUP_PREFIX: &str = "chat_history";
+const SNAPSHOT_PREFIX: &str = "snapshot";
+const BACKUP_EXTENSION: &str = ".db.gz";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const BACKUP_DIR: &str = "backups";
+const SNAPSHOT_DIR: &str = "snapshots";
+const BACKUP_STEP_PAGES: std::os::raw::c_int = 256;
+const BACKUP_STEP_PAUSE: Duration = Duration::from_millis(5);
+// 调度器检查快照是否到期的间隔
+const SCHEDULER_TICK: Duration = Duration::from_secs(10 * 60);
+const REQUIRED_TABLES: [&str; 4] = ["conversations", "messages", "providers", "models"];
+/// 完整性检查发现问题时发送，载荷为检查报告
+pub const INTEGRITY_FAILED_EVENT: &str = "database://integrity-failed";
+/// 自动快照或快照前的完整性检查出错时发送，载荷为错误信息
+pub const SNAPSHOT_FAILED_EVENT: &str = "database://snapshot-failed";
+
+#[derive(Serialize, Debug, Clone)]
+pub struct BackupInfo {
+    pub path: String,
+    pub size: u64,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct IntegrityReport {
+    pub ok: bool,
+    pub problems: Vec<String>,
+    pub checked_at: String,
+}
+
+/// 启动时完整性检查的结果
+#[derive(Default)]
+pub struct IntegrityState {
+    report: Mutex<Option<IntegrityReport>>,
+}
+
+impl IntegrityState {
+    /// 切换到另一个数据库后清除缓存的结果，后台任务会重新检查
+    pub(crate) fn reset(&self) {
+        *self.report.lock().unwrap() = None;
+    }
+}
+
+fn check_integrity(conn: &Connection, quick: bool) -> AppResult<IntegrityReport> {
+    let pragma = if quick {
+        "PRAGMA quick_check"
+    } else {
+        "PRAGMA integrity_check"
+    };
+    let mut stmt = conn.prepare(pragma)?;
+    let problems = stmt
+        .query_map([], |row| row.get::<_, String>(0))?
+        .collect::<Result<Vec<_>, _>>()?
+        .into_iter()
+        .filter(|line| line != "ok")
+        .collect::<Vec<_>>();
+
+    Ok(IntegrityReport {
+        ok: problems.is_empty(),
+        problems,
+        checked_at: chrono::Utc::now().to_rfc3339(),
+    })
+}
+
+fn sidecar_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+fn backup_file_name(prefix: &str) -> String {
+    format!(
+        "{}-{}{}",
+        prefix,
+        chrono::Local::now().format(TIMESTAMP_FORMAT),
+        BACKUP_EXTENSION
+    )
+}
+
+fn backup_info(path: &Path) -> AppResult<BackupInfo> {
+    let metadata = fs::metadata(path)?;
+    let created_at = metadata
+        .modified()
+        .map(|time| chrono::DateTime::<chrono::Utc>::from(time).to_rfc3339())
+        .unwrap_or_default();
+    Ok(BackupInfo {
+        path: path.to_string_lossy().into_owned(),
+        size: metadata.len(),
+        created_at,
+    })
+}
+
+/// 通过在线备份 API 复制到临时文件，再压缩为最终的备份文件
+fn write_backup(source: &Connection, passphrase: Option<&str>, output: &Path) -> AppResult<()> {
+    if let Some(parent) = output.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp = sidecar_path(output, ".tmp");
+    let _ = fs::remove_file(&temp);
+
+    let result = (|| {
+        {
+            let mut dest = Connection::open(&temp)?;
+            if let Some(passphrase) = passphrase {
+                dest.pragma_update(None, "key", passphrase)?;
+            }
+            let backup = Backup::new(source, &mut dest)?;
+            backup
+                .run_to_completion(BACKUP_STEP_PAGES, BACKUP_STEP_PAUSE, None)
+                .map_err(|e| AppError::database("备份数据库失败").with_details(e))?;
+        }
+
+        let mut reader = BufReader::new(File::open(&temp)?);
+        let writer = BufWriter::new(File::create(output)?);
+        let mut encoder = GzEncoder::new(writer, Compression::default());
+        io::copy(&mut reader, &mut encoder)?;
+        encoder.finish()?;
+        Ok(())
+    })();
+
+    let _ = fs::remove_file(&temp);
+    if result.is_err() {
+        let _ = fs::remove_file(output);
+    }
+    result
+}
+
+/// 解压备份到临时文件；未压缩的 .db 文件直接复制
+fn unpack_backup(source: &Path, temp: &Path) -> AppResult<()> {
+    let is_gzip = source.to_string_lossy().to_lowercase().ends_with(".gz");
+
+    if is_gzip {
+        let mut decoder = GzDecoder::new(BufReader::new(
+            File::open(source).map_err(|e| AppError::io("无法打开备份文件").with_details(e))?,
+        ));
+        let mut writer = BufWriter::new(File::create(temp)?);
+        io::copy(&mut decoder, &mut writer)
+            .map_err(|e| AppError::invalid_input("备份文件已损坏").with_details(e))?;
+    } else {
+        fs::copy(source, temp).map_err(|e| AppError::io("无法读取备份文件").with_details(e))?;
+    }
+    Ok(())
+}
+
+/// 确认备份文件能打开、通过完整性检查且包含必需的表
+fn open_validated_backup(path: &Path, passphrase: Option<&str>) -> AppResult<Connection> {
+    let conn = Connection::open(path)?;
+    match (is_encrypted_file(path), passphrase) {
+        (true, Some(passphrase)) => conn.pragma_update(None, "key", passphrase)?,
+        (true, None) => {
+            return Err(AppError::invalid_input(
+                "备份文件已加密，请先使用相同密码启用数据库加密",
+            ))
+        }
+        (false, Some(_)) => {
+            return Err(AppError::invalid_input(
+                "备份文件未加密，无法恢复到加密数据库",
+            ))
+        }
+        (false, None) => {}
+    }
+
+    let report = check_integrity(&conn, false)
+        .map_err(|_| AppError::locked("备份文件无法读取或密码不匹配"))?;
+    if !report.ok {
+        return Err(AppError::invalid_input(format!(
+            "备份文件未通过完整性检查: {}",
+            report.problems.join("; ")
+        )));
+    }
+
+    for table in REQUIRED_TABLES {
+        let exists: bool = conn.query_row(
+            "SELECT EXISTS(SELECT 1 FROM sqlite_master WHERE type = 'table' AND name = ?1)",
+            [table],
+            |row| row.get(0),
+        )?;
+        if !exists {
+            return Err(AppError::invalid_input(format!(
+                "备份文件缺少数据表: {}",
+                table
+            )));
+        }
+    }
+    Ok(conn)
+}
+
+fn restore_into(
+    live: &mut Connection,
+    passphrase: Option<&str>,
+    source: &Path,
+    temp: &Path,
+) -> AppResult<()> {
+    let _ = fs::remove_file(temp);
+    let result = (|| {
+        unpack_backup(source, temp)?;
+        let backup_conn = open_validated_backup(temp, passphrase)?;
+        {
+            let backup = Backup::new(&backup_conn, live)?;
+            backup
+                .run_to_completion(BACKUP_STEP_PAGES, BACKUP_STEP_PAUSE, None)
+                .map_err(|e| AppError::database("恢复数据库失败").with_details(e))?;
+        }
+        // 旧版本的备份可能缺少新增的表和列
+        create_schema(live)
+    })();
+    let _ = fs::remove_file(temp);
+    result
+}
+
+fn list_backup_files(directory: &Path, prefix: &str) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(directory)
+        .map(|entries| {
+            entries
+                .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+                .filter(|path| {
+                    path.file_name()
+                        .map(|name| name.to_string_lossy())
+                        .is_some_and(|name| {
+                            name.starts_with(prefix) && name.ends_with(BACKUP_EXTENSION)
+                        })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    // 文件名中的时间戳可以直接按字典序排序，最新的在前
+    files.sort();
+    files.reverse();
+    files
+}
+
+fn snapshot_time(path: &Path) -> Option<chrono::NaiveDateTime> {
+    let name = path.file_name()?.to_string_lossy().into_owned();
+    let stamp = name
+        .strip_prefix(SNAPSHOT_PREFIX)?
+        .strip_prefix('-')?
+        .strip_suffix(BACKUP_EXTENSION)?
+        .to_string();
+    chrono::NaiveDateTime::parse_from_str(&stamp, TIMESTAMP_FORMAT).ok()
+}
+
+fn snapshot_due(directory: &Path, interval_hours: u32) -> bool {
+    let latest = list_backup_files(directory, SNAPSHOT_PREFIX)
+        .first()
+        .and_then(|path| snapshot_time(path));
+    match latest {
+        Some(time) => {
+            chrono::Local::now().naive_local() - time
+                >= chrono::Duration::hours(interval_hours as i64)
+        }
+        None => true,
+    }
+}
+
+fn prune_snapshots(directory: &Path, keep: usize) {
+    for path in list_backup_files(directory, SNAPSHOT_PREFIX)
+        .into_iter()
+        .skip(keep.max(1))
+    {
+        let _ = fs::remove_file(path);
+    }
+}
+
+fn snapshot_dir<R: Runtime>(app: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(get_workspace_dir(app)?.join(SNAPSHOT_DIR))
+}
+
+async fn take_snapshot(db: &Database, directory: PathBuf, keep: usize) -> AppResult<BackupInfo> {
+    let passphrase = db.passphrase();
+    db.run(move |conn| {
+        let output = directory.join(backup_file_name(SNAPSHOT_PREFIX));
+        write_backup(conn, passphrase.as_deref(), &output)?;
+        prune_snapshots(&directory, keep);
+        backup_info(&output)
+    })
+    .await
+}
+
+/// 启动后台快照任务：数据库解锁后先做完整性检查，检查通过才按设置生成快照，
+/// 避免损坏的数据把旧的正常快照轮换掉
+pub fn start_snapshot_scheduler<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let db = app.state::<Database>();
+            let integrity = app.state::<IntegrityState>();
+            let policy = app.state::<Settings>().get().snapshots;
+
+            if db.is_unlocked() {
+                let checked = integrity.report.lock().unwrap().clone();
+                let report = match checked {
+                    Some(report) => Some(report),
+                    None => match db.run(|conn| check_integrity(conn, true)).await {
+                        Ok(report) => {
+                            if !report.ok {
+                                let _ = app.emit(INTEGRITY_FAILED_EVENT, report.clone());
+                            }
+                            *integrity.report.lock().unwrap() = Some(report.clone());
+                            Some(report)
+                        }
+                        Err(e) => {
+                            let _ = app.emit(SNAPSHOT_FAILED_EVENT, e);
+                            None
+                        }
+                    },
+                };
+
+                // 每次重新取快照目录，切换工作区后写入新工作区的目录
+                if policy.enabled && report.is_some_and(|report| report.ok) {
+                    let result = match snapshot_dir(&app) {
+                        Ok(directory) if snapshot_due(&directory, policy.interval_hours) => {
+                            take_snapshot(&db, directory, policy.keep).await.map(|_| ())
+                        }
+                        Ok(_) => Ok(()),
+                        Err(e) => Err(e),
+                    };
+                    if let Err(e) = result {
+                        let _ = app.emit(SNAPSHOT_FAILED_EVENT, e);
+                    }
+                }
+            }
+
+            tokio::time::sleep(SCHEDULER_TICK).await;
+        }
+    });
+}
+
+/// 备份到指定目录（默认当前工作区目录下的 backups），文件名带时间戳
+#[tauri::command]
+pub async fn backup_database<R: Runtime>(
+    app: AppHandle<R>,
+    db: State<'_, Database>,
+    directory: Option<String>,
+) -> AppResult<BackupInfo> {
+    let directory = match directory {
+        Some(directory) => PathBuf::from(directory),
+        None => get_workspace_dir(&app)?.join(BACKUP_DIR),
+    };
+    let passphrase = db.passphrase();
+
+    db.run(move |conn| {
+        let output = directory.join(backup_file_name(BACKUP_PREFIX));
+        write_backup(conn, passphrase.as_deref(), &output)?;
+        backup_info(&output)
+    })
+    .await
+}
+
+/// 从备份恢复，恢复前会自动为当前数据生成一份快照
+///
+/// 备份中的密钥参数可能与当前不同，恢复后按恢复的数据重新加载密钥库
+#[tauri::command]
+pub async fn restore_database<R: Runtime>(
+    app: AppHandle<R>,
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    integrity: State<'_, IntegrityState>,
+    settings: State<'_, Settings>,
+    path: String,
+) -> AppResult<()> {
+    let source = PathBuf::from(&path);
+    if !source.exists() {
+        return Err(AppError::not_found("备份文件不存在"));
+    }
+
+    let directory = snapshot_dir(&app)?;
+    take_snapshot(&db, directory, settings.get().snapshots.keep).await?;
+
+    let passphrase = db.passphrase();
+    let temp = sidecar_path(&db.path(), ".restoring");
+    db.run(move |conn| restore_into(conn, passphrase.as_deref(), &source, &temp))
+        .await?;
+    integrity.reset();
+
+    match secrets::prepare_vault(&db, &get_workspace_dir(&app)?).await {
+        Ok(prepared) => {
+            vault.replace_with(prepared);
+            Ok(())
+        }
+        Err(e) => {
+            vault.set_failed(&e);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub async fn list_snapshots<R: Runtime>(app: AppHandle<R>) -> AppResult<Vec<BackupInfo>> {
+    let directory = snapshot_dir(&app)?;
+    list_backup_files(&directory, SNAPSHOT_PREFIX)
+        .iter()
+        .map(|path| backup_info(path))
+        .collect()
+}
+
+/// 完整的数据库完整性检查，耗时比启动时的快速检查长
+#[tauri::command]
+pub async fn check_database_integrity(
+    db: State<'_, Database>,
+    integrity: State<'_, IntegrityState>,
+) -> AppResult<IntegrityReport> {
+    let report = db.run(|conn| check_integrity(conn, false)).await?;
+    *integrity.report.lock().unwrap() = Some(report.clone());
+    Ok(report)
+}
+
+#[tauri::command]
+pub fn get_integrity_report(integrity: State<'_, IntegrityState>) -> Option<IntegrityReport> {
+    integrity.report.lock().unwrap().clone()
+}
@@ -0,0 +1,199 @@
+//! 可选的整库加密（SQLCipher）
+//!
+//! 加密模式下数据库文件在启动时处于锁定状态，需要用户输入密码解锁。
+//! 明文库与加密库之间的迁移通过 `sqlcipher_export` 导出到新文件后整体替换。
+
+use super::secrets::{self, Vault};
+use super::{create_schema, open_pool, Database};
+use crate::error::{AppError, AppResult};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+const SQLITE_HEADER: &[u8; 16] = b"SQLite format 3\0";
+const MIN_PASSPHRASE_LEN: usize = 8;
+
+#[derive(Serialize, Debug)]
+pub struct DatabaseStatus {
+    pub encrypted: bool,
+    pub unlocked: bool,
+}
+
+/// 明文 SQLite 文件以固定文件头开始，加密后文件头是随机数据
+pub fn is_encrypted_file(path: &Path) -> bool {
+    let mut header = [0u8; 16];
+    match File::open(path).and_then(|mut file| file.read_exact(&mut header)) {
+        Ok(()) => &header != SQLITE_HEADER,
+        Err(_) => false,
+    }
+}
+
+/// 打开单个连接并校验密码，密码错误时 SQLCipher 会在首次读取时报错
+fn open_connection(path: &Path, passphrase: Option<&str>) -> AppResult<Connection> {
+    let conn = Connection::open(path)?;
+    if let Some(passphrase) = passphrase {
+        conn.pragma_update(None, "key", passphrase)?;
+    }
+    conn.query_row("SELECT count(*) FROM sqlite_master", [], |row| {
+        row.get::<_, i64>(0)
+    })
+    .map_err(|_| AppError::locked("数据库密码错误"))?;
+    Ok(conn)
+}
+
+fn validate_passphrase(passphrase: &str) -> AppResult<()> {
+    if passphrase.chars().count() < MIN_PASSPHRASE_LEN {
+        return Err(AppError::invalid_input(format!(
+            "数据库密码至少需要 {} 个字符",
+            MIN_PASSPHRASE_LEN
+        )));
+    }
+    Ok(())
+}
+
+fn sidecar_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// 将数据库导出为新的加密（或明文）文件，然后替换原文件
+fn export_and_replace(path: &Path, current: Option<&str>, target: Option<&str>) -> AppResult<()> {
+    let exported = sidecar_path(path, ".migrating");
+    let _ = fs::remove_file(&exported);
+
+    let conn = open_connection(path, current)?;
+    let result = (|| {
+        conn.execute(
+            "ATTACH DATABASE ?1 AS migrated KEY ?2",
+            params![exported.to_string_lossy(), target.unwrap_or_default()],
+        )?;
+        conn.query_row("SELECT sqlcipher_export('migrated')", [], |_| Ok(()))?;
+        conn.execute("DETACH DATABASE migrated", [])?;
+        Ok::<_, rusqlite::Error>(())
+    })();
+    drop(conn);
+
+    if let Err(e) = result {
+        let _ = fs::remove_file(&exported);
+        return Err(AppError::database("导出数据库失败").with_details(e));
+    }
+
+    // 原文件先改名保留，新文件就位后再删除，避免中途失败丢失数据
+    let previous = sidecar_path(path, ".previous");
+    fs::rename(path, &previous).map_err(|e| AppError::io("替换数据库文件失败").with_details(e))?;
+    if let Err(e) = fs::rename(&exported, path) {
+        let _ = fs::rename(&previous, path);
+        return Err(AppError::io("替换数据库文件失败").with_details(e));
+    }
+
+    let _ = fs::remove_file(&previous);
+    for suffix in ["-wal", "-shm"] {
+        let _ = fs::remove_file(sidecar_path(path, suffix));
+    }
+    Ok(())
+}
+
+/// 断开连接池后迁移数据库文件，失败时按原配置重新连接
+///
+/// 迁移期间持有独占锁：执行中的操作全部结束、连接全部关闭后才导出，
+/// 新的操作等迁移完成后使用新的连接池，不会写入即将被替换的旧文件
+async fn migrate(db: &Database, target: Option<String>) -> AppResult<()> {
+    let _exclusive = db.exclusive().await;
+    let path = db.path();
+    let current = db.passphrase();
+    db.detach();
+
+    let result = {
+        let path = path.clone();
+        let current = current.clone();
+        let target = target.clone();
+        tauri::async_runtime::spawn_blocking(move || {
+            export_and_replace(&path, current.as_deref(), target.as_deref())?;
+            open_pool(&path, target)
+        })
+        .await?
+    };
+
+    match result {
+        Ok(pool) => {
+            db.attach(pool, target);
+            Ok(())
+        }
+        Err(e) => {
+            db.attach(open_pool(&path, current.clone())?, current);
+            Err(e)
+        }
+    }
+}
+
+#[tauri::command]
+pub fn get_database_status(db: State<'_, Database>) -> DatabaseStatus {
+    DatabaseStatus {
+        // 只有加密数据库会处于锁定状态
+        encrypted: db.passphrase().is_some() || !db.is_unlocked(),
+        unlocked: db.is_unlocked(),
+    }
+}
+
+#[tauri::command]
+pub async fn unlock_database(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    passphrase: String,
+) -> AppResult<()> {
+    if db.is_unlocked() {
+        return Ok(());
+    }
+
+    let path = db.path();
+    let key = passphrase.clone();
+    let pool = tauri::async_runtime::spawn_blocking(move || {
+        open_connection(&path, Some(&key))?;
+        let pool = open_pool(&path, Some(key))?;
+        let conn = pool.get()?;
+        create_schema(&conn)?;
+        drop(conn);
+        Ok::<_, AppError>(pool)
+    })
+    .await??;
+
+    db.attach(pool, Some(passphrase));
+    secrets::load_vault(&db, &vault).await
+}
+
+/// 将现有的明文数据库迁移为加密数据库
+#[tauri::command]
+pub async fn enable_database_encryption(
+    db: State<'_, Database>,
+    passphrase: String,
+) -> AppResult<()> {
+    if !db.is_unlocked() {
+        return Err(AppError::locked("数据库已加密，请先解锁"));
+    }
+    if db.passphrase().is_some() {
+        return Err(AppError::conflict("数据库已经启用加密"));
+    }
+    validate_passphrase(&passphrase)?;
+
+    migrate(&db, Some(passphrase)).await
+}
+
+/// 将加密数据库还原为明文，需要再次输入当前密码确认
+#[tauri::command]
+pub async fn disable_database_encryption(
+    db: State<'_, Database>,
+    passphrase: String,
+) -> AppResult<()> {
+    match db.passphrase() {
+        None if db.is_unlocked() => return Err(AppError::conflict("数据库未启用加密")),
+        None => return Err(AppError::locked("数据库已加密，请先解锁")),
+        Some(current) if current != passphrase => return Err(AppError::locked("数据库密码错误")),
+        Some(_) => {}
+    }
+
+    migrate(&db, None).await
+}
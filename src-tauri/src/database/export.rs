@@ -0,0 +1,460 @@
+//! 会话导出为 Markdown、JSON 和单文件 HTML
+
+use super::{attach_files, map_conversation, map_message, Conversation, Database};
+use crate::error::{AppError, AppResult};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+/// JSON 导出格式的版本号，导入时据此识别
+pub const EXPORT_VERSION: u32 = 1;
+
+#[derive(Deserialize, Debug, Clone, Copy)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Markdown,
+    Json,
+    Html,
+}
+
+impl ExportFormat {
+    fn extension(&self) -> &'static str {
+        match self {
+            ExportFormat::Markdown => "md",
+            ExportFormat::Json => "json",
+            ExportFormat::Html => "html",
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ConversationExport {
+    pub version: u32,
+    pub exported_at: String,
+    pub conversation: Conversation,
+    pub models: Vec<ExportedModel>,
+    pub messages: Vec<ExportedMessage>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportedModel {
+    pub id: String,
+    pub name: String,
+    pub model_key: String,
+    pub provider: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportedMessage {
+    pub id: String,
+    pub role: String,
+    pub content: String,
+    pub timestamp: String,
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<ExportedAttachment>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ExportedAttachment {
+    pub file_name: String,
+    pub mime_type: String,
+    pub file_path: String,
+    /// Base64 编码的文件内容，原文件不存在时为空
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+}
+
+fn load_export(conn: &Connection, conversation_id: &str) -> AppResult<ConversationExport> {
+    let conversation = conn
+        .query_row(
+            "SELECT id, title, summary, created_at, updated_at, is_pinned, folder_id FROM conversations WHERE id = ?1",
+            params![conversation_id],
+            map_conversation,
+        )
+        .optional()?
+        .ok_or_else(|| AppError::not_found(format!("会话不存在: {}", conversation_id)))?;
+
+    let mut stmt = conn
+        .prepare("SELECT id, conversation_id, role, content, timestamp, model_id FROM messages WHERE conversation_id = ?1 ORDER BY timestamp ASC")?;
+    let mut messages = stmt
+        .query_map(params![conversation_id], map_message)?
+        .collect::<Result<Vec<_>, _>>()?;
+    attach_files(conn, conversation_id, &mut messages)?;
+
+    let mut stmt = conn.prepare(
+        "SELECT m.id, m.name, m.model_key, p.name
+             FROM models m
+             JOIN providers p ON m.provider_id = p.id
+             WHERE m.id IN (SELECT model_id FROM messages WHERE conversation_id = ?1)",
+    )?;
+    let models = stmt
+        .query_map(params![conversation_id], |row| {
+            Ok(ExportedModel {
+                id: row.get(0)?,
+                name: row.get(1)?,
+                model_key: row.get(2)?,
+                provider: row.get(3)?,
+            })
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let messages = messages
+        .into_iter()
+        .map(|message| ExportedMessage {
+            id: message.id,
+            role: message.role,
+            content: message.content,
+            timestamp: message.timestamp,
+            model_id: message.model_id,
+            attachments: message
+                .attachments
+                .into_iter()
+                .map(|attachment| ExportedAttachment {
+                    file_name: attachment.file_name,
+                    mime_type: attachment.mime_type,
+                    file_path: attachment.file_path,
+                    data: None,
+                })
+                .collect(),
+        })
+        .collect();
+
+    Ok(ConversationExport {
+        version: EXPORT_VERSION,
+        exported_at: chrono::Utc::now().to_rfc3339(),
+        conversation,
+        models,
+        messages,
+    })
+}
+
+/// 读取附件内容以便嵌入 JSON 和 HTML，找不到的文件保留路径
+fn embed_attachments(export: &mut ConversationExport) {
+    for message in &mut export.messages {
+        for attachment in &mut message.attachments {
+            if let Ok(bytes) = fs::read(&attachment.file_path) {
+                attachment.data = Some(BASE64.encode(bytes));
+            }
+        }
+    }
+}
+
+fn role_label(role: &str) -> &str {
+    match role {
+        "user" => "用户",
+        "assistant" => "助手",
+        "system" => "系统",
+        other => other,
+    }
+}
+
+fn format_timestamp(timestamp: &str) -> String {
+    chrono::DateTime::parse_from_rfc3339(timestamp)
+        .map(|time| {
+            time.with_timezone(&chrono::Local)
+                .format("%Y-%m-%d %H:%M:%S")
+                .to_string()
+        })
+        .unwrap_or_else(|_| timestamp.to_string())
+}
+
+fn model_names(export: &ConversationExport) -> HashMap<&str, &str> {
+    export
+        .models
+        .iter()
+        .map(|model| (model.id.as_str(), model.name.as_str()))
+        .collect()
+}
+
+fn is_image(attachment: &ExportedAttachment) -> bool {
+    attachment.mime_type.starts_with("image/")
+}
+
+/// data URL 中使用的 MIME 类型。附件信息可能来自导入的文件，
+/// 只接受 `type/subtype` 形式，其他值一律按二进制文件处理
+fn data_mime_type(attachment: &ExportedAttachment) -> String {
+    let is_token = |part: &str| {
+        !part.is_empty()
+            && part
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "!#$&^_.+-".contains(c))
+    };
+    let mime = match attachment.mime_type.split_once('/') {
+        Some((kind, subtype)) if is_token(kind) && is_token(subtype) => &attachment.mime_type,
+        _ => "application/octet-stream",
+    };
+    escape_html(mime)
+}
+
+fn render_markdown(export: &ConversationExport) -> String {
+    let names = model_names(export);
+    let mut out = String::new();
+
+    let _ = writeln!(out, "# {}\n", export.conversation.title);
+    let _ = writeln!(
+        out,
+        "- 创建时间: {}",
+        format_timestamp(&export.conversation.created_at)
+    );
+    let _ = writeln!(
+        out,
+        "- 更新时间: {}",
+        format_timestamp(&export.conversation.updated_at)
+    );
+    if !export.models.is_empty() {
+        let models: Vec<String> = export
+            .models
+            .iter()
+            .map(|model| format!("{} ({})", model.name, model.provider))
+            .collect();
+        let _ = writeln!(out, "- 使用模型: {}", models.join(", "));
+    }
+
+    for message in &export.messages {
+        let _ = write!(
+            out,
+            "\n## {} · {}",
+            role_label(&message.role),
+            format_timestamp(&message.timestamp)
+        );
+        if let Some(model_id) = &message.model_id {
+            let _ = write!(
+                out,
+                " · {}",
+                names.get(model_id.as_str()).copied().unwrap_or(model_id)
+            );
+        }
+        let _ = writeln!(out, "\n\n{}", message.content.trim_end());
+
+        if !message.attachments.is_empty() {
+            out.push('\n');
+            for attachment in &message.attachments {
+                let prefix = if is_image(attachment) { "!" } else { "" };
+                let _ = writeln!(
+                    out,
+                    "{}[{}](<{}>)",
+                    prefix, attachment.file_name, attachment.file_path
+                );
+            }
+        }
+    }
+
+    out
+}
+
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+const HTML_STYLE: &str = "
+body { font-family: -apple-system, 'PingFang SC', 'Microsoft YaHei', sans-serif; background: #f5f5f7; color: #1d1d1f; margin: 0; }
+main { max-width: 860px; margin: 0 auto; padding: 32px 20px; }
+h1 { font-size: 24px; margin-bottom: 8px; }
+.meta { color: #6e6e73; font-size: 13px; margin-bottom: 24px; }
+.message { background: #fff; border-radius: 12px; padding: 16px 20px; margin-bottom: 16px; box-shadow: 0 1px 3px rgba(0,0,0,.08); }
+.message.user { background: #e8f0fe; }
+.message header { font-size: 12px; color: #6e6e73; margin-bottom: 8px; }
+.message header strong { color: #1d1d1f; margin-right: 8px; }
+.content { white-space: pre-wrap; word-break: break-word; line-height: 1.6; }
+.attachments { margin-top: 12px; }
+.attachments img { max-width: 100%; border-radius: 8px; display: block; margin-top: 8px; }
+.attachments a { font-size: 13px; }
+";
+
+fn render_html(export: &ConversationExport) -> String {
+    let names = model_names(export);
+    let title = escape_html(&export.conversation.title);
+    let mut out = String::new();
+
+    let _ = write!(
+        out,
+        "<!DOCTYPE html>\n<html lang=\"zh-CN\">\n<head>\n<meta charset=\"utf-8\">\n<title>{}</title>\n<style>{}</style>\n</head>\n<body>\n<main>\n<h1>{}</h1>\n",
+        title, HTML_STYLE, title
+    );
+
+    let mut meta = format!(
+        "创建于 {} · 更新于 {}",
+        format_timestamp(&export.conversation.created_at),
+        format_timestamp(&export.conversation.updated_at)
+    );
+    if !export.models.is_empty() {
+        let models: Vec<&str> = export.models.iter().map(|m| m.name.as_str()).collect();
+        let _ = write!(meta, " · 模型: {}", models.join(", "));
+    }
+    let _ = writeln!(out, "<div class=\"meta\">{}</div>", escape_html(&meta));
+
+    for message in &export.messages {
+        let _ = write!(
+            out,
+            "<section class=\"message {}\">\n<header><strong>{}</strong>{}",
+            escape_html(&message.role),
+            escape_html(role_label(&message.role)),
+            escape_html(&format_timestamp(&message.timestamp))
+        );
+        if let Some(model_id) = &message.model_id {
+            let name = names.get(model_id.as_str()).copied().unwrap_or(model_id);
+            let _ = write!(out, " · {}", escape_html(name));
+        }
+        let _ = writeln!(
+            out,
+            "</header>\n<div class=\"content\">{}</div>",
+            escape_html(&message.content)
+        );
+
+        if !message.attachments.is_empty() {
+            out.push_str("<div class=\"attachments\">\n");
+            for attachment in &message.attachments {
+                let name = escape_html(&attachment.file_name);
+                let mime = data_mime_type(attachment);
+                match &attachment.data {
+                    Some(data) if is_image(attachment) => {
+                        let _ = writeln!(
+                            out,
+                            "<img alt=\"{}\" src=\"data:{};base64,{}\">",
+                            name, mime, data
+                        );
+                    }
+                    Some(data) => {
+                        let _ = writeln!(
+                            out,
+                            "<div><a download=\"{}\" href=\"data:{};base64,{}\">{}</a></div>",
+                            name, mime, data, name
+                        );
+                    }
+                    None => {
+                        let _ = writeln!(out, "<div>{} (文件缺失)</div>", name);
+                    }
+                }
+            }
+            out.push_str("</div>\n");
+        }
+        out.push_str("</section>\n");
+    }
+
+    out.push_str("</main>\n</body>\n</html>\n");
+    out
+}
+
+fn write_export(
+    mut export: ConversationExport,
+    format: ExportFormat,
+    path: &Path,
+) -> AppResult<()> {
+    let content = match format {
+        ExportFormat::Markdown => render_markdown(&export),
+        ExportFormat::Json => {
+            embed_attachments(&mut export);
+            serde_json::to_string_pretty(&export)?
+        }
+        ExportFormat::Html => {
+            embed_attachments(&mut export);
+            render_html(&export)
+        }
+    };
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::write(path, content).map_err(|e| AppError::io("写入导出文件失败").with_details(e))
+}
+
+/// 根据标题生成安全的文件名，并附加 ID 前缀避免重名
+fn export_file_name(conversation: &Conversation, format: ExportFormat) -> String {
+    let title: String = conversation
+        .title
+        .chars()
+        .map(|c| {
+            if c.is_control() || r#"\/:*?"<>|"#.contains(c) {
+                '_'
+            } else {
+                c
+            }
+        })
+        .take(50)
+        .collect();
+    let title = title.trim();
+    let title = if title.is_empty() {
+        "conversation"
+    } else {
+        title
+    };
+    let short_id: String = conversation.id.chars().take(8).collect();
+    format!("{}-{}.{}", title, short_id, format.extension())
+}
+
+#[tauri::command]
+pub async fn export_conversation(
+    db: State<'_, Database>,
+    conversation_id: String,
+    format: ExportFormat,
+    path: String,
+) -> AppResult<String> {
+    let export = db
+        .run(move |conn| load_export(conn, &conversation_id))
+        .await?;
+
+    let output = PathBuf::from(path);
+    let written = output.clone();
+    tauri::async_runtime::spawn_blocking(move || write_export(export, format, &output)).await??;
+
+    Ok(written.to_string_lossy().into_owned())
+}
+
+/// 批量导出到目录，每个会话一个文件；未指定会话时导出全部
+#[tauri::command]
+pub async fn export_conversations(
+    db: State<'_, Database>,
+    conversation_ids: Vec<String>,
+    format: ExportFormat,
+    directory: String,
+) -> AppResult<Vec<String>> {
+    let exports = db
+        .run(move |conn| {
+            let ids = if conversation_ids.is_empty() {
+                let mut stmt = conn
+                    .prepare("SELECT id FROM conversations WHERE deleted_at IS NULL ORDER BY updated_at DESC")?;
+                let ids = stmt
+                    .query_map([], |row| row.get::<_, String>(0))?
+                    .collect::<Result<Vec<_>, _>>()?;
+                ids
+            } else {
+                conversation_ids
+            };
+
+            ids.iter()
+                .map(|id| load_export(conn, id))
+                .collect::<Result<Vec<_>, _>>()
+        })
+        .await?;
+
+    let directory = PathBuf::from(directory);
+    tauri::async_runtime::spawn_blocking(move || {
+        let mut written = Vec::new();
+        for export in exports {
+            let path = directory.join(export_file_name(&export.conversation, format));
+            write_export(export, format, &path)?;
+            written.push(path.to_string_lossy().into_owned());
+        }
+        Ok(written)
+    })
+    .await?
+}
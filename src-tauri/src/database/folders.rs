@@ -0,0 +1,233 @@
+//! 会话文件夹，支持多级嵌套
+//!
+//! 删除文件夹时其中的会话和子文件夹上移到父级，不会级联删除。
+
+use super::{ensure_column, Database};
+use crate::error::{AppError, AppResult};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::State;
+use uuid::Uuid;
+
+#[derive(Serialize, Debug)]
+pub struct Folder {
+    pub id: String,
+    pub name: String,
+    pub parent_id: Option<String>,
+    pub created_at: String,
+}
+
+pub(crate) fn create_tables(conn: &Connection) -> AppResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS folders (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            parent_id TEXT,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY(parent_id) REFERENCES folders(id) ON DELETE SET NULL
+        )",
+        [],
+    )?;
+
+    ensure_column(
+        conn,
+        "conversations",
+        "folder_id",
+        "TEXT REFERENCES folders(id) ON DELETE SET NULL",
+    )?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_conversations_folder ON conversations(folder_id)",
+        [],
+    )?;
+
+    Ok(())
+}
+
+fn validate_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid_input("文件夹名称不能为空"));
+    }
+    Ok(name.to_string())
+}
+
+fn ensure_folder_exists(conn: &Connection, folder_id: &str) -> AppResult<()> {
+    let exists: bool = conn.query_row(
+        "SELECT EXISTS(SELECT 1 FROM folders WHERE id = ?1)",
+        params![folder_id],
+        |row| row.get(0),
+    )?;
+    if !exists {
+        return Err(AppError::not_found(format!("文件夹不存在: {}", folder_id)));
+    }
+    Ok(())
+}
+
+/// 判断 `folder_id` 是否是 `target_id` 本身或其祖先，用于防止移动后形成环
+fn is_self_or_ancestor(conn: &Connection, folder_id: &str, target_id: &str) -> AppResult<bool> {
+    conn.query_row(
+        "WITH RECURSIVE ancestors(id) AS (
+             SELECT ?2
+             UNION
+             SELECT f.parent_id FROM folders f JOIN ancestors a ON f.id = a.id
+             WHERE f.parent_id IS NOT NULL
+         )
+         SELECT EXISTS(SELECT 1 FROM ancestors WHERE id = ?1)",
+        params![folder_id, target_id],
+        |row| row.get(0),
+    )
+    .map_err(AppError::from)
+}
+
+#[tauri::command]
+pub async fn create_folder(
+    db: State<'_, Database>,
+    name: String,
+    parent_id: Option<String>,
+) -> AppResult<Folder> {
+    let name = validate_name(&name)?;
+    db.run(move |conn| {
+        if let Some(parent_id) = &parent_id {
+            ensure_folder_exists(conn, parent_id)?;
+        }
+
+        let folder = Folder {
+            id: Uuid::new_v4().to_string(),
+            name,
+            parent_id,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        conn.execute(
+            "INSERT INTO folders (id, name, parent_id, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![folder.id, folder.name, folder.parent_id, folder.created_at],
+        )?;
+
+        Ok(folder)
+    })
+    .await
+}
+
+/// 返回所有文件夹的平铺列表，由前端按 `parent_id` 组装成树
+#[tauri::command]
+pub async fn get_folders(db: State<'_, Database>) -> AppResult<Vec<Folder>> {
+    db.run(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, name, parent_id, created_at FROM folders ORDER BY name COLLATE NOCASE ASC",
+        )?;
+        let folders = stmt
+            .query_map([], |row| {
+                Ok(Folder {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    parent_id: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(folders)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn rename_folder(
+    db: State<'_, Database>,
+    folder_id: String,
+    name: String,
+) -> AppResult<()> {
+    let name = validate_name(&name)?;
+    db.run(move |conn| {
+        let updated = conn.execute(
+            "UPDATE folders SET name = ?1 WHERE id = ?2",
+            params![name, folder_id],
+        )?;
+        if updated == 0 {
+            return Err(AppError::not_found(format!("文件夹不存在: {}", folder_id)));
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// 移动文件夹到新的父级，`parent_id` 为空时移到顶层
+#[tauri::command]
+pub async fn move_folder(
+    db: State<'_, Database>,
+    folder_id: String,
+    parent_id: Option<String>,
+) -> AppResult<()> {
+    db.run(move |conn| {
+        ensure_folder_exists(conn, &folder_id)?;
+        if let Some(parent_id) = &parent_id {
+            ensure_folder_exists(conn, parent_id)?;
+            if is_self_or_ancestor(conn, &folder_id, parent_id)? {
+                return Err(AppError::invalid_input(
+                    "不能将文件夹移动到自身或其子文件夹中",
+                ));
+            }
+        }
+
+        conn.execute(
+            "UPDATE folders SET parent_id = ?1 WHERE id = ?2",
+            params![parent_id, folder_id],
+        )?;
+        Ok(())
+    })
+    .await
+}
+
+/// 删除文件夹，其中的会话和子文件夹移到被删除文件夹的父级
+#[tauri::command]
+pub async fn delete_folder(db: State<'_, Database>, folder_id: String) -> AppResult<()> {
+    db.run(move |conn| {
+        let tx = conn.transaction()?;
+
+        let parent_id: Option<String> = tx
+            .query_row(
+                "SELECT parent_id FROM folders WHERE id = ?1",
+                params![folder_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::not_found(format!("文件夹不存在: {}", folder_id)))?;
+
+        tx.execute(
+            "UPDATE conversations SET folder_id = ?1 WHERE folder_id = ?2",
+            params![parent_id, folder_id],
+        )?;
+        tx.execute(
+            "UPDATE folders SET parent_id = ?1 WHERE parent_id = ?2",
+            params![parent_id, folder_id],
+        )?;
+        tx.execute("DELETE FROM folders WHERE id = ?1", params![folder_id])?;
+
+        tx.commit().map_err(AppError::from)
+    })
+    .await
+}
+
+/// 将会话移入文件夹，`folder_id` 为空时移出到顶层
+#[tauri::command]
+pub async fn move_conversation_to_folder(
+    db: State<'_, Database>,
+    conversation_id: String,
+    folder_id: Option<String>,
+) -> AppResult<()> {
+    db.run(move |conn| {
+        if let Some(folder_id) = &folder_id {
+            ensure_folder_exists(conn, folder_id)?;
+        }
+        let updated = conn.execute(
+            "UPDATE conversations SET folder_id = ?1 WHERE id = ?2",
+            params![folder_id, conversation_id],
+        )?;
+        if updated == 0 {
+            return Err(AppError::not_found(format!(
+                "会话不存在: {}",
+                conversation_id
+            )));
+        }
+        Ok(())
+    })
+    .await
+}
@@ -0,0 +1,583 @@
+//! 术语表
+//!
+//! 术语按语言对保存，可设置是否区分大小写、是否保留原文不翻译。
+//! 翻译时只把原文中出现的术语交给模型，译文再逐条检查是否使用了规定译法。
+//! 语言为空表示适用于任意语言，例如不翻译的产品名。
+
+use super::Database;
+use crate::error::{AppError, AppResult, ErrorCode};
+use calamine::{open_workbook_auto, Reader};
+use regex::{Regex, RegexBuilder};
+use rusqlite::{named_params, params, Connection, OptionalExtension};
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use tauri::State;
+use uuid::Uuid;
+
+const TERM_COLUMNS: &str = "id, term, translation, source_language, target_language,
+    case_sensitive, do_not_translate, note, created_at, updated_at";
+const PAIR_FILTER: &str = "(:source = '' OR source_language IN (:source, ''))
+    AND (:target = '' OR target_language IN (:target, ''))";
+
+#[derive(Serialize, Debug, Clone)]
+pub struct GlossaryTerm {
+    pub id: String,
+    pub term: String,
+    /// 不翻译的术语为空
+    pub translation: String,
+    pub source_language: String,
+    pub target_language: String,
+    pub case_sensitive: bool,
+    pub do_not_translate: bool,
+    pub note: String,
+    pub created_at: String,
+    pub updated_at: String,
+}
+
+#[derive(Deserialize, Debug, Default)]
+#[serde(default)]
+pub struct GlossaryTermInput {
+    pub term: String,
+    pub translation: String,
+    pub source_language: String,
+    pub target_language: String,
+    pub case_sensitive: bool,
+    pub do_not_translate: bool,
+    pub note: String,
+}
+
+#[derive(Serialize, Debug, Default)]
+pub struct GlossaryImportReport {
+    pub imported: usize,
+    pub updated: usize,
+    pub skipped: usize,
+    pub errors: Vec<String>,
+}
+
+pub(crate) fn create_tables(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS glossary_terms (
+            id TEXT PRIMARY KEY,
+            term TEXT NOT NULL,
+            translation TEXT NOT NULL DEFAULT '',
+            source_language TEXT NOT NULL DEFAULT '',
+            target_language TEXT NOT NULL DEFAULT '',
+            case_sensitive BOOLEAN NOT NULL DEFAULT 0,
+            do_not_translate BOOLEAN NOT NULL DEFAULT 0,
+            note TEXT NOT NULL DEFAULT '',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            UNIQUE(source_language, target_language, term)
+        );
+        CREATE INDEX IF NOT EXISTS idx_glossary_terms_pair
+            ON glossary_terms(target_language, source_language);",
+    )
+    .map_err(AppError::from)
+}
+
+fn map_term(row: &rusqlite::Row) -> rusqlite::Result<GlossaryTerm> {
+    Ok(GlossaryTerm {
+        id: row.get(0)?,
+        term: row.get(1)?,
+        translation: row.get(2)?,
+        source_language: row.get(3)?,
+        target_language: row.get(4)?,
+        case_sensitive: row.get(5)?,
+        do_not_translate: row.get(6)?,
+        note: row.get(7)?,
+        created_at: row.get(8)?,
+        updated_at: row.get(9)?,
+    })
+}
+
+fn normalize_language(language: &str) -> String {
+    language.trim().to_lowercase()
+}
+
+impl GlossaryTermInput {
+    fn validate(self) -> AppResult<Self> {
+        let term = self.term.trim().to_string();
+        if term.is_empty() {
+            return Err(AppError::invalid_input("术语不能为空"));
+        }
+        let translation = if self.do_not_translate {
+            String::new()
+        } else {
+            let translation = self.translation.trim().to_string();
+            if translation.is_empty() {
+                return Err(AppError::invalid_input(format!(
+                    "请填写术语的译文: {}",
+                    term
+                )));
+            }
+            translation
+        };
+        Ok(Self {
+            term,
+            translation,
+            source_language: normalize_language(&self.source_language),
+            target_language: normalize_language(&self.target_language),
+            note: self.note.trim().to_string(),
+            ..self
+        })
+    }
+}
+
+fn unique_term_error(e: rusqlite::Error, term: &str) -> AppError {
+    match AppError::from(e) {
+        error if error.code == ErrorCode::Conflict => {
+            AppError::conflict(format!("术语已存在: {}", term))
+        }
+        error => error,
+    }
+}
+
+fn get_term(conn: &Connection, id: &str) -> AppResult<GlossaryTerm> {
+    conn.query_row(
+        &format!("SELECT {} FROM glossary_terms WHERE id = ?1", TERM_COLUMNS),
+        [id],
+        map_term,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found(format!("术语不存在: {}", id)))
+}
+
+/// 插入术语，同一语言对下已有相同术语时更新；返回是否为新术语
+fn upsert_term(conn: &Connection, input: &GlossaryTermInput, now: &str) -> AppResult<bool> {
+    let existing: Option<String> = conn
+        .query_row(
+            "SELECT id FROM glossary_terms
+             WHERE source_language = ?1 AND target_language = ?2 AND term = ?3",
+            params![input.source_language, input.target_language, input.term],
+            |row| row.get(0),
+        )
+        .optional()?;
+
+    match existing {
+        Some(id) => {
+            conn.execute(
+                "UPDATE glossary_terms SET translation = ?1, case_sensitive = ?2,
+                 do_not_translate = ?3, note = ?4, updated_at = ?5 WHERE id = ?6",
+                params![
+                    input.translation,
+                    input.case_sensitive,
+                    input.do_not_translate,
+                    input.note,
+                    now,
+                    id
+                ],
+            )?;
+            Ok(false)
+        }
+        None => {
+            insert_term(conn, &Uuid::new_v4().to_string(), input, now)?;
+            Ok(true)
+        }
+    }
+}
+
+fn insert_term(conn: &Connection, id: &str, input: &GlossaryTermInput, now: &str) -> AppResult<()> {
+    conn.execute(
+        "INSERT INTO glossary_terms
+            (id, term, translation, source_language, target_language, case_sensitive,
+             do_not_translate, note, created_at, updated_at)
+         VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?9)",
+        params![
+            id,
+            input.term,
+            input.translation,
+            input.source_language,
+            input.target_language,
+            input.case_sensitive,
+            input.do_not_translate,
+            input.note,
+            now
+        ],
+    )
+    .map_err(|e| unique_term_error(e, &input.term))?;
+    Ok(())
+}
+
+/// 以字母或数字开头、结尾的术语按整词匹配，中文等没有词边界的术语按子串匹配
+fn term_pattern(text: &str, case_sensitive: bool) -> Regex {
+    let word_edge = |c: Option<char>| c.is_some_and(|c| c.is_ascii_alphanumeric());
+    let start = if word_edge(text.chars().next()) {
+        r"\b"
+    } else {
+        ""
+    };
+    let end = if word_edge(text.chars().last()) {
+        r"\b"
+    } else {
+        ""
+    };
+    RegexBuilder::new(&format!("{}{}{}", start, regex::escape(text), end))
+        .case_insensitive(!case_sensitive)
+        .build()
+        .expect("术语规则无效")
+}
+
+struct CompiledTerm {
+    term: GlossaryTerm,
+    source: Regex,
+    /// 译文中应出现的内容：规定译法，或不翻译术语的原文
+    expected: Regex,
+}
+
+/// 某个语言对适用的术语
+pub(crate) struct Glossary {
+    terms: Vec<CompiledTerm>,
+}
+
+impl Glossary {
+    /// 源语言为空时加载所有源语言的术语
+    pub(crate) fn load(conn: &Connection, source: Option<&str>, target: &str) -> AppResult<Self> {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM glossary_terms WHERE {} ORDER BY length(term) DESC",
+            TERM_COLUMNS, PAIR_FILTER
+        ))?;
+        let terms = stmt
+            .query_map(
+                named_params! {
+                    ":source": normalize_language(source.unwrap_or_default()),
+                    ":target": normalize_language(target),
+                },
+                map_term,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(Self {
+            terms: terms
+                .into_iter()
+                .map(|term| {
+                    let expected = if term.do_not_translate {
+                        &term.term
+                    } else {
+                        &term.translation
+                    };
+                    CompiledTerm {
+                        source: term_pattern(&term.term, term.case_sensitive),
+                        expected: term_pattern(expected, term.case_sensitive),
+                        term,
+                    }
+                })
+                .collect(),
+        })
+    }
+
+    /// 需要原样保留的术语，用于占位符保护
+    pub(crate) fn protected_terms(&self) -> Vec<String> {
+        self.terms
+            .iter()
+            .filter(|t| t.term.do_not_translate)
+            .map(|t| t.term.term.clone())
+            .collect()
+    }
+
+    /// 在任一原文中出现的术语
+    pub(crate) fn matching<'a>(&'a self, texts: &[&str]) -> Vec<&'a GlossaryTerm> {
+        self.terms
+            .iter()
+            .filter(|t| texts.iter().any(|text| t.source.is_match(text)))
+            .map(|t| &t.term)
+            .collect()
+    }
+
+    /// 检查译文是否使用了原文中术语的规定译法
+    pub(crate) fn violations(&self, source: &str, translation: &str) -> Vec<String> {
+        self.terms
+            .iter()
+            .filter(|t| t.source.is_match(source) && !t.expected.is_match(translation))
+            .map(|t| {
+                if t.term.do_not_translate {
+                    format!("术语“{}”应保留原文", t.term.term)
+                } else {
+                    format!("术语“{}”应译为“{}”", t.term.term, t.term.translation)
+                }
+            })
+            .collect()
+    }
+}
+
+#[tauri::command]
+pub async fn get_glossary_terms(
+    db: State<'_, Database>,
+    source_language: Option<String>,
+    target_language: Option<String>,
+    query: Option<String>,
+) -> AppResult<Vec<GlossaryTerm>> {
+    db.run(move |conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM glossary_terms
+             WHERE {} AND (:query = '' OR term LIKE :pattern OR translation LIKE :pattern)
+             ORDER BY term COLLATE NOCASE ASC",
+            TERM_COLUMNS, PAIR_FILTER
+        ))?;
+        let query = query.unwrap_or_default().trim().to_string();
+        let terms = stmt
+            .query_map(
+                named_params! {
+                    ":source": normalize_language(source_language.as_deref().unwrap_or_default()),
+                    ":target": normalize_language(target_language.as_deref().unwrap_or_default()),
+                    ":query": query,
+                    ":pattern": format!("%{}%", query),
+                },
+                map_term,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(terms)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn create_glossary_term(
+    db: State<'_, Database>,
+    term: GlossaryTermInput,
+) -> AppResult<GlossaryTerm> {
+    let input = term.validate()?;
+    db.run(move |conn| {
+        let id = Uuid::new_v4().to_string();
+        insert_term(conn, &id, &input, &chrono::Utc::now().to_rfc3339())?;
+        get_term(conn, &id)
+    })
+    .await
+}
+
+/// 用提交的内容替换整条术语
+#[tauri::command]
+pub async fn update_glossary_term(
+    db: State<'_, Database>,
+    term_id: String,
+    term: GlossaryTermInput,
+) -> AppResult<GlossaryTerm> {
+    let input = term.validate()?;
+    db.run(move |conn| {
+        let updated = conn
+            .execute(
+                "UPDATE glossary_terms SET term = ?1, translation = ?2, source_language = ?3,
+                 target_language = ?4, case_sensitive = ?5, do_not_translate = ?6, note = ?7,
+                 updated_at = ?8 WHERE id = ?9",
+                params![
+                    input.term,
+                    input.translation,
+                    input.source_language,
+                    input.target_language,
+                    input.case_sensitive,
+                    input.do_not_translate,
+                    input.note,
+                    chrono::Utc::now().to_rfc3339(),
+                    term_id
+                ],
+            )
+            .map_err(|e| unique_term_error(e, &input.term))?;
+        if updated == 0 {
+            return Err(AppError::not_found(format!("术语不存在: {}", term_id)));
+        }
+        get_term(conn, &term_id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_glossary_term(db: State<'_, Database>, term_id: String) -> AppResult<()> {
+    db.run(move |conn| {
+        let deleted = conn.execute("DELETE FROM glossary_terms WHERE id = ?1", [&term_id])?;
+        if deleted == 0 {
+            return Err(AppError::not_found(format!("术语不存在: {}", term_id)));
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// 解析 CSV，支持引号转义，分隔符从首行的逗号、分号和制表符中选择
+fn parse_csv(content: &str) -> Vec<Vec<String>> {
+    let content = content.trim_start_matches('\u{feff}');
+    let first_line = content.lines().next().unwrap_or_default();
+    let delimiter = [',', ';', '\t']
+        .into_iter()
+        .max_by_key(|d| first_line.matches(*d).count())
+        .unwrap_or(',');
+
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut chars = content.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => in_quotes = !in_quotes,
+            c if c == delimiter && !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+fn read_rows(path: &Path) -> AppResult<Vec<Vec<String>>> {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    match extension.as_str() {
+        "csv" | "tsv" | "txt" => Ok(parse_csv(&std::fs::read_to_string(path)?)),
+        "xlsx" | "xlsm" | "xls" | "ods" => {
+            let mut workbook = open_workbook_auto(path)
+                .map_err(|e| AppError::invalid_input("无法打开术语表文件").with_details(e))?;
+            let range = match workbook.worksheet_range_at(0) {
+                Some(Ok(range)) => range,
+                Some(Err(e)) => {
+                    return Err(AppError::invalid_input("无法读取工作表").with_details(e))
+                }
+                None => return Err(AppError::invalid_input("术语表文件中没有工作表")),
+            };
+            Ok(range
+                .rows()
+                .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+                .collect())
+        }
+        _ => Err(AppError::invalid_input("只支持 CSV 和 Excel 格式的术语表")),
+    }
+}
+
+fn parse_flag(value: &str) -> bool {
+    matches!(
+        value.trim().to_lowercase().as_str(),
+        "1" | "true" | "yes" | "y" | "x" | "是" | "✓"
+    )
+}
+
+/// 表头各列的位置，没有可识别的表头时按“术语、译文”两列处理
+struct Columns {
+    term: usize,
+    translation: Option<usize>,
+    source_language: Option<usize>,
+    target_language: Option<usize>,
+    case_sensitive: Option<usize>,
+    do_not_translate: Option<usize>,
+    note: Option<usize>,
+}
+
+impl Columns {
+    fn detect(header: &[String]) -> Option<Self> {
+        let find = |aliases: &[&str]| {
+            header.iter().position(|cell| {
+                let cell = cell.trim().to_lowercase().replace([' ', '-'], "_");
+                aliases.contains(&cell.as_str())
+            })
+        };
+        Some(Self {
+            term: find(&["term", "source", "source_term", "术语", "原文"])?,
+            translation: find(&["translation", "target", "target_term", "译文", "译名"]),
+            source_language: find(&["source_language", "源语言"]),
+            target_language: find(&["target_language", "目标语言"]),
+            case_sensitive: find(&["case_sensitive", "区分大小写"]),
+            do_not_translate: find(&["do_not_translate", "dnt", "不翻译"]),
+            note: find(&["note", "notes", "备注", "说明"]),
+        })
+    }
+
+    fn positional() -> Self {
+        Self {
+            term: 0,
+            translation: Some(1),
+            source_language: None,
+            target_language: None,
+            case_sensitive: None,
+            do_not_translate: None,
+            note: None,
+        }
+    }
+}
+
+/// 从 CSV 或 Excel 导入术语，文件中没有语言列时使用传入的语言；已有的术语会被更新
+#[tauri::command]
+pub async fn import_glossary(
+    db: State<'_, Database>,
+    file_path: String,
+    source_language: Option<String>,
+    target_language: Option<String>,
+) -> AppResult<GlossaryImportReport> {
+    let path = PathBuf::from(file_path);
+    if !path.exists() {
+        return Err(AppError::not_found("文件不存在"));
+    }
+    let mut rows = tauri::async_runtime::spawn_blocking(move || read_rows(&path)).await??;
+    // 报错时使用文件中的行号
+    let (columns, first_line) = match rows.first().and_then(|header| Columns::detect(header)) {
+        Some(columns) => {
+            rows.remove(0);
+            (columns, 2)
+        }
+        None => (Columns::positional(), 1),
+    };
+
+    let mut report = GlossaryImportReport::default();
+    let mut inputs = Vec::new();
+    for (index, row) in rows.into_iter().enumerate() {
+        let cell = |col: Option<usize>| {
+            col.and_then(|col| row.get(col))
+                .map(|value| value.trim().to_string())
+                .unwrap_or_default()
+        };
+        let term = cell(Some(columns.term));
+        if term.is_empty() {
+            report.skipped += 1;
+            continue;
+        }
+        let or_default = |value: String, default: &Option<String>| {
+            if value.is_empty() {
+                default.clone().unwrap_or_default()
+            } else {
+                value
+            }
+        };
+        let input = GlossaryTermInput {
+            term,
+            translation: cell(columns.translation),
+            source_language: or_default(cell(columns.source_language), &source_language),
+            target_language: or_default(cell(columns.target_language), &target_language),
+            case_sensitive: parse_flag(&cell(columns.case_sensitive)),
+            do_not_translate: parse_flag(&cell(columns.do_not_translate)),
+            note: cell(columns.note),
+        };
+        match input.validate() {
+            Ok(input) => inputs.push(input),
+            Err(e) => {
+                report.skipped += 1;
+                report
+                    .errors
+                    .push(format!("第 {} 行: {}", index + first_line, e));
+            }
+        }
+    }
+
+    db.run(move |conn| {
+        let now = chrono::Utc::now().to_rfc3339();
+        let tx = conn.transaction()?;
+        for input in &inputs {
+            if upsert_term(&tx, input, &now)? {
+                report.imported += 1;
+            } else {
+                report.updated += 1;
+            }
+        }
+        tx.commit()?;
+        Ok(report)
+    })
+    .await
+}
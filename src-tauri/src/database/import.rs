@@ -0,0 +1,666 @@
+//! 从其他聊天客户端导入会话历史
+//!
+//! 支持 ChatGPT 导出的 `conversations.json`（消息树结构）、Claude 导出的 zip，
+//! 以及通用 JSON/JSONL 格式（包括本应用自己的 JSON 导出）。
+//! 每个导入的会话记录来源和原始 ID，重复导入时会跳过。
+//! 本应用 JSON 导出中嵌入的附件会写入工作区的 attachments 目录。
+
+use super::export::ConversationExport;
+use super::{get_workspace_dir, Database};
+use crate::error::{AppError, AppResult};
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use serde_json::Value;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Runtime, State};
+use uuid::Uuid;
+
+const SOURCE_CHATGPT: &str = "chatgpt";
+const SOURCE_CLAUDE: &str = "claude";
+const SOURCE_GENERIC: &str = "generic";
+const SOURCE_MORO: &str = "moro";
+const ATTACHMENT_DIR: &str = "attachments";
+
+#[derive(Serialize, Debug, Default)]
+pub struct ImportFileReport {
+    pub path: String,
+    pub format: String,
+    pub imported: usize,
+    pub skipped_duplicates: usize,
+    pub failed: usize,
+    pub errors: Vec<String>,
+}
+
+struct ImportedMessage {
+    role: String,
+    content: String,
+    timestamp: String,
+    model: Option<String>,
+    attachments: Vec<ImportedAttachment>,
+}
+
+struct ImportedAttachment {
+    file_name: String,
+    mime_type: String,
+    file_path: String,
+    /// 导出文件中嵌入的内容，没有时沿用原路径
+    data: Option<Vec<u8>>,
+}
+
+struct ImportedConversation {
+    external_id: String,
+    title: String,
+    created_at: String,
+    updated_at: String,
+    messages: Vec<ImportedMessage>,
+}
+
+struct ParsedFile {
+    source: &'static str,
+    conversations: Vec<ImportedConversation>,
+    errors: Vec<String>,
+}
+
+/// 将 Unix 时间戳或 ISO 8601 字符串统一为 RFC 3339
+fn normalize_timestamp(value: Option<&Value>) -> Option<String> {
+    match value? {
+        Value::Number(number) => {
+            let seconds = number.as_f64()?;
+            let millis = (seconds * 1000.0) as i64;
+            chrono::DateTime::from_timestamp_millis(millis).map(|time| time.to_rfc3339())
+        }
+        Value::String(text) => chrono::DateTime::parse_from_rfc3339(text)
+            .map(|time| time.with_timezone(&chrono::Utc).to_rfc3339())
+            .ok()
+            .or_else(|| {
+                chrono::NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M:%S")
+                    .ok()
+                    .map(|time| time.and_utc().to_rfc3339())
+            }),
+        _ => None,
+    }
+}
+
+fn normalize_role(role: &str) -> Option<&'static str> {
+    match role {
+        "user" | "human" => Some("user"),
+        "assistant" | "model" | "bot" => Some("assistant"),
+        "system" => Some("system"),
+        _ => None,
+    }
+}
+
+/// 文本内容可能是字符串，也可能是 `[{type: "text", text}]` 形式的片段数组
+fn extract_text(value: Option<&Value>) -> String {
+    match value {
+        Some(Value::String(text)) => text.clone(),
+        Some(Value::Array(parts)) => parts
+            .iter()
+            .filter_map(|part| match part {
+                Value::String(text) => Some(text.as_str()),
+                Value::Object(object) => object.get("text").and_then(Value::as_str),
+                _ => None,
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+        _ => String::new(),
+    }
+}
+
+/// 没有原始 ID 的会话用内容生成稳定指纹（FNV-1a）用于去重
+fn fingerprint(conversation: &ImportedConversation) -> String {
+    let mut hash: u64 = 0xcbf29ce484222325;
+    let mut feed = |text: &str| {
+        for byte in text.bytes().chain(std::iter::once(0)) {
+            hash ^= byte as u64;
+            hash = hash.wrapping_mul(0x100000001b3);
+        }
+    };
+    feed(&conversation.title);
+    feed(&conversation.created_at);
+    for message in &conversation.messages {
+        feed(&message.role);
+        feed(&message.content);
+    }
+    format!("fp-{:016x}", hash)
+}
+
+fn finish_conversation(
+    external_id: Option<String>,
+    title: Option<String>,
+    created_at: Option<String>,
+    updated_at: Option<String>,
+    messages: Vec<ImportedMessage>,
+) -> ImportedConversation {
+    let now = chrono::Utc::now().to_rfc3339();
+    let created_at = created_at
+        .or_else(|| messages.first().map(|m| m.timestamp.clone()))
+        .unwrap_or_else(|| now.clone());
+    let updated_at = updated_at
+        .or_else(|| messages.last().map(|m| m.timestamp.clone()))
+        .unwrap_or_else(|| created_at.clone());
+    let title = title
+        .filter(|title| !title.trim().is_empty())
+        .unwrap_or_else(|| "导入的会话".to_string());
+
+    let mut conversation = ImportedConversation {
+        external_id: String::new(),
+        title,
+        created_at,
+        updated_at,
+        messages,
+    };
+    conversation.external_id = external_id.unwrap_or_else(|| fingerprint(&conversation));
+    conversation
+}
+
+/// ChatGPT 的消息是一棵树（编辑和重新生成会产生分支），
+/// 从 current_node 沿 parent 回溯得到当前显示的那条对话线
+fn parse_chatgpt_conversation(value: &Value) -> AppResult<ImportedConversation> {
+    let mapping = value
+        .get("mapping")
+        .and_then(Value::as_object)
+        .ok_or_else(|| AppError::invalid_input("缺少 mapping 字段"))?;
+
+    let mut node_id = value
+        .get("current_node")
+        .and_then(Value::as_str)
+        .map(str::to_string);
+    let mut path = Vec::new();
+    while let Some(id) = node_id {
+        let Some(node) = mapping.get(&id) else { break };
+        path.push(node);
+        node_id = node
+            .get("parent")
+            .and_then(Value::as_str)
+            .map(str::to_string);
+        if path.len() > mapping.len() {
+            return Err(AppError::invalid_input("消息树存在循环引用"));
+        }
+    }
+    path.reverse();
+
+    let fallback_time = normalize_timestamp(value.get("create_time"));
+    let mut messages = Vec::new();
+    for node in path {
+        let Some(message) = node.get("message").filter(|m| !m.is_null()) else {
+            continue;
+        };
+        let hidden = message
+            .pointer("/metadata/is_visually_hidden_from_conversation")
+            .and_then(Value::as_bool)
+            .unwrap_or(false);
+        let Some(role) = message
+            .pointer("/author/role")
+            .and_then(Value::as_str)
+            .and_then(normalize_role)
+        else {
+            continue;
+        };
+        let content = extract_text(message.pointer("/content/parts"));
+        if hidden || content.trim().is_empty() {
+            continue;
+        }
+
+        messages.push(ImportedMessage {
+            role: role.to_string(),
+            content,
+            timestamp: normalize_timestamp(message.get("create_time"))
+                .or_else(|| fallback_time.clone())
+                .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+            model: message
+                .pointer("/metadata/model_slug")
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            attachments: Vec::new(),
+        });
+    }
+
+    Ok(finish_conversation(
+        value
+            .get("conversation_id")
+            .or_else(|| value.get("id"))
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        value
+            .get("title")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        fallback_time,
+        normalize_timestamp(value.get("update_time")),
+        messages,
+    ))
+}
+
+fn parse_claude_conversation(value: &Value) -> AppResult<ImportedConversation> {
+    let chat_messages = value
+        .get("chat_messages")
+        .and_then(Value::as_array)
+        .ok_or_else(|| AppError::invalid_input("缺少 chat_messages 字段"))?;
+
+    let mut messages = Vec::new();
+    for message in chat_messages {
+        let Some(role) = message
+            .get("sender")
+            .and_then(Value::as_str)
+            .and_then(normalize_role)
+        else {
+            continue;
+        };
+        let mut content = extract_text(message.get("text"));
+        if content.trim().is_empty() {
+            content = extract_text(message.get("content"));
+        }
+        if content.trim().is_empty() {
+            continue;
+        }
+
+        messages.push(ImportedMessage {
+            role: role.to_string(),
+            content,
+            timestamp: normalize_timestamp(message.get("created_at"))
+                .unwrap_or_else(|| chrono::Utc::now().to_rfc3339()),
+            model: None,
+            attachments: Vec::new(),
+        });
+    }
+
+    Ok(finish_conversation(
+        value
+            .get("uuid")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        value
+            .get("name")
+            .and_then(Value::as_str)
+            .map(str::to_string),
+        normalize_timestamp(value.get("created_at")),
+        normalize_timestamp(value.get("updated_at")),
+        messages,
+    ))
+}
+
+/// 通用格式：`{ id?, title?, created_at?, messages: [{ role, content, timestamp? }] }`
+fn parse_generic_conversation(value: &Value) -> AppResult<ImportedConversation> {
+    let raw_messages = value
+        .get("messages")
+        .and_then(Value::as_array)
+        .ok_or_else(|| AppError::invalid_input("缺少 messages 字段"))?;
+
+    let mut messages = Vec::new();
+    for message in raw_messages {
+        let role = ["role", "author", "sender"]
+            .iter()
+            .find_map(|key| message.get(*key).and_then(Value::as_str))
+            .and_then(normalize_role);
+        let Some(role) = role else { continue };
+        let content = extract_text(message.get("content").or_else(|| message.get("text")));
+        if content.trim().is_empty() {
+            continue;
+        }
+        let timestamp = ["timestamp", "created_at", "create_time"]
+            .iter()
+            .find_map(|key| normalize_timestamp(message.get(*key)))
+            .unwrap_or_else(|| chrono::Utc::now().to_rfc3339());
+
+        messages.push(ImportedMessage {
+            role: role.to_string(),
+            content,
+            timestamp,
+            model: ["model", "model_id"]
+                .iter()
+                .find_map(|key| message.get(*key).and_then(Value::as_str))
+                .map(str::to_string),
+            attachments: Vec::new(),
+        });
+    }
+
+    Ok(finish_conversation(
+        value.get("id").and_then(Value::as_str).map(str::to_string),
+        ["title", "name"]
+            .iter()
+            .find_map(|key| value.get(*key).and_then(Value::as_str))
+            .map(str::to_string),
+        normalize_timestamp(value.get("created_at")),
+        normalize_timestamp(value.get("updated_at")),
+        messages,
+    ))
+}
+
+fn from_export(export: ConversationExport) -> AppResult<ImportedConversation> {
+    let mut messages = Vec::new();
+    for message in export.messages {
+        let mut attachments = Vec::new();
+        for attachment in message.attachments {
+            let data = match attachment.data {
+                Some(data) => Some(BASE64.decode(data).map_err(|e| {
+                    AppError::invalid_input(format!("附件内容无效: {}", attachment.file_name))
+                        .with_details(e)
+                })?),
+                None => None,
+            };
+            attachments.push(ImportedAttachment {
+                file_name: attachment.file_name,
+                mime_type: attachment.mime_type,
+                file_path: attachment.file_path,
+                data,
+            });
+        }
+        messages.push(ImportedMessage {
+            role: message.role,
+            content: message.content,
+            timestamp: message.timestamp,
+            model: message.model_id,
+            attachments,
+        });
+    }
+
+    Ok(ImportedConversation {
+        external_id: export.conversation.id,
+        title: export.conversation.title,
+        created_at: export.conversation.created_at,
+        updated_at: export.conversation.updated_at,
+        messages,
+    })
+}
+
+fn parse_items(
+    items: Vec<Value>,
+    source: &'static str,
+    parse: fn(&Value) -> AppResult<ImportedConversation>,
+) -> ParsedFile {
+    let mut parsed = ParsedFile {
+        source,
+        conversations: Vec::new(),
+        errors: Vec::new(),
+    };
+    for (index, item) in items.iter().enumerate() {
+        match parse(item) {
+            Ok(conversation) => parsed.conversations.push(conversation),
+            Err(e) => parsed
+                .errors
+                .push(format!("第 {} 个会话: {}", index + 1, e)),
+        }
+    }
+    parsed
+}
+
+/// 根据 JSON 结构判断来源格式
+fn parse_json_value(value: Value) -> AppResult<ParsedFile> {
+    let items = match value {
+        Value::Array(items) => items,
+        Value::Object(_) => vec![value],
+        _ => return Err(AppError::invalid_input("无法识别的 JSON 结构")),
+    };
+    let Some(first) = items.first() else {
+        return Ok(parse_items(
+            items,
+            SOURCE_GENERIC,
+            parse_generic_conversation,
+        ));
+    };
+
+    if first.get("mapping").is_some() {
+        Ok(parse_items(
+            items,
+            SOURCE_CHATGPT,
+            parse_chatgpt_conversation,
+        ))
+    } else if first.get("chat_messages").is_some() {
+        Ok(parse_items(items, SOURCE_CLAUDE, parse_claude_conversation))
+    } else if first.get("conversation").is_some() && first.get("version").is_some() {
+        let mut parsed = ParsedFile {
+            source: SOURCE_MORO,
+            conversations: Vec::new(),
+            errors: Vec::new(),
+        };
+        for (index, item) in items.into_iter().enumerate() {
+            let conversation = serde_json::from_value::<ConversationExport>(item)
+                .map_err(AppError::from)
+                .and_then(from_export);
+            match conversation {
+                Ok(conversation) => parsed.conversations.push(conversation),
+                Err(e) => parsed
+                    .errors
+                    .push(format!("第 {} 个会话: {}", index + 1, e)),
+            }
+        }
+        Ok(parsed)
+    } else {
+        Ok(parse_items(
+            items,
+            SOURCE_GENERIC,
+            parse_generic_conversation,
+        ))
+    }
+}
+
+/// JSONL 每行一个会话；如果每行是单条消息，则整个文件视为一个会话
+fn parse_jsonl(content: &str, file_stem: &str) -> AppResult<ParsedFile> {
+    let mut lines = Vec::new();
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let value: Value = serde_json::from_str(line).map_err(|e| {
+            AppError::invalid_input(format!("第 {} 行不是有效的 JSON", index + 1)).with_details(e)
+        })?;
+        lines.push(value);
+    }
+
+    if lines.iter().all(|line| line.get("messages").is_none()) {
+        let conversation = serde_json::json!({ "title": file_stem, "messages": lines });
+        return Ok(parse_items(
+            vec![conversation],
+            SOURCE_GENERIC,
+            parse_generic_conversation,
+        ));
+    }
+    parse_json_value(Value::Array(lines))
+}
+
+fn read_zip_conversations(path: &Path) -> AppResult<String> {
+    let file = File::open(path)?;
+    let mut archive = zip::ZipArchive::new(file)
+        .map_err(|e| AppError::invalid_input("无法读取压缩包").with_details(e))?;
+    let name = archive
+        .file_names()
+        .find(|name| name.ends_with("conversations.json"))
+        .map(str::to_string)
+        .ok_or_else(|| AppError::invalid_input("压缩包中没有 conversations.json"))?;
+
+    let mut content = String::new();
+    archive.by_name(&name)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+fn parse_file(path: &Path) -> AppResult<ParsedFile> {
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+
+    match extension.as_str() {
+        "zip" => {
+            let content = read_zip_conversations(path)?;
+            let value = serde_json::from_str(&content)?;
+            parse_json_value(value)
+        }
+        "jsonl" | "ndjson" => {
+            let content = fs::read_to_string(path)?;
+            let stem = path
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            parse_jsonl(&content, &stem)
+        }
+        _ => {
+            let content = fs::read_to_string(path)?;
+            let value = serde_json::from_str(&content)?;
+            parse_json_value(value)
+        }
+    }
+}
+
+fn is_duplicate(conn: &Connection, source: &str, external_id: &str) -> AppResult<bool> {
+    let exists = conn
+        .query_row(
+            "SELECT 1 FROM conversations
+             WHERE (import_source = ?1 AND external_id = ?2) OR (?1 = 'moro' AND id = ?2)",
+            params![source, external_id],
+            |_| Ok(()),
+        )
+        .optional()?;
+    Ok(exists.is_some())
+}
+
+/// 把嵌入的附件写入附件目录，返回新文件的路径；文件名加上唯一前缀避免重名
+fn write_attachment(directory: &Path, file_name: &str, data: &[u8]) -> AppResult<PathBuf> {
+    let name = Path::new(file_name)
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_else(|| "attachment".to_string());
+    fs::create_dir_all(directory)?;
+    let path = directory.join(format!("{}-{}", Uuid::new_v4(), name));
+    fs::write(&path, data)?;
+    Ok(path)
+}
+
+/// 写入一个会话，已导入过的返回 false
+fn insert_conversation(
+    conn: &mut Connection,
+    source: &str,
+    conversation: &ImportedConversation,
+    attachment_dir: &Path,
+) -> AppResult<bool> {
+    if is_duplicate(conn, source, &conversation.external_id)? {
+        return Ok(false);
+    }
+
+    // 写入失败时删除已经写出的附件文件
+    let mut written = Vec::new();
+    let result = insert_messages(conn, source, conversation, attachment_dir, &mut written);
+    if result.is_err() {
+        for path in written {
+            let _ = fs::remove_file(path);
+        }
+    }
+    result.map(|()| true)
+}
+
+fn insert_messages(
+    conn: &mut Connection,
+    source: &str,
+    conversation: &ImportedConversation,
+    attachment_dir: &Path,
+    written: &mut Vec<PathBuf>,
+) -> AppResult<()> {
+    let tx = conn.transaction()?;
+    let id = Uuid::new_v4().to_string();
+    tx.execute(
+        "INSERT INTO conversations (id, title, summary, created_at, updated_at, is_pinned, import_source, external_id)
+         VALUES (?1, ?2, '', ?3, ?4, 0, ?5, ?6)",
+        params![
+            id,
+            conversation.title,
+            conversation.created_at,
+            conversation.updated_at,
+            source,
+            conversation.external_id
+        ],
+    )?;
+
+    for message in &conversation.messages {
+        let message_id = Uuid::new_v4().to_string();
+        tx.execute(
+            "INSERT INTO messages (id, conversation_id, role, content, timestamp, model_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                message_id,
+                id,
+                message.role,
+                message.content,
+                message.timestamp,
+                message.model
+            ],
+        )?;
+
+        for attachment in &message.attachments {
+            let file_path = match &attachment.data {
+                Some(data) => {
+                    let path = write_attachment(attachment_dir, &attachment.file_name, data)?;
+                    written.push(path.clone());
+                    path.to_string_lossy().into_owned()
+                }
+                None => attachment.file_path.clone(),
+            };
+            tx.execute(
+                "INSERT INTO attachments (id, message_id, file_name, mime_type, file_path, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    Uuid::new_v4().to_string(),
+                    message_id,
+                    attachment.file_name,
+                    attachment.mime_type,
+                    file_path,
+                    message.timestamp
+                ],
+            )?;
+        }
+    }
+
+    tx.commit()?;
+    Ok(())
+}
+
+fn import_file(conn: &mut Connection, path: &str, attachment_dir: &Path) -> ImportFileReport {
+    let mut report = ImportFileReport {
+        path: path.to_string(),
+        ..Default::default()
+    };
+
+    let parsed = match parse_file(Path::new(path)) {
+        Ok(parsed) => parsed,
+        Err(e) => {
+            report.format = "unknown".to_string();
+            report.errors.push(e.to_string());
+            return report;
+        }
+    };
+
+    report.format = parsed.source.to_string();
+    report.failed = parsed.errors.len();
+    report.errors = parsed.errors;
+
+    for conversation in &parsed.conversations {
+        match insert_conversation(conn, parsed.source, conversation, attachment_dir) {
+            Ok(true) => report.imported += 1,
+            Ok(false) => report.skipped_duplicates += 1,
+            Err(e) => {
+                report.failed += 1;
+                report.errors.push(format!("{}: {}", conversation.title, e));
+            }
+        }
+    }
+
+    report
+}
+
+/// 导入多个文件，单个文件失败不影响其他文件，结果按文件返回
+#[tauri::command]
+pub async fn import_conversations<R: Runtime>(
+    app: AppHandle<R>,
+    db: State<'_, Database>,
+    paths: Vec<String>,
+) -> AppResult<Vec<ImportFileReport>> {
+    let attachment_dir = get_workspace_dir(&app)?.join(ATTACHMENT_DIR);
+    db.run(move |conn| {
+        Ok(paths
+            .iter()
+            .map(|path| import_file(conn, path, &attachment_dir))
+            .collect())
+    })
+    .await
+}
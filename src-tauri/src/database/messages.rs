@@ -0,0 +1,167 @@
+//! 单条消息的编辑与删除
+//!
+//! 每次编辑前的内容保存在 `message_revisions` 中，可以查看历史版本。
+
+use super::{map_message, Database, Message};
+use crate::error::{AppError, AppResult};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::Serialize;
+use tauri::State;
+use uuid::Uuid;
+
+#[derive(Serialize, Debug)]
+pub struct MessageRevision {
+    pub id: String,
+    pub message_id: String,
+    pub content: String,
+    /// 被替换的时间
+    pub created_at: String,
+}
+
+#[derive(Serialize, Debug)]
+pub struct EditedMessage {
+    pub message: Message,
+    /// 截断后续对话时被删除的消息
+    pub removed_message_ids: Vec<String>,
+}
+
+pub(crate) fn create_tables(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS message_revisions (
+            id TEXT PRIMARY KEY,
+            message_id TEXT NOT NULL,
+            content TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY(message_id) REFERENCES messages(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_message_revisions_message
+            ON message_revisions(message_id, created_at);",
+    )
+    .map_err(AppError::from)
+}
+
+fn load_message(conn: &Connection, message_id: &str) -> AppResult<Message> {
+    conn.query_row(
+        "SELECT id, conversation_id, role, content, timestamp, model_id FROM messages WHERE id = ?1",
+        params![message_id],
+        map_message,
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found(format!("消息不存在: {}", message_id)))
+}
+
+fn touch_conversation(conn: &Connection, conversation_id: &str) -> AppResult<()> {
+    conn.execute(
+        "UPDATE conversations SET updated_at = ?1 WHERE id = ?2",
+        params![chrono::Utc::now().to_rfc3339(), conversation_id],
+    )?;
+    Ok(())
+}
+
+/// 修改消息内容，旧内容存入修订历史。
+/// `truncate_following` 为 true 时删除该用户消息之后的所有消息，便于重新发送
+#[tauri::command]
+pub async fn edit_message(
+    db: State<'_, Database>,
+    message_id: String,
+    content: String,
+    truncate_following: Option<bool>,
+) -> AppResult<EditedMessage> {
+    let truncate_following = truncate_following.unwrap_or(false);
+    db.run(move |conn| {
+        let tx = conn.transaction()?;
+        let mut message = load_message(&tx, &message_id)?;
+
+        if truncate_following && message.role != "user" {
+            return Err(AppError::invalid_input("只有编辑用户消息时才能截断后续对话"));
+        }
+
+        if message.content != content {
+            tx.execute(
+                "INSERT INTO message_revisions (id, message_id, content, created_at) VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    Uuid::new_v4().to_string(),
+                    message.id,
+                    message.content,
+                    chrono::Utc::now().to_rfc3339()
+                ],
+            )?;
+            tx.execute(
+                "UPDATE messages SET content = ?1 WHERE id = ?2",
+                params![content, message.id],
+            )?;
+            message.content = content;
+        }
+
+        let mut removed_message_ids = Vec::new();
+        if truncate_following {
+            // 时间戳相同时按插入顺序（rowid）判断先后，不能用随机生成的 id
+            let mut stmt = tx.prepare(
+                "DELETE FROM messages
+                 WHERE conversation_id = ?1
+                   AND (timestamp, rowid) > (SELECT timestamp, rowid FROM messages WHERE id = ?2)
+                 RETURNING id",
+            )?;
+            removed_message_ids = stmt
+                .query_map(params![message.conversation_id, message.id], |row| {
+                    row.get(0)
+                })?
+                .collect::<Result<Vec<String>, _>>()?;
+        }
+
+        touch_conversation(&tx, &message.conversation_id)?;
+        tx.commit()?;
+
+        Ok(EditedMessage {
+            message,
+            removed_message_ids,
+        })
+    })
+    .await
+}
+
+/// 删除单条消息，其附件和修订历史一并删除
+#[tauri::command]
+pub async fn delete_message(db: State<'_, Database>, message_id: String) -> AppResult<()> {
+    db.run(move |conn| {
+        let tx = conn.transaction()?;
+        let conversation_id: String = tx
+            .query_row(
+                "DELETE FROM messages WHERE id = ?1 RETURNING conversation_id",
+                params![message_id],
+                |row| row.get(0),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::not_found(format!("消息不存在: {}", message_id)))?;
+
+        touch_conversation(&tx, &conversation_id)?;
+        tx.commit().map_err(AppError::from)
+    })
+    .await
+}
+
+/// 按时间倒序返回消息的历史版本
+#[tauri::command]
+pub async fn get_message_revisions(
+    db: State<'_, Database>,
+    message_id: String,
+) -> AppResult<Vec<MessageRevision>> {
+    db.run(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, message_id, content, created_at FROM message_revisions
+                 WHERE message_id = ?1 ORDER BY created_at DESC",
+        )?;
+        let revisions = stmt
+            .query_map(params![message_id], |row| {
+                Ok(MessageRevision {
+                    id: row.get(0)?,
+                    message_id: row.get(1)?,
+                    content: row.get(2)?,
+                    created_at: row.get(3)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(revisions)
+    })
+    .await
+}
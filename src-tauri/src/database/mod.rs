@@ -0,0 +1,873 @@
+use crate::error::{AppError, AppResult};
+use crate::workspace::Workspaces;
+use r2d2::Pool;
+use r2d2_sqlite::SqliteConnectionManager;
+use rusqlite::{named_params, params, Connection, OptionalExtension, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::{AppHandle, Manager, Runtime, State};
+use uuid::Uuid;
+
+pub mod analytics;
+pub mod backup;
+pub mod encryption;
+pub mod export;
+pub mod folders;
+pub mod glossary;
+pub mod import;
+pub mod messages;
+pub mod pagination;
+pub mod providers;
+pub mod secrets;
+pub mod tags;
+pub mod translation_memory;
+pub mod trash;
+
+use secrets::Vault;
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Conversation {
+    pub id: String,
+    pub title: String,
+    pub summary: String,
+    pub created_at: String,
+    pub updated_at: String,
+    #[serde(default)] // field might be missing in older records
+    pub is_pinned: bool,
+    #[serde(default)]
+    pub folder_id: Option<String>,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Message {
+    pub id: String,
+    pub conversation_id: String,
+    pub role: String,
+    pub content: String,
+    pub timestamp: String,
+    #[serde(default)]
+    pub model_id: Option<String>,
+    #[serde(default)]
+    pub attachments: Vec<Attachment>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Attachment {
+    pub id: String,
+    pub file_name: String,
+    pub mime_type: String,
+    pub file_path: String,
+}
+
+#[derive(Deserialize, Debug)]
+pub struct NewAttachment {
+    pub file_name: String,
+    #[serde(default)]
+    pub mime_type: String,
+    pub file_path: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Provider {
+    pub id: String,
+    pub name: String,
+    pub base_url: String,
+    /// 列表接口返回的是掩码后的值
+    pub api_key: String,
+    pub icon: String,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct Model {
+    pub id: String,
+    pub provider_id: String,
+    pub name: String,
+    pub model_key: String,
+    pub is_active: bool,
+    pub created_at: String,
+}
+
+const DB_NAME: &str = "chat_history.db";
+const POOL_SIZE: u32 = 8;
+const BUSY_TIMEOUT: Duration = Duration::from_secs(5);
+
+pub type DbPool = Pool<SqliteConnectionManager>;
+
+struct DatabaseState {
+    pool: Option<DbPool>,
+    passphrase: Option<String>,
+}
+
+/// 共享的 SQLite 连接池，作为 Tauri 状态管理
+///
+/// 加密数据库在解锁前没有连接池，此时所有数据库命令都会返回错误。
+pub struct Database {
+    path: RwLock<PathBuf>,
+    state: RwLock<DatabaseState>,
+    /// 执行中的操作持有读锁，替换数据库文件前取得写锁
+    operations: tokio::sync::RwLock<()>,
+}
+
+impl Database {
+    fn open(path: PathBuf, passphrase: Option<String>) -> AppResult<Self> {
+        let pool = open_pool(&path, passphrase.clone())?;
+        Ok(Self {
+            path: RwLock::new(path),
+            state: RwLock::new(DatabaseState {
+                pool: Some(pool),
+                passphrase,
+            }),
+            operations: tokio::sync::RwLock::new(()),
+        })
+    }
+
+    fn locked(path: PathBuf) -> Self {
+        Self {
+            path: RwLock::new(path),
+            state: RwLock::new(DatabaseState {
+                pool: None,
+                passphrase: None,
+            }),
+            operations: tokio::sync::RwLock::new(()),
+        }
+    }
+
+    pub fn path(&self) -> PathBuf {
+        self.path.read().unwrap().clone()
+    }
+
+    pub fn is_unlocked(&self) -> bool {
+        self.state.read().unwrap().pool.is_some()
+    }
+
+    fn passphrase(&self) -> Option<String> {
+        self.state.read().unwrap().passphrase.clone()
+    }
+
+    fn pool(&self) -> AppResult<DbPool> {
+        self.state
+            .read()
+            .unwrap()
+            .pool
+            .clone()
+            .ok_or_else(|| AppError::locked("数据库已加密，请先解锁"))
+    }
+
+    fn attach(&self, pool: DbPool, passphrase: Option<String>) {
+        *self.state.write().unwrap() = DatabaseState {
+            pool: Some(pool),
+            passphrase,
+        };
+    }
+
+    /// 等待执行中的操作结束并阻止新的操作，守卫释放前连接池不会再借出连接
+    async fn exclusive(&self) -> tokio::sync::RwLockWriteGuard<'_, ()> {
+        self.operations.write().await
+    }
+
+    /// 断开连接池，用于替换数据库文件前释放所有空闲连接
+    fn detach(&self) {
+        self.state.write().unwrap().pool = None;
+    }
+
+    /// 切换工作区时换成另一个数据库，正在执行的操作仍使用旧的连接池直到完成
+    pub(crate) fn replace_with(&self, other: Database) {
+        let Database { path, state, .. } = other;
+        *self.path.write().unwrap() = path.into_inner().unwrap();
+        *self.state.write().unwrap() = state.into_inner().unwrap();
+    }
+
+    /// 同步获取连接，仅用于应用启动阶段
+    pub fn with_conn<T>(&self, f: impl FnOnce(&mut Connection) -> AppResult<T>) -> AppResult<T> {
+        let mut conn = self.pool()?.get()?;
+        f(&mut conn)
+    }
+
+    /// 在阻塞线程池中执行数据库操作，避免占用异步运行时
+    pub async fn run<T, F>(&self, f: F) -> AppResult<T>
+    where
+        T: Send + 'static,
+        F: FnOnce(&mut Connection) -> AppResult<T> + Send + 'static,
+    {
+        let _operation = self.operations.read().await;
+        let pool = self.pool()?;
+        tauri::async_runtime::spawn_blocking(move || {
+            let mut conn = pool.get()?;
+            f(&mut conn)
+        })
+        .await?
+    }
+}
+
+fn open_pool(path: &Path, passphrase: Option<String>) -> AppResult<DbPool> {
+    let manager = SqliteConnectionManager::file(path)
+        .with_init(move |conn| configure_connection(conn, passphrase.as_deref()));
+    Pool::builder()
+        .max_size(POOL_SIZE)
+        .build(manager)
+        .map_err(AppError::from)
+}
+
+// 每个新连接都需要开启 WAL、忙等待和外键约束（SQLite 默认不启用外键）
+fn configure_connection(conn: &mut Connection, passphrase: Option<&str>) -> Result<()> {
+    // SQLCipher 要求 key 是连接上的第一条语句
+    if let Some(passphrase) = passphrase {
+        conn.pragma_update(None, "key", passphrase)?;
+    }
+    conn.pragma_update_and_check(None, "journal_mode", "WAL", |row| row.get::<_, String>(0))?;
+    conn.pragma_update(None, "synchronous", "NORMAL")?;
+    conn.pragma_update(None, "foreign_keys", true)?;
+    conn.busy_timeout(BUSY_TIMEOUT)?;
+    Ok(())
+}
+
+/// 当前工作区的数据目录，数据库、密钥文件、备份和快照都保存在这里
+fn get_workspace_dir<R: Runtime>(app_handle: &AppHandle<R>) -> AppResult<PathBuf> {
+    let workspace_dir = app_handle.state::<Workspaces>().active_dir();
+
+    if !workspace_dir.exists() {
+        fs::create_dir_all(&workspace_dir)?;
+    }
+
+    Ok(workspace_dir)
+}
+
+fn get_db_path<R: Runtime>(app_handle: &AppHandle<R>) -> AppResult<PathBuf> {
+    Ok(get_workspace_dir(app_handle)?.join(DB_NAME))
+}
+
+/// 为旧版本数据库补充新增的列
+fn ensure_column(conn: &Connection, table: &str, column: &str, definition: &str) -> Result<()> {
+    let mut stmt = conn.prepare(&format!("PRAGMA table_info({})", table))?;
+    let exists = stmt
+        .query_map([], |row| row.get::<_, String>(1))?
+        .filter_map(Result::ok)
+        .any(|name| name == column);
+
+    if !exists {
+        conn.execute(
+            &format!("ALTER TABLE {} ADD COLUMN {} {}", table, column, definition),
+            [],
+        )?;
+    }
+    Ok(())
+}
+
+/// 读取 app_meta 中的配置项
+pub(crate) fn get_meta(conn: &Connection, key: &str) -> Result<Option<String>> {
+    conn.query_row(
+        "SELECT value FROM app_meta WHERE key = ?1",
+        params![key],
+        |row| row.get(0),
+    )
+    .optional()
+}
+
+pub(crate) fn set_meta(conn: &Connection, key: &str, value: &str) -> Result<()> {
+    conn.execute(
+        "INSERT INTO app_meta (key, value) VALUES (?1, ?2)
+         ON CONFLICT(key) DO UPDATE SET value = excluded.value",
+        params![key, value],
+    )?;
+    Ok(())
+}
+
+pub fn init_db<R: Runtime>(app_handle: &AppHandle<R>) -> AppResult<Database> {
+    open_database(get_db_path(app_handle)?)
+}
+
+/// 打开指定目录下的数据库，不存在时新建
+pub(crate) fn open_workspace_db(workspace_dir: &Path) -> AppResult<Database> {
+    fs::create_dir_all(workspace_dir)?;
+    open_database(workspace_dir.join(DB_NAME))
+}
+
+fn open_database(db_path: PathBuf) -> AppResult<Database> {
+    // 加密数据库需要等待用户输入密码后再建立连接
+    if encryption::is_encrypted_file(&db_path) {
+        return Ok(Database::locked(db_path));
+    }
+
+    let db = Database::open(db_path, None)?;
+    db.with_conn(|conn| create_schema(conn))?;
+    Ok(db)
+}
+
+fn create_schema(conn: &Connection) -> AppResult<()> {
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS conversations (
+            id TEXT PRIMARY KEY,
+            title TEXT NOT NULL,
+            summary TEXT DEFAULT '',
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            is_pinned BOOLEAN DEFAULT 0
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS messages (
+            id TEXT PRIMARY KEY,
+            conversation_id TEXT NOT NULL,
+            role TEXT NOT NULL,
+            content TEXT NOT NULL,
+            timestamp TEXT NOT NULL,
+            FOREIGN KEY(conversation_id) REFERENCES conversations(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS providers (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL,
+            base_url TEXT NOT NULL,
+            api_key TEXT NOT NULL,
+            icon TEXT DEFAULT '',
+            created_at TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS models (
+            id TEXT PRIMARY KEY,
+            provider_id TEXT NOT NULL,
+            name TEXT NOT NULL,
+            model_key TEXT NOT NULL,
+            is_active BOOLEAN DEFAULT 0,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY(provider_id) REFERENCES providers(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS app_meta (
+            key TEXT PRIMARY KEY,
+            value TEXT NOT NULL
+        )",
+        [],
+    )?;
+
+    // 加密后无法再从密文展示密钥末尾，单独保存末四位用于掩码显示
+    ensure_column(conn, "providers", "api_key_hint", "TEXT DEFAULT ''")?;
+
+    // 记录生成回复所用的模型，模型被删除后仍保留其 ID
+    ensure_column(conn, "messages", "model_id", "TEXT")?;
+
+    // 导入的会话记录来源和原始 ID，用于重复导入时去重
+    ensure_column(conn, "conversations", "import_source", "TEXT")?;
+    ensure_column(conn, "conversations", "external_id", "TEXT")?;
+    conn.execute(
+        "CREATE UNIQUE INDEX IF NOT EXISTS idx_conversations_import
+         ON conversations(import_source, external_id) WHERE external_id IS NOT NULL",
+        [],
+    )?;
+
+    conn.execute(
+        "CREATE TABLE IF NOT EXISTS attachments (
+            id TEXT PRIMARY KEY,
+            message_id TEXT NOT NULL,
+            file_name TEXT NOT NULL,
+            mime_type TEXT NOT NULL DEFAULT '',
+            file_path TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            FOREIGN KEY(message_id) REFERENCES messages(id) ON DELETE CASCADE
+        )",
+        [],
+    )?;
+
+    pagination::create_indexes(conn)?;
+    analytics::create_indexes(conn)?;
+    folders::create_tables(conn)?;
+    tags::create_tables(conn)?;
+    trash::create_tables(conn)?;
+    messages::create_tables(conn)?;
+    translation_memory::create_tables(conn)?;
+    glossary::create_tables(conn)?;
+
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn create_conversation(db: State<'_, Database>, title: String) -> AppResult<String> {
+    db.run(move |conn| {
+        let id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO conversations (id, title, summary, created_at, updated_at, is_pinned) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![id, title, "", now, now, false],
+        )?;
+
+        Ok(id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn save_message(
+    db: State<'_, Database>,
+    conversation_id: String,
+    role: String,
+    content: String,
+    model_id: Option<String>,
+    attachments: Option<Vec<NewAttachment>>,
+) -> AppResult<String> {
+    db.run(move |conn| {
+        let id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+        let tx = conn.transaction()?;
+
+        tx.execute(
+            "INSERT INTO messages (id, conversation_id, role, content, timestamp, model_id) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![id, conversation_id, role, content, now, model_id],
+        )?;
+
+        for attachment in attachments.unwrap_or_default() {
+            tx.execute(
+                "INSERT INTO attachments (id, message_id, file_name, mime_type, file_path, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    Uuid::new_v4().to_string(),
+                    id,
+                    attachment.file_name,
+                    attachment.mime_type,
+                    attachment.file_path,
+                    now
+                ],
+            )?;
+        }
+
+        // Update conversation timestamp
+        tx.execute(
+            "UPDATE conversations SET updated_at = ?1 WHERE id = ?2",
+            params![now, conversation_id],
+        )?;
+
+        tx.commit()?;
+        Ok(id)
+    })
+    .await
+}
+
+fn map_message(row: &rusqlite::Row) -> Result<Message> {
+    Ok(Message {
+        id: row.get(0)?,
+        conversation_id: row.get(1)?,
+        role: row.get(2)?,
+        content: row.get(3)?,
+        timestamp: row.get(4)?,
+        model_id: row.get(5)?,
+        attachments: Vec::new(),
+    })
+}
+
+/// 一次查询会话内所有附件并挂到对应消息上
+fn attach_files(conn: &Connection, conversation_id: &str, messages: &mut [Message]) -> Result<()> {
+    let mut stmt = conn.prepare(
+        "SELECT a.id, a.message_id, a.file_name, a.mime_type, a.file_path
+         FROM attachments a
+         JOIN messages m ON a.message_id = m.id
+         WHERE m.conversation_id = ?1
+         ORDER BY a.created_at ASC",
+    )?;
+    fill_attachments(&mut stmt, params![conversation_id], messages)
+}
+
+/// 只查询给定消息的附件，分页时避免加载整个会话的附件
+fn attach_message_files(conn: &Connection, messages: &mut [Message]) -> Result<()> {
+    if messages.is_empty() {
+        return Ok(());
+    }
+    let placeholders = vec!["?"; messages.len()].join(", ");
+    let mut stmt = conn.prepare(&format!(
+        "SELECT id, message_id, file_name, mime_type, file_path
+         FROM attachments
+         WHERE message_id IN ({})
+         ORDER BY created_at ASC",
+        placeholders
+    ))?;
+    let ids: Vec<String> = messages.iter().map(|m| m.id.clone()).collect();
+    fill_attachments(&mut stmt, rusqlite::params_from_iter(ids), messages)
+}
+
+fn fill_attachments(
+    stmt: &mut rusqlite::Statement<'_>,
+    params: impl rusqlite::Params,
+    messages: &mut [Message],
+) -> Result<()> {
+    let rows = stmt.query_map(params, |row| {
+        Ok((
+            row.get::<_, String>(1)?,
+            Attachment {
+                id: row.get(0)?,
+                file_name: row.get(2)?,
+                mime_type: row.get(3)?,
+                file_path: row.get(4)?,
+            },
+        ))
+    })?;
+
+    let mut by_message: HashMap<String, Vec<Attachment>> = HashMap::new();
+    for row in rows {
+        let (message_id, attachment) = row?;
+        by_message.entry(message_id).or_default().push(attachment);
+    }
+    for message in messages.iter_mut() {
+        if let Some(attachments) = by_message.remove(&message.id) {
+            message.attachments = attachments;
+        }
+    }
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn get_history(
+    db: State<'_, Database>,
+    conversation_id: String,
+) -> AppResult<Vec<Message>> {
+    db.run(move |conn| {
+        let mut stmt = conn
+            .prepare("SELECT id, conversation_id, role, content, timestamp, model_id FROM messages WHERE conversation_id = ?1 ORDER BY timestamp ASC")?;
+
+        let message_iter = stmt
+            .query_map(params![conversation_id], map_message)?;
+
+        let mut messages = Vec::new();
+        for message in message_iter {
+            messages.push(message?);
+        }
+
+        attach_files(conn, &conversation_id, &mut messages)?;
+        Ok(messages)
+    })
+    .await
+}
+
+fn map_conversation(row: &rusqlite::Row) -> Result<Conversation> {
+    Ok(Conversation {
+        id: row.get(0)?,
+        title: row.get(1)?,
+        summary: row.get(2)?,
+        created_at: row.get(3)?,
+        updated_at: row.get(4)?,
+        is_pinned: row.get(5)?,
+        folder_id: row.get(6)?,
+    })
+}
+
+/// 会话列表按文件夹或标签筛选，参数为空时不过滤；回收站中的会话始终排除
+pub(crate) const CONVERSATION_FILTER: &str = "c.deleted_at IS NULL
+     AND (:folder_id IS NULL OR c.folder_id = :folder_id)
+     AND (:tag_id IS NULL OR EXISTS (
+         SELECT 1 FROM conversation_tags ct WHERE ct.conversation_id = c.id AND ct.tag_id = :tag_id
+     ))";
+
+#[tauri::command]
+pub async fn get_conversation_list(
+    db: State<'_, Database>,
+    folder_id: Option<String>,
+    tag_id: Option<String>,
+) -> AppResult<Vec<Conversation>> {
+    db.run(move |conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT c.id, c.title, c.summary, c.created_at, c.updated_at, c.is_pinned, c.folder_id
+                 FROM conversations c WHERE {} ORDER BY c.updated_at DESC",
+            CONVERSATION_FILTER
+        ))?;
+
+        let conversation_iter = stmt.query_map(
+            named_params! { ":folder_id": folder_id, ":tag_id": tag_id },
+            map_conversation,
+        )?;
+
+        let mut conversations = Vec::new();
+        for conversation in conversation_iter {
+            conversations.push(conversation?);
+        }
+
+        Ok(conversations)
+    })
+    .await
+}
+
+/// 移入回收站，彻底删除见 `trash::empty_trash`
+#[tauri::command]
+pub async fn delete_conversation(
+    db: State<'_, Database>,
+    conversation_id: String,
+) -> AppResult<()> {
+    db.run(move |conn| {
+        conn.execute(
+            "UPDATE conversations SET deleted_at = ?1 WHERE id = ?2 AND deleted_at IS NULL",
+            params![chrono::Utc::now().to_rfc3339(), conversation_id],
+        )?;
+
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn update_conversation_title(
+    db: State<'_, Database>,
+    conversation_id: String,
+    title: String,
+) -> AppResult<()> {
+    db.run(move |conn| {
+        conn.execute(
+            "UPDATE conversations SET title = ?1 WHERE id = ?2",
+            params![title, conversation_id],
+        )?;
+
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn toggle_pin_conversation(
+    db: State<'_, Database>,
+    conversation_id: String,
+) -> AppResult<bool> {
+    db.run(move |conn| {
+        // 在同一语句中翻转，避免并发切换时读到旧状态
+        conn.query_row(
+            "UPDATE conversations SET is_pinned = NOT is_pinned WHERE id = ?1 RETURNING is_pinned",
+            params![conversation_id],
+            |row| row.get(0),
+        )
+        .optional()?
+        .ok_or_else(|| AppError::not_found(format!("会话不存在: {}", conversation_id)))
+    })
+    .await
+}
+
+// --- Providers and Models Commands ---
+
+#[tauri::command]
+pub async fn create_provider(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    name: String,
+    base_url: String,
+    api_key: String,
+    icon: String,
+) -> AppResult<String> {
+    let name = providers::require_text(&name, "服务商名称")?;
+    let base_url = providers::normalize_base_url(&base_url)?;
+    let encrypted_key = vault.encrypt(&api_key)?;
+    let hint = secrets::key_hint(&api_key);
+
+    db.run(move |conn| {
+        let id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO providers (id, name, base_url, api_key, api_key_hint, icon, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7)",
+            params![id, name, base_url, encrypted_key, hint, icon, now],
+        )?;
+
+        Ok(id)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_providers(db: State<'_, Database>) -> AppResult<Vec<Provider>> {
+    db.run(|conn| {
+        let mut stmt = conn
+            .prepare("SELECT id, name, base_url, api_key, api_key_hint, icon, created_at FROM providers ORDER BY created_at DESC")?;
+
+        // 只返回掩码，明文密钥不会离开后端
+        let iter = stmt
+            .query_map([], |row| {
+                let api_key: String = row.get(3)?;
+                let hint: Option<String> = row.get(4)?;
+                Ok(Provider {
+                    id: row.get(0)?,
+                    name: row.get(1)?,
+                    base_url: row.get(2)?,
+                    api_key: secrets::mask_api_key(&api_key, hint.as_deref().unwrap_or_default()),
+                    icon: row.get(5)?,
+                    created_at: row.get(6)?,
+                })
+            })?;
+
+        let mut result = Vec::new();
+        for item in iter {
+            result.push(item?);
+        }
+        Ok(result)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_provider(db: State<'_, Database>, provider_id: String) -> AppResult<()> {
+    db.run(move |conn| {
+        conn.execute("DELETE FROM providers WHERE id = ?1", params![provider_id])?;
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn create_model(
+    db: State<'_, Database>,
+    provider_id: String,
+    name: String,
+    model_key: String,
+) -> AppResult<String> {
+    db.run(move |conn| {
+        let id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+
+        conn.execute(
+            "INSERT INTO models (id, provider_id, name, model_key, is_active, created_at) VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![id, provider_id, name, model_key, false, now],
+        )?;
+
+        Ok(id)
+    })
+    .await
+}
+
+fn map_model(row: &rusqlite::Row) -> Result<Model> {
+    Ok(Model {
+        id: row.get(0)?,
+        provider_id: row.get(1)?,
+        name: row.get(2)?,
+        model_key: row.get(3)?,
+        is_active: row.get(4)?,
+        created_at: row.get(5)?,
+    })
+}
+
+#[tauri::command]
+pub async fn get_models_by_provider(
+    db: State<'_, Database>,
+    provider_id: String,
+) -> AppResult<Vec<Model>> {
+    db.run(move |conn| {
+        let mut stmt = conn
+            .prepare("SELECT id, provider_id, name, model_key, is_active, created_at FROM models WHERE provider_id = ?1 ORDER BY created_at DESC")?;
+
+        let iter = stmt
+            .query_map(params![provider_id], map_model)?;
+
+        let mut result = Vec::new();
+        for item in iter {
+            result.push(item?);
+        }
+        Ok(result)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_all_models(db: State<'_, Database>) -> AppResult<Vec<Model>> {
+    db.run(|conn| {
+        let mut stmt = conn
+            .prepare("SELECT id, provider_id, name, model_key, is_active, created_at FROM models ORDER BY created_at DESC")?;
+
+        let iter = stmt.query_map([], map_model)?;
+
+        let mut result = Vec::new();
+        for item in iter {
+            result.push(item?);
+        }
+        Ok(result)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn delete_model(db: State<'_, Database>, model_id: String) -> AppResult<()> {
+    db.run(move |conn| {
+        conn.execute("DELETE FROM models WHERE id = ?1", params![model_id])?;
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn set_active_model(db: State<'_, Database>, model_id: String) -> AppResult<()> {
+    db.run(move |conn| {
+        // Transaction to ensure only one active
+        let tx = conn.transaction()?;
+        tx.execute("UPDATE models SET is_active = 0", [])?;
+        tx.execute(
+            "UPDATE models SET is_active = 1 WHERE id = ?1",
+            params![model_id],
+        )?;
+        tx.commit()?;
+
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_active_model(db: State<'_, Database>) -> AppResult<Option<Model>> {
+    db.run(|conn| {
+        conn.query_row(
+            "SELECT id, provider_id, name, model_key, is_active, created_at FROM models WHERE is_active = 1 LIMIT 1",
+            [],
+            map_model,
+        )
+        .optional()
+        .map_err(AppError::from)
+    })
+    .await
+}
+
+#[derive(Serialize, Deserialize, Debug)]
+pub struct ModelWithProvider {
+    pub model_id: String,
+    pub model_name: String,
+    pub model_key: String,
+    pub provider_url: String,
+    pub provider_key: String,
+}
+
+/// 唯一会解密 API 密钥的地方，结果仅用于后端发起请求
+pub async fn get_model_with_provider(
+    db: &Database,
+    vault: &Vault,
+    model_id: &str,
+) -> AppResult<ModelWithProvider> {
+    let model_id = model_id.to_string();
+    let mut model = db
+        .run(move |conn| {
+            conn.query_row(
+                "SELECT m.id, m.name, m.model_key, p.base_url, p.api_key 
+             FROM models m 
+             JOIN providers p ON m.provider_id = p.id 
+             WHERE m.id = ?1",
+                params![model_id],
+                |row| {
+                    Ok(ModelWithProvider {
+                        model_id: row.get(0)?,
+                        model_name: row.get(1)?,
+                        model_key: row.get(2)?,
+                        provider_url: row.get(3)?,
+                        provider_key: row.get(4)?,
+                    })
+                },
+            )
+            .optional()?
+            .ok_or_else(|| AppError::not_found(format!("模型不存在: {}", model_id)))
+        })
+        .await?;
+
+    model.provider_key = vault.decrypt(&model.provider_key)?;
+    Ok(model)
+}
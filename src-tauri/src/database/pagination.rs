@@ -0,0 +1,261 @@
+//! 基于游标的分页查询
+//!
+//! 游标由排序键拼接而成，翻页时用行值比较定位，不受偏移量增长的影响。
+
+use super::{
+    attach_message_files, map_conversation, map_message, Conversation, Database, Message,
+    CONVERSATION_FILTER,
+};
+use crate::error::{AppError, AppResult};
+use rusqlite::{named_params, params, Connection};
+use serde::Serialize;
+use tauri::State;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 200;
+const PREVIEW_CHARS: u32 = 120;
+const CURSOR_SEPARATOR: char = '|';
+
+#[derive(Serialize, Debug)]
+pub struct Page<T> {
+    pub items: Vec<T>,
+    /// 没有更多数据时为空
+    pub next_cursor: Option<String>,
+}
+
+/// 会话列表的轻量版本，不包含消息内容
+#[derive(Serialize, Debug)]
+pub struct ConversationSummary {
+    pub id: String,
+    pub title: String,
+    pub created_at: String,
+    pub updated_at: String,
+    pub is_pinned: bool,
+    pub message_count: i64,
+    pub last_message_preview: Option<String>,
+    pub last_message_role: Option<String>,
+    pub folder_id: Option<String>,
+}
+
+pub(crate) fn create_indexes(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE INDEX IF NOT EXISTS idx_messages_conversation_time
+             ON messages(conversation_id, timestamp, id);
+         CREATE INDEX IF NOT EXISTS idx_conversations_order
+             ON conversations(is_pinned, updated_at, id);",
+    )
+    .map_err(AppError::from)
+}
+
+fn page_size(limit: Option<u32>) -> u32 {
+    limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE)
+}
+
+fn invalid_cursor() -> AppError {
+    AppError::invalid_input("无效的分页游标")
+}
+
+/// 会话游标：置顶标记|更新时间|ID
+fn parse_conversation_cursor(cursor: &str) -> AppResult<(bool, String, String)> {
+    let mut parts = cursor.splitn(3, CURSOR_SEPARATOR);
+    let (Some(pinned), Some(updated_at), Some(id)) = (parts.next(), parts.next(), parts.next())
+    else {
+        return Err(invalid_cursor());
+    };
+    let pinned = match pinned {
+        "1" => true,
+        "0" => false,
+        _ => return Err(invalid_cursor()),
+    };
+    Ok((pinned, updated_at.to_string(), id.to_string()))
+}
+
+fn conversation_cursor(is_pinned: bool, updated_at: &str, id: &str) -> String {
+    format!(
+        "{}{sep}{}{sep}{}",
+        is_pinned as u8,
+        updated_at,
+        id,
+        sep = CURSOR_SEPARATOR
+    )
+}
+
+/// 消息游标：时间戳|ID
+fn parse_message_cursor(cursor: &str) -> AppResult<(String, String)> {
+    cursor
+        .split_once(CURSOR_SEPARATOR)
+        .map(|(timestamp, id)| (timestamp.to_string(), id.to_string()))
+        .ok_or_else(invalid_cursor)
+}
+
+/// 多取一条用来判断是否还有下一页
+fn split_page<T>(mut rows: Vec<T>, size: u32, cursor: impl Fn(&T) -> String) -> Page<T> {
+    let next_cursor = if rows.len() > size as usize {
+        rows.truncate(size as usize);
+        rows.last().map(cursor)
+    } else {
+        None
+    };
+    Page {
+        items: rows,
+        next_cursor,
+    }
+}
+
+/// 会话按置顶优先、最近更新在前排序；游标为空时从第一页开始
+fn query_conversations<T>(
+    conn: &Connection,
+    columns: &str,
+    filter: (Option<&str>, Option<&str>),
+    cursor: Option<&str>,
+    size: u32,
+    map: impl Fn(&rusqlite::Row) -> rusqlite::Result<T>,
+) -> AppResult<Vec<T>> {
+    let (pinned, updated_at, id) = match cursor {
+        Some(cursor) => {
+            let (pinned, updated_at, id) = parse_conversation_cursor(cursor)?;
+            (Some(pinned), Some(updated_at), Some(id))
+        }
+        None => (None, None, None),
+    };
+
+    let sql = format!(
+        "SELECT {} FROM conversations c
+         WHERE {}
+           AND (:pinned IS NULL OR (c.is_pinned, c.updated_at, c.id) < (:pinned, :updated_at, :id))
+         ORDER BY c.is_pinned DESC, c.updated_at DESC, c.id DESC
+         LIMIT :limit",
+        columns, CONVERSATION_FILTER
+    );
+    let (folder_id, tag_id) = filter;
+    let mut stmt = conn.prepare(&sql)?;
+    let rows = stmt
+        .query_map(
+            named_params! {
+                ":folder_id": folder_id,
+                ":tag_id": tag_id,
+                ":pinned": pinned,
+                ":updated_at": updated_at,
+                ":id": id,
+                ":limit": size + 1,
+            },
+            map,
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(rows)
+}
+
+#[tauri::command]
+pub async fn get_conversation_page(
+    db: State<'_, Database>,
+    folder_id: Option<String>,
+    tag_id: Option<String>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> AppResult<Page<Conversation>> {
+    let size = page_size(limit);
+    db.run(move |conn| {
+        let rows = query_conversations(
+            conn,
+            "c.id, c.title, c.summary, c.created_at, c.updated_at, c.is_pinned, c.folder_id",
+            (folder_id.as_deref(), tag_id.as_deref()),
+            cursor.as_deref(),
+            size,
+            map_conversation,
+        )?;
+        Ok(split_page(rows, size, |c| {
+            conversation_cursor(c.is_pinned, &c.updated_at, &c.id)
+        }))
+    })
+    .await
+}
+
+/// 返回消息数量和最后一条消息的摘要，用于侧边栏等只需要概览的场景
+#[tauri::command]
+pub async fn get_conversation_summaries(
+    db: State<'_, Database>,
+    folder_id: Option<String>,
+    tag_id: Option<String>,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> AppResult<Page<ConversationSummary>> {
+    let size = page_size(limit);
+    db.run(move |conn| {
+        let columns = format!(
+            "c.id, c.title, c.created_at, c.updated_at, c.is_pinned,
+             (SELECT COUNT(*) FROM messages m WHERE m.conversation_id = c.id),
+             (SELECT substr(m.content, 1, {preview}) FROM messages m WHERE m.conversation_id = c.id
+                 ORDER BY m.timestamp DESC, m.id DESC LIMIT 1),
+             (SELECT m.role FROM messages m WHERE m.conversation_id = c.id
+                 ORDER BY m.timestamp DESC, m.id DESC LIMIT 1),
+             c.folder_id",
+            preview = PREVIEW_CHARS
+        );
+        let rows = query_conversations(
+            conn,
+            &columns,
+            (folder_id.as_deref(), tag_id.as_deref()),
+            cursor.as_deref(),
+            size,
+            |row| {
+                Ok(ConversationSummary {
+                    id: row.get(0)?,
+                    title: row.get(1)?,
+                    created_at: row.get(2)?,
+                    updated_at: row.get(3)?,
+                    is_pinned: row.get(4)?,
+                    message_count: row.get(5)?,
+                    last_message_preview: row.get(6)?,
+                    last_message_role: row.get(7)?,
+                    folder_id: row.get(8)?,
+                })
+            },
+        )?;
+        Ok(split_page(rows, size, |c| {
+            conversation_cursor(c.is_pinned, &c.updated_at, &c.id)
+        }))
+    })
+    .await
+}
+
+/// 从最新的消息开始向前翻页，每页内部仍按时间正序返回，
+/// `next_cursor` 用于加载更早的消息
+#[tauri::command]
+pub async fn get_history_page(
+    db: State<'_, Database>,
+    conversation_id: String,
+    cursor: Option<String>,
+    limit: Option<u32>,
+) -> AppResult<Page<Message>> {
+    let size = page_size(limit);
+    db.run(move |conn| {
+        let (timestamp, id) = match cursor.as_deref() {
+            Some(cursor) => {
+                let (timestamp, id) = parse_message_cursor(cursor)?;
+                (Some(timestamp), Some(id))
+            }
+            None => (None, None),
+        };
+
+        let mut stmt = conn.prepare(
+            "SELECT id, conversation_id, role, content, timestamp, model_id FROM messages
+                 WHERE conversation_id = ?1 AND (?2 IS NULL OR (timestamp, id) < (?2, ?3))
+                 ORDER BY timestamp DESC, id DESC
+                 LIMIT ?4",
+        )?;
+        let rows = stmt
+            .query_map(
+                params![conversation_id, timestamp, id, size + 1],
+                map_message,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut page = split_page(rows, size, |m| {
+            format!("{}{}{}", m.timestamp, CURSOR_SEPARATOR, m.id)
+        });
+        page.items.reverse();
+        attach_message_files(conn, &mut page.items)?;
+        Ok(page)
+    })
+    .await
+}
@@ -0,0 +1,190 @@
+//! 模型服务商与模型的修改
+//!
+//! 修改时只更新传入的字段；更换 API Key 不需要删除服务商，其下的模型保持不变。
+
+use super::secrets::{self, Vault};
+use super::Database;
+use crate::error::{AppError, AppResult};
+use rusqlite::{params, Connection, OptionalExtension};
+use tauri::State;
+use uuid::Uuid;
+
+/// 校验服务商地址，只接受带主机名的 http/https 地址，去掉末尾的 `/`
+pub(crate) fn normalize_base_url(base_url: &str) -> AppResult<String> {
+    let base_url = base_url.trim();
+    let parsed = reqwest::Url::parse(base_url).map_err(|e| {
+        AppError::invalid_input(format!("无效的服务商地址: {}", base_url)).with_details(e)
+    })?;
+    if !matches!(parsed.scheme(), "http" | "https") || parsed.host_str().is_none() {
+        return Err(AppError::invalid_input(format!(
+            "服务商地址必须以 http:// 或 https:// 开头: {}",
+            base_url
+        )));
+    }
+    if parsed.query().is_some() || parsed.fragment().is_some() {
+        return Err(AppError::invalid_input(format!(
+            "服务商地址不能包含查询参数: {}",
+            base_url
+        )));
+    }
+    Ok(base_url.trim_end_matches('/').to_string())
+}
+
+pub(crate) fn require_text(value: &str, field: &str) -> AppResult<String> {
+    let value = value.trim();
+    if value.is_empty() {
+        return Err(AppError::invalid_input(format!("{}不能为空", field)));
+    }
+    Ok(value.to_string())
+}
+
+fn ensure_provider_exists(conn: &Connection, provider_id: &str) -> AppResult<()> {
+    conn.query_row(
+        "SELECT 1 FROM providers WHERE id = ?1",
+        params![provider_id],
+        |_| Ok(()),
+    )
+    .optional()?
+    .ok_or_else(|| AppError::not_found(format!("服务商不存在: {}", provider_id)))
+}
+
+/// 只更新传入的字段；`api_key` 传空字符串表示清除密钥
+#[tauri::command]
+pub async fn update_provider(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    provider_id: String,
+    name: Option<String>,
+    base_url: Option<String>,
+    api_key: Option<String>,
+    icon: Option<String>,
+) -> AppResult<()> {
+    let name = name.map(|n| require_text(&n, "服务商名称")).transpose()?;
+    let base_url = base_url.as_deref().map(normalize_base_url).transpose()?;
+    let (encrypted_key, hint) = match api_key {
+        Some(key) => (Some(vault.encrypt(&key)?), Some(secrets::key_hint(&key))),
+        None => (None, None),
+    };
+
+    db.run(move |conn| {
+        let updated = conn.execute(
+            "UPDATE providers SET
+                name = COALESCE(?1, name),
+                base_url = COALESCE(?2, base_url),
+                api_key = COALESCE(?3, api_key),
+                api_key_hint = COALESCE(?4, api_key_hint),
+                icon = COALESCE(?5, icon)
+             WHERE id = ?6",
+            params![name, base_url, encrypted_key, hint, icon, provider_id],
+        )?;
+        if updated == 0 {
+            return Err(AppError::not_found(format!(
+                "服务商不存在: {}",
+                provider_id
+            )));
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// 只更新传入的字段，传入 `provider_id` 时把模型移到另一个服务商下
+#[tauri::command]
+pub async fn update_model(
+    db: State<'_, Database>,
+    model_id: String,
+    name: Option<String>,
+    model_key: Option<String>,
+    provider_id: Option<String>,
+) -> AppResult<()> {
+    let name = name.map(|n| require_text(&n, "模型名称")).transpose()?;
+    let model_key = model_key
+        .map(|k| require_text(&k, "模型标识"))
+        .transpose()?;
+
+    db.run(move |conn| {
+        if let Some(provider_id) = &provider_id {
+            ensure_provider_exists(conn, provider_id)?;
+        }
+        let updated = conn.execute(
+            "UPDATE models SET
+                name = COALESCE(?1, name),
+                model_key = COALESCE(?2, model_key),
+                provider_id = COALESCE(?3, provider_id)
+             WHERE id = ?4",
+            params![name, model_key, provider_id, model_id],
+        )?;
+        if updated == 0 {
+            return Err(AppError::not_found(format!("模型不存在: {}", model_id)));
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// 复制服务商及其所有模型，返回新服务商的 ID。
+/// 未传入 `api_key` 时沿用原服务商的密钥，复制出的模型都不是当前激活的模型
+#[tauri::command]
+pub async fn duplicate_provider(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    provider_id: String,
+    name: Option<String>,
+    api_key: Option<String>,
+) -> AppResult<String> {
+    let name = name.map(|n| require_text(&n, "服务商名称")).transpose()?;
+    let (encrypted_key, hint) = match api_key {
+        Some(key) => (Some(vault.encrypt(&key)?), Some(secrets::key_hint(&key))),
+        None => (None, None),
+    };
+
+    db.run(move |conn| {
+        let tx = conn.transaction()?;
+        let (source_name, source_key, source_hint): (String, String, Option<String>) = tx
+            .query_row(
+                "SELECT name, api_key, api_key_hint FROM providers WHERE id = ?1",
+                params![provider_id],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .optional()?
+            .ok_or_else(|| AppError::not_found(format!("服务商不存在: {}", provider_id)))?;
+
+        let new_id = Uuid::new_v4().to_string();
+        let now = chrono::Utc::now().to_rfc3339();
+        tx.execute(
+            "INSERT INTO providers (id, name, base_url, api_key, api_key_hint, icon, created_at)
+             SELECT ?1, ?2, base_url, ?3, ?4, icon, ?5 FROM providers WHERE id = ?6",
+            params![
+                new_id,
+                name.unwrap_or_else(|| format!("{} (副本)", source_name)),
+                encrypted_key.unwrap_or(source_key),
+                hint.or(source_hint),
+                now,
+                provider_id
+            ],
+        )?;
+
+        // 沿用原模型的创建时间，保持副本中模型的排列顺序
+        let model_rows: Vec<(String, String, String)> = {
+            let mut stmt = tx.prepare(
+                "SELECT name, model_key, created_at FROM models WHERE provider_id = ?1 ORDER BY created_at ASC",
+            )?;
+            let rows = stmt
+                .query_map(params![provider_id], |row| {
+                    Ok((row.get(0)?, row.get(1)?, row.get(2)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            rows
+        };
+        for (model_name, model_key, created_at) in model_rows {
+            tx.execute(
+                "INSERT INTO models (id, provider_id, name, model_key, is_active, created_at) VALUES (?1, ?2, ?3, ?4, 0, ?5)",
+                params![Uuid::new_v4().to_string(), new_id, model_name, model_key, created_at],
+            )?;
+        }
+
+        tx.commit()?;
+        Ok(new_id)
+    })
+    .await
+}
@@ -0,0 +1,511 @@
+//! 服务商 API 密钥的加密存储
+//!
+//! 密钥材料来自本地密钥文件或用户主密码，经 Argon2 派生出 AES-256-GCM 密钥。
+//! 数据库中保存的格式为 `enc:v1:<base64(nonce || ciphertext)>`。
+
+use super::{get_meta, get_workspace_dir, set_meta, Database};
+use crate::error::{AppError, AppResult};
+use aes_gcm::aead::rand_core::RngCore;
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use argon2::Argon2;
+use base64::engine::general_purpose::STANDARD as BASE64;
+use base64::Engine;
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{AppHandle, Runtime, State};
+
+const ENCRYPTED_PREFIX: &str = "enc:v1:";
+const KEY_FILE_NAME: &str = "secret.key";
+const NONCE_LEN: usize = 12;
+const SALT_LEN: usize = 16;
+const KEY_MATERIAL_LEN: usize = 32;
+const MIN_PASSWORD_LEN: usize = 8;
+// 用于校验主密码或密钥文件是否正确的已知明文
+const CHECK_PLAINTEXT: &str = "moro-secret-check";
+
+const META_MODE: &str = "secret_mode";
+const META_SALT: &str = "secret_salt";
+const META_CHECK: &str = "secret_check";
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SecretMode {
+    KeyFile,
+    Password,
+}
+
+impl SecretMode {
+    fn as_str(&self) -> &'static str {
+        match self {
+            SecretMode::KeyFile => "key_file",
+            SecretMode::Password => "password",
+        }
+    }
+
+    fn parse(value: &str) -> Option<Self> {
+        match value {
+            "key_file" => Some(SecretMode::KeyFile),
+            "password" => Some(SecretMode::Password),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct SecretsStatus {
+    pub mode: SecretMode,
+    pub unlocked: bool,
+    /// 密钥库无法加载的原因，例如密钥文件丢失或损坏
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+struct VaultState {
+    mode: SecretMode,
+    cipher: Option<Aes256Gcm>,
+    error: Option<String>,
+}
+
+/// API 密钥保险库，主密码模式下解锁前不持有任何密钥
+pub struct Vault {
+    key_file: RwLock<PathBuf>,
+    state: RwLock<VaultState>,
+}
+
+impl Vault {
+    fn new(key_file: PathBuf, mode: SecretMode, cipher: Option<Aes256Gcm>) -> Self {
+        Self {
+            key_file: RwLock::new(key_file),
+            state: RwLock::new(VaultState {
+                mode,
+                cipher,
+                error: None,
+            }),
+        }
+    }
+
+    fn key_file(&self) -> PathBuf {
+        self.key_file.read().unwrap().clone()
+    }
+
+    pub fn status(&self) -> SecretsStatus {
+        let state = self.state.read().unwrap();
+        SecretsStatus {
+            mode: state.mode,
+            unlocked: state.cipher.is_some(),
+            error: state.error.clone(),
+        }
+    }
+
+    fn mode(&self) -> SecretMode {
+        self.state.read().unwrap().mode
+    }
+
+    fn cipher(&self) -> AppResult<Aes256Gcm> {
+        let state = self.state.read().unwrap();
+        if let Some(cipher) = &state.cipher {
+            return Ok(cipher.clone());
+        }
+        Err(match &state.error {
+            Some(error) => AppError::locked("API 密钥库无法加载").with_details(error),
+            None => AppError::locked("API 密钥库已锁定，请先输入主密码解锁"),
+        })
+    }
+
+    fn set(&self, mode: SecretMode, cipher: Option<Aes256Gcm>) {
+        *self.state.write().unwrap() = VaultState {
+            mode,
+            cipher,
+            error: None,
+        };
+    }
+
+    /// 加载失败时保持锁定，并记录原因供前端提示
+    pub(crate) fn set_failed(&self, error: &AppError) {
+        *self.state.write().unwrap() = VaultState {
+            mode: SecretMode::KeyFile,
+            cipher: None,
+            error: Some(error.to_string()),
+        };
+    }
+
+    pub fn encrypt(&self, plaintext: &str) -> AppResult<String> {
+        if plaintext.is_empty() {
+            return Ok(String::new());
+        }
+        encrypt_with(&self.cipher()?, plaintext)
+    }
+
+    pub fn decrypt(&self, stored: &str) -> AppResult<String> {
+        // 尚未迁移的旧数据仍是明文
+        if !stored.starts_with(ENCRYPTED_PREFIX) {
+            return Ok(stored.to_string());
+        }
+        decrypt_with(&self.cipher()?, stored)
+    }
+}
+
+/// 掩码展示用的密钥末四位，过短的密钥不保留任何字符
+pub fn key_hint(api_key: &str) -> String {
+    let chars: Vec<char> = api_key.chars().collect();
+    if chars.len() <= 8 {
+        return String::new();
+    }
+    chars[chars.len() - 4..].iter().collect()
+}
+
+pub fn mask_api_key(stored: &str, hint: &str) -> String {
+    if stored.is_empty() {
+        return String::new();
+    }
+    format!("••••••••{}", hint)
+}
+
+fn random_bytes(len: usize) -> Vec<u8> {
+    let mut bytes = vec![0u8; len];
+    OsRng.fill_bytes(&mut bytes);
+    bytes
+}
+
+fn derive_cipher(material: &[u8], salt: &[u8]) -> AppResult<Aes256Gcm> {
+    let mut key = [0u8; 32];
+    Argon2::default()
+        .hash_password_into(material, salt, &mut key)
+        .map_err(|e| AppError::internal("派生密钥失败").with_details(e))?;
+    Ok(Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(&key)))
+}
+
+fn encrypt_with(cipher: &Aes256Gcm, plaintext: &str) -> AppResult<String> {
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+    let ciphertext = cipher
+        .encrypt(&nonce, plaintext.as_bytes())
+        .map_err(|e| AppError::internal("加密失败").with_details(e))?;
+
+    let mut payload = nonce.to_vec();
+    payload.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}", ENCRYPTED_PREFIX, BASE64.encode(payload)))
+}
+
+fn decrypt_with(cipher: &Aes256Gcm, stored: &str) -> AppResult<String> {
+    let encoded = stored
+        .strip_prefix(ENCRYPTED_PREFIX)
+        .ok_or_else(|| AppError::invalid_input("密文格式无效"))?;
+    let payload = BASE64
+        .decode(encoded)
+        .map_err(|e| AppError::invalid_input("密文格式无效").with_details(e))?;
+    if payload.len() <= NONCE_LEN {
+        return Err(AppError::invalid_input("密文格式无效"));
+    }
+
+    let (nonce, ciphertext) = payload.split_at(NONCE_LEN);
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), ciphertext)
+        .map_err(|_| AppError::locked("解密失败，密钥不匹配"))?;
+    String::from_utf8(plaintext)
+        .map_err(|e| AppError::invalid_input("密文格式无效").with_details(e))
+}
+
+fn read_key_file(path: &Path) -> AppResult<Vec<u8>> {
+    let content =
+        fs::read_to_string(path).map_err(|e| AppError::io("读取密钥文件失败").with_details(e))?;
+    BASE64
+        .decode(content.trim())
+        .map_err(|e| AppError::invalid_input("密钥文件格式无效").with_details(e))
+}
+
+fn write_key_file(path: &Path, material: &[u8]) -> AppResult<()> {
+    fs::write(path, BASE64.encode(material))
+        .map_err(|e| AppError::io("写入密钥文件失败").with_details(e))?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(path, fs::Permissions::from_mode(0o600))
+            .map_err(|e| AppError::io("设置密钥文件权限失败").with_details(e))?;
+    }
+
+    Ok(())
+}
+
+/// 更换密钥时新密钥文件的临时位置，数据库提交成功后替换正式的密钥文件
+fn pending_key_file(key_file: &Path) -> PathBuf {
+    key_file.with_extension("key.new")
+}
+
+/// 用保存的盐派生密钥，并用校验值确认密钥材料正确
+fn load_cipher(conn: &Connection, material: &[u8]) -> AppResult<Aes256Gcm> {
+    let salt = get_meta(conn, META_SALT)?.ok_or_else(|| AppError::internal("缺少密钥参数"))?;
+    let salt = BASE64.decode(salt)?;
+    let check = get_meta(conn, META_CHECK)?.ok_or_else(|| AppError::internal("缺少密钥参数"))?;
+
+    let cipher = derive_cipher(material, &salt)?;
+    match decrypt_with(&cipher, &check) {
+        Ok(value) if value == CHECK_PLAINTEXT => Ok(cipher),
+        _ => Err(AppError::locked("主密码或密钥文件不正确")),
+    }
+}
+
+/// 生成新的盐和校验值并写入 app_meta
+fn store_key_params(conn: &Connection, mode: SecretMode, material: &[u8]) -> AppResult<Aes256Gcm> {
+    let salt = random_bytes(SALT_LEN);
+    let cipher = derive_cipher(material, &salt)?;
+    let check = encrypt_with(&cipher, CHECK_PLAINTEXT)?;
+
+    set_meta(conn, META_MODE, mode.as_str())?;
+    set_meta(conn, META_SALT, &BASE64.encode(salt))?;
+    set_meta(conn, META_CHECK, &check)?;
+
+    Ok(cipher)
+}
+
+/// 加密旧版本遗留的明文密钥
+fn encrypt_legacy_keys(conn: &Connection, cipher: &Aes256Gcm) -> AppResult<()> {
+    let mut stmt = conn.prepare(
+        "SELECT id, api_key FROM providers WHERE api_key != '' AND api_key NOT LIKE 'enc:v1:%'",
+    )?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+        })?
+        .collect::<Result<Vec<_>, _>>()?;
+
+    for (id, api_key) in rows {
+        conn.execute(
+            "UPDATE providers SET api_key = ?1, api_key_hint = ?2 WHERE id = ?3",
+            params![encrypt_with(cipher, &api_key)?, key_hint(&api_key), id],
+        )?;
+    }
+    Ok(())
+}
+
+/// 读取密钥文件并派生密钥
+///
+/// 更换密钥时数据库已提交、新密钥文件却没能替换旧文件的，改用遗留的新密钥文件并补上替换
+fn load_key_file(conn: &Connection, key_file: &Path) -> AppResult<Aes256Gcm> {
+    let result = read_key_file(key_file).and_then(|material| load_cipher(conn, &material));
+    if result.is_ok() {
+        return result;
+    }
+
+    let pending = pending_key_file(key_file);
+    let recovered = read_key_file(&pending).and_then(|material| load_cipher(conn, &material));
+    match recovered {
+        Ok(cipher) => {
+            // 替换失败时下次启动仍会读取临时文件
+            let _ = fs::rename(&pending, key_file);
+            Ok(cipher)
+        }
+        Err(_) => result,
+    }
+}
+
+/// 从数据库读取密钥参数并准备密钥，首次运行时生成本地密钥文件
+fn load_state(conn: &Connection, key_file: &Path) -> AppResult<(SecretMode, Option<Aes256Gcm>)> {
+    let mode = get_meta(conn, META_MODE)?;
+    let (mode, cipher) = match mode.as_deref().and_then(SecretMode::parse) {
+        Some(SecretMode::Password) => (SecretMode::Password, None),
+        Some(SecretMode::KeyFile) => (SecretMode::KeyFile, Some(load_key_file(conn, key_file)?)),
+        None => {
+            let material = random_bytes(KEY_MATERIAL_LEN);
+            write_key_file(key_file, &material)?;
+            let cipher = store_key_params(conn, SecretMode::KeyFile, &material)?;
+            (SecretMode::KeyFile, Some(cipher))
+        }
+    };
+
+    if let Some(cipher) = &cipher {
+        encrypt_legacy_keys(conn, cipher)?;
+    }
+
+    Ok((mode, cipher))
+}
+
+/// 在数据库解锁后加载密钥库
+pub(crate) async fn load_vault(db: &Database, vault: &Vault) -> AppResult<()> {
+    let key_file = vault.key_file();
+    match db.run(move |conn| load_state(conn, &key_file)).await {
+        Ok((mode, cipher)) => {
+            vault.set(mode, cipher);
+            Ok(())
+        }
+        Err(e) => {
+            vault.set_failed(&e);
+            Err(e)
+        }
+    }
+}
+
+/// 切换工作区时预先加载的密钥库状态
+pub(crate) struct PreparedVault {
+    key_file: PathBuf,
+    mode: SecretMode,
+    cipher: Option<Aes256Gcm>,
+}
+
+/// 用新工作区的数据库和密钥文件准备密钥库，数据库已解锁时立即加载；
+/// 失败时不影响当前使用的密钥库
+pub(crate) async fn prepare_vault(db: &Database, workspace_dir: &Path) -> AppResult<PreparedVault> {
+    let key_file = workspace_dir.join(KEY_FILE_NAME);
+    let (mode, cipher) = if db.is_unlocked() {
+        let key_file = key_file.clone();
+        db.run(move |conn| load_state(conn, &key_file)).await?
+    } else {
+        (SecretMode::KeyFile, None)
+    };
+    Ok(PreparedVault {
+        key_file,
+        mode,
+        cipher,
+    })
+}
+
+impl Vault {
+    /// 切换工作区后改用新工作区的密钥库
+    pub(crate) fn replace_with(&self, prepared: PreparedVault) {
+        *self.key_file.write().unwrap() = prepared.key_file;
+        self.set(prepared.mode, prepared.cipher);
+    }
+}
+
+/// 加密数据库在解锁前无法读取密钥参数，此时密钥库保持锁定，解锁数据库后再加载
+///
+/// 密钥文件丢失等加载失败的情况不影响启动，密钥库保持锁定并通过 `get_secrets_status` 报告原因
+pub fn init_vault<R: Runtime>(app_handle: &AppHandle<R>, db: &Database) -> AppResult<Vault> {
+    let key_file = get_workspace_dir(app_handle)?.join(KEY_FILE_NAME);
+    let vault = Vault::new(key_file, SecretMode::KeyFile, None);
+
+    if db.is_unlocked() {
+        match db.with_conn(|conn| load_state(conn, &vault.key_file())) {
+            Ok((mode, cipher)) => vault.set(mode, cipher),
+            Err(e) => vault.set_failed(&e),
+        }
+    }
+
+    Ok(vault)
+}
+
+#[tauri::command]
+pub fn get_secrets_status(vault: State<'_, Vault>) -> SecretsStatus {
+    vault.status()
+}
+
+#[tauri::command]
+pub async fn unlock_secrets(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    password: String,
+) -> AppResult<()> {
+    if vault.mode() != SecretMode::Password {
+        return Ok(());
+    }
+
+    let cipher = db
+        .run(move |conn| {
+            let cipher = load_cipher(conn, password.as_bytes())?;
+            encrypt_legacy_keys(conn, &cipher)?;
+            Ok(cipher)
+        })
+        .await?;
+
+    vault.set(SecretMode::Password, Some(cipher));
+    Ok(())
+}
+
+#[tauri::command]
+pub fn lock_secrets(vault: State<'_, Vault>) {
+    // 密钥文件模式下锁定没有意义，启动时会重新读取密钥文件
+    if vault.mode() == SecretMode::Password {
+        vault.set(SecretMode::Password, None);
+    }
+}
+
+/// 更换加密密钥并重新加密所有 API 密钥
+///
+/// 提供 `new_password` 时切换为主密码模式，否则生成新的本地密钥文件。
+#[tauri::command]
+pub async fn rotate_secret_key(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    new_password: Option<String>,
+) -> AppResult<SecretsStatus> {
+    let old_cipher = vault.cipher()?;
+
+    let (mode, material) = match new_password {
+        Some(password) => {
+            if password.chars().count() < MIN_PASSWORD_LEN {
+                return Err(AppError::invalid_input(format!(
+                    "主密码至少需要 {} 个字符",
+                    MIN_PASSWORD_LEN
+                )));
+            }
+            (SecretMode::Password, password.into_bytes())
+        }
+        None => (SecretMode::KeyFile, random_bytes(KEY_MATERIAL_LEN)),
+    };
+
+    // 新密钥文件先写到临时位置，数据库提交成功后再替换
+    let key_file = vault.key_file();
+    let pending_key_file = pending_key_file(&key_file);
+    if mode == SecretMode::KeyFile {
+        write_key_file(&pending_key_file, &material)?;
+    }
+
+    let result = db
+        .run(move |conn| {
+            let tx = conn.transaction()?;
+            let new_cipher = store_key_params(&tx, mode, &material)?;
+
+            let rows = {
+                let mut stmt =
+                    tx.prepare("SELECT id, api_key FROM providers WHERE api_key != ''")?;
+                let rows = stmt
+                    .query_map([], |row| {
+                        Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+                    })?
+                    .collect::<Result<Vec<_>, _>>()?;
+                rows
+            };
+
+            for (id, stored) in rows {
+                let api_key = if stored.starts_with(ENCRYPTED_PREFIX) {
+                    decrypt_with(&old_cipher, &stored)?
+                } else {
+                    stored
+                };
+                tx.execute(
+                    "UPDATE providers SET api_key = ?1, api_key_hint = ?2 WHERE id = ?3",
+                    params![encrypt_with(&new_cipher, &api_key)?, key_hint(&api_key), id],
+                )?;
+            }
+
+            tx.commit()?;
+            Ok(new_cipher)
+        })
+        .await;
+
+    let new_cipher = match result {
+        Ok(cipher) => cipher,
+        Err(e) => {
+            let _ = fs::remove_file(&pending_key_file);
+            return Err(e);
+        }
+    };
+
+    // 数据库已经改用新密钥，无论密钥文件能否替换都要使用新密钥
+    vault.set(mode, Some(new_cipher));
+    match mode {
+        SecretMode::KeyFile => fs::rename(&pending_key_file, &key_file).map_err(|e| {
+            AppError::io("替换密钥文件失败，下次启动时会使用新密钥文件").with_details(e)
+        })?,
+        // 改用主密码后旧密钥文件不再需要
+        SecretMode::Password => {
+            let _ = fs::remove_file(&key_file);
+        }
+    }
+
+    Ok(vault.status())
+}
@@ -0,0 +1,210 @@
+//! 会话标签，一个会话可以有多个标签
+
+use super::Database;
+use crate::error::{AppError, AppResult, ErrorCode};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use tauri::State;
+use uuid::Uuid;
+
+const DEFAULT_COLOR: &str = "#808080";
+
+#[derive(Serialize, Debug)]
+pub struct Tag {
+    pub id: String,
+    pub name: String,
+    pub color: String,
+    pub created_at: String,
+}
+
+pub(crate) fn create_tables(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS tags (
+            id TEXT PRIMARY KEY,
+            name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+            color TEXT NOT NULL,
+            created_at TEXT NOT NULL
+        );
+        CREATE TABLE IF NOT EXISTS conversation_tags (
+            conversation_id TEXT NOT NULL,
+            tag_id TEXT NOT NULL,
+            PRIMARY KEY(conversation_id, tag_id),
+            FOREIGN KEY(conversation_id) REFERENCES conversations(id) ON DELETE CASCADE,
+            FOREIGN KEY(tag_id) REFERENCES tags(id) ON DELETE CASCADE
+        );
+        CREATE INDEX IF NOT EXISTS idx_conversation_tags_tag ON conversation_tags(tag_id);",
+    )
+    .map_err(AppError::from)
+}
+
+fn map_tag(row: &rusqlite::Row) -> rusqlite::Result<Tag> {
+    Ok(Tag {
+        id: row.get(0)?,
+        name: row.get(1)?,
+        color: row.get(2)?,
+        created_at: row.get(3)?,
+    })
+}
+
+fn validate_name(name: &str) -> AppResult<String> {
+    let name = name.trim();
+    if name.is_empty() {
+        return Err(AppError::invalid_input("标签名称不能为空"));
+    }
+    Ok(name.to_string())
+}
+
+/// 颜色统一保存为小写的 `#rrggbb`
+fn normalize_color(color: &str) -> AppResult<String> {
+    let color = color.trim();
+    let valid = color.len() == 7
+        && color.starts_with('#')
+        && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if !valid {
+        return Err(AppError::invalid_input(format!(
+            "无效的标签颜色: {}",
+            color
+        )));
+    }
+    Ok(color.to_ascii_lowercase())
+}
+
+fn unique_name_error(e: rusqlite::Error, name: &str) -> AppError {
+    match AppError::from(e) {
+        error if error.code == ErrorCode::Conflict => {
+            AppError::conflict(format!("标签已存在: {}", name))
+        }
+        error => error,
+    }
+}
+
+#[tauri::command]
+pub async fn create_tag(
+    db: State<'_, Database>,
+    name: String,
+    color: Option<String>,
+) -> AppResult<Tag> {
+    let name = validate_name(&name)?;
+    let color = normalize_color(color.as_deref().unwrap_or(DEFAULT_COLOR))?;
+    db.run(move |conn| {
+        let tag = Tag {
+            id: Uuid::new_v4().to_string(),
+            name,
+            color,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        conn.execute(
+            "INSERT INTO tags (id, name, color, created_at) VALUES (?1, ?2, ?3, ?4)",
+            params![tag.id, tag.name, tag.color, tag.created_at],
+        )
+        .map_err(|e| unique_name_error(e, &tag.name))?;
+        Ok(tag)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_tags(db: State<'_, Database>) -> AppResult<Vec<Tag>> {
+    db.run(|conn| {
+        let mut stmt =
+            conn.prepare("SELECT id, name, color, created_at FROM tags ORDER BY name ASC")?;
+        let tags = stmt
+            .query_map([], map_tag)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tags)
+    })
+    .await
+}
+
+/// 只更新传入的字段
+#[tauri::command]
+pub async fn update_tag(
+    db: State<'_, Database>,
+    tag_id: String,
+    name: Option<String>,
+    color: Option<String>,
+) -> AppResult<()> {
+    let name = name.as_deref().map(validate_name).transpose()?;
+    let color = color.as_deref().map(normalize_color).transpose()?;
+    db.run(move |conn| {
+        let updated = conn
+            .execute(
+                "UPDATE tags SET name = COALESCE(?1, name), color = COALESCE(?2, color) WHERE id = ?3",
+                params![name, color, tag_id],
+            )
+            .map_err(|e| unique_name_error(e, name.as_deref().unwrap_or_default()))?;
+        if updated == 0 {
+            return Err(AppError::not_found(format!("标签不存在: {}", tag_id)));
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// 删除标签，会话本身不受影响
+#[tauri::command]
+pub async fn delete_tag(db: State<'_, Database>, tag_id: String) -> AppResult<()> {
+    db.run(move |conn| {
+        conn.execute("DELETE FROM tags WHERE id = ?1", params![tag_id])?;
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn tag_conversation(
+    db: State<'_, Database>,
+    conversation_id: String,
+    tag_id: String,
+) -> AppResult<()> {
+    db.run(move |conn| {
+        conn.execute(
+            "INSERT OR IGNORE INTO conversation_tags (conversation_id, tag_id) VALUES (?1, ?2)",
+            params![conversation_id, tag_id],
+        )
+        .map_err(|e| match e.sqlite_error().map(|err| err.extended_code) {
+            Some(rusqlite::ffi::SQLITE_CONSTRAINT_FOREIGNKEY) => {
+                AppError::not_found("会话或标签不存在")
+            }
+            _ => AppError::from(e),
+        })?;
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn untag_conversation(
+    db: State<'_, Database>,
+    conversation_id: String,
+    tag_id: String,
+) -> AppResult<()> {
+    db.run(move |conn| {
+        conn.execute(
+            "DELETE FROM conversation_tags WHERE conversation_id = ?1 AND tag_id = ?2",
+            params![conversation_id, tag_id],
+        )?;
+        Ok(())
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn get_conversation_tags(
+    db: State<'_, Database>,
+    conversation_id: String,
+) -> AppResult<Vec<Tag>> {
+    db.run(move |conn| {
+        let mut stmt = conn.prepare(
+            "SELECT t.id, t.name, t.color, t.created_at FROM tags t
+                 JOIN conversation_tags ct ON ct.tag_id = t.id
+                 WHERE ct.conversation_id = ?1
+                 ORDER BY t.name ASC",
+        )?;
+        let tags = stmt
+            .query_map(params![conversation_id], map_tag)?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(tags)
+    })
+    .await
+}
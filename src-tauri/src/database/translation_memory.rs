@@ -0,0 +1,408 @@
+//! 翻译记忆
+//!
+//! 保存原文与译文的句段对，按语言对和项目区分。翻译时完全相同的原文直接复用，
+//! 相似的原文（按编辑距离计算相似度）作为参考示例交给模型。
+//! 人工审校过的记录优先使用，也不会被模型的译文覆盖。
+
+use super::{ensure_column, Database};
+use crate::error::{AppError, AppResult};
+use rusqlite::{named_params, Connection, OptionalExtension};
+use serde::Serialize;
+use std::collections::HashMap;
+use tauri::State;
+use uuid::Uuid;
+
+const DEFAULT_LOOKUP_LIMIT: u32 = 5;
+const MAX_LOOKUP_LIMIT: u32 = 50;
+/// 低于该相似度的记录不算模糊匹配
+const MIN_FUZZY_SCORE: f32 = 0.7;
+// 模糊匹配只比较较短的句段，并限制每次比较的候选数量
+const MAX_FUZZY_CHARS: usize = 300;
+const MAX_FUZZY_CANDIDATES: u32 = 2000;
+
+const SCOPE_FILTER: &str = "target_language = :target
+    AND (:source = '' OR source_language IN (:source, ''))";
+// 同一项目、审校过、语言一致、最近更新的记录优先
+const SCOPE_ORDER: &str = "(project = :project) DESC, reviewed DESC,
+    (source_language = :source) DESC, updated_at DESC";
+const MATCH_COLUMNS: &str =
+    "id, source_text, target_text, source_language, target_language, project, updated_at, reviewed";
+
+#[derive(Serialize, Debug, Clone)]
+pub struct MemoryMatch {
+    pub id: String,
+    pub source_text: String,
+    pub target_text: String,
+    /// 未知时为空
+    pub source_language: String,
+    pub target_language: String,
+    pub project: String,
+    /// 相似度，1 表示完全相同
+    pub score: f32,
+    pub updated_at: String,
+    /// 人工审校过
+    pub reviewed: bool,
+}
+
+/// 语言对和项目，源语言未知时为空，匹配任意源语言
+#[derive(Debug, Clone)]
+pub(crate) struct MemoryScope {
+    source_language: String,
+    target_language: String,
+    project: String,
+}
+
+impl MemoryScope {
+    pub(crate) fn new(source: Option<&str>, target: &str, project: Option<&str>) -> Self {
+        let normalize = |value: Option<&str>| value.unwrap_or_default().trim().to_lowercase();
+        Self {
+            source_language: normalize(source),
+            target_language: normalize(Some(target)),
+            project: project.unwrap_or_default().trim().to_string(),
+        }
+    }
+
+    /// 检测到源语言后，保存记录时使用
+    pub(crate) fn with_source(&self, source: Option<&str>) -> Self {
+        match source.map(str::trim).filter(|s| !s.is_empty()) {
+            Some(source) if self.source_language.is_empty() => Self {
+                source_language: source.to_lowercase(),
+                ..self.clone()
+            },
+            _ => self.clone(),
+        }
+    }
+}
+
+pub(crate) fn create_tables(conn: &Connection) -> AppResult<()> {
+    conn.execute_batch(
+        "CREATE TABLE IF NOT EXISTS translation_memory (
+            id TEXT PRIMARY KEY,
+            source_language TEXT NOT NULL DEFAULT '',
+            target_language TEXT NOT NULL,
+            project TEXT NOT NULL DEFAULT '',
+            source_text TEXT NOT NULL,
+            target_text TEXT NOT NULL,
+            created_at TEXT NOT NULL,
+            updated_at TEXT NOT NULL,
+            UNIQUE(source_language, target_language, project, source_text)
+        );
+        CREATE INDEX IF NOT EXISTS idx_translation_memory_lookup
+            ON translation_memory(target_language, source_text);",
+    )?;
+    ensure_column(
+        conn,
+        "translation_memory",
+        "reviewed",
+        "INTEGER NOT NULL DEFAULT 0",
+    )?;
+    Ok(())
+}
+
+fn map_match(row: &rusqlite::Row) -> rusqlite::Result<MemoryMatch> {
+    Ok(MemoryMatch {
+        id: row.get(0)?,
+        source_text: row.get(1)?,
+        target_text: row.get(2)?,
+        source_language: row.get(3)?,
+        target_language: row.get(4)?,
+        project: row.get(5)?,
+        score: 1.0,
+        updated_at: row.get(6)?,
+        reviewed: row.get(7)?,
+    })
+}
+
+/// 按字符计算的编辑距离，超过 `max` 时提前返回 `None`
+fn edit_distance(a: &[char], b: &[char], max: usize) -> Option<usize> {
+    if a.len().abs_diff(b.len()) > max {
+        return None;
+    }
+    let mut previous: Vec<usize> = (0..=b.len()).collect();
+    let mut current = vec![0; b.len() + 1];
+    for (i, ca) in a.iter().enumerate() {
+        current[0] = i + 1;
+        let mut row_min = current[0];
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = previous[j] + usize::from(ca != cb);
+            current[j + 1] = substitution.min(previous[j + 1] + 1).min(current[j] + 1);
+            row_min = row_min.min(current[j + 1]);
+        }
+        if row_min > max {
+            return None;
+        }
+        std::mem::swap(&mut previous, &mut current);
+    }
+    Some(previous[b.len()]).filter(|distance| *distance <= max)
+}
+
+/// 相似度 = 1 - 编辑距离 / 较长句段的字符数
+fn similarity(a: &[char], b: &[char], min_score: f32) -> Option<f32> {
+    let longest = a.len().max(b.len());
+    if longest == 0 {
+        return None;
+    }
+    let max_distance = ((1.0 - min_score) * longest as f32).floor() as usize;
+    edit_distance(a, b, max_distance).map(|distance| 1.0 - distance as f32 / longest as f32)
+}
+
+/// 完全相同的原文对应的译文，以及译文是否审校过
+pub(crate) fn exact_entries(
+    conn: &Connection,
+    scope: &MemoryScope,
+    sources: &[String],
+) -> AppResult<HashMap<String, (String, bool)>> {
+    let mut stmt = conn.prepare(&format!(
+        "SELECT target_text, reviewed FROM translation_memory
+         WHERE source_text = :text AND {} ORDER BY {} LIMIT 1",
+        SCOPE_FILTER, SCOPE_ORDER
+    ))?;
+
+    let mut matches = HashMap::new();
+    for source in sources {
+        let entry: Option<(String, bool)> = stmt
+            .query_row(
+                named_params! {
+                    ":text": source,
+                    ":target": scope.target_language,
+                    ":source": scope.source_language,
+                    ":project": scope.project,
+                },
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        if let Some(entry) = entry {
+            matches.insert(source.clone(), entry);
+        }
+    }
+    Ok(matches)
+}
+
+/// 完全相同的原文对应的译文
+pub(crate) fn exact_matches(
+    conn: &Connection,
+    scope: &MemoryScope,
+    sources: &[String],
+) -> AppResult<HashMap<String, String>> {
+    Ok(exact_entries(conn, scope, sources)?
+        .into_iter()
+        .map(|(source, (target, _))| (source, target))
+        .collect())
+}
+
+/// 每条原文最相似的 `limit` 条记录，不含完全相同的原文
+pub(crate) fn fuzzy_matches(
+    conn: &Connection,
+    scope: &MemoryScope,
+    sources: &[String],
+    limit: usize,
+) -> AppResult<Vec<Vec<MemoryMatch>>> {
+    let sources: Vec<Vec<char>> = sources.iter().map(|s| s.chars().collect()).collect();
+    let lengths = sources
+        .iter()
+        .map(Vec::len)
+        .filter(|len| *len > 0 && *len <= MAX_FUZZY_CHARS);
+    let (Some(shortest), Some(longest)) = (lengths.clone().min(), lengths.max()) else {
+        return Ok(vec![Vec::new(); sources.len()]);
+    };
+
+    // 相似度达标的句段长度不会相差太多
+    let min_len = (shortest as f32 * MIN_FUZZY_SCORE).floor() as usize;
+    let max_len = (longest as f32 / MIN_FUZZY_SCORE).ceil() as usize;
+    let mut stmt = conn.prepare(&format!(
+        "SELECT {} FROM translation_memory
+         WHERE {} AND length(source_text) BETWEEN :min_len AND :max_len
+         ORDER BY {} LIMIT :limit",
+        MATCH_COLUMNS, SCOPE_FILTER, SCOPE_ORDER
+    ))?;
+    let candidates = stmt
+        .query_map(
+            named_params! {
+                ":target": scope.target_language,
+                ":source": scope.source_language,
+                ":project": scope.project,
+                ":min_len": min_len as i64,
+                ":max_len": max_len as i64,
+                ":limit": MAX_FUZZY_CANDIDATES,
+            },
+            map_match,
+        )?
+        .collect::<Result<Vec<_>, _>>()?;
+    let candidate_chars: Vec<Vec<char>> = candidates
+        .iter()
+        .map(|c| c.source_text.chars().collect())
+        .collect();
+
+    Ok(sources
+        .iter()
+        .map(|source| {
+            if source.is_empty() || source.len() > MAX_FUZZY_CHARS {
+                return Vec::new();
+            }
+            let mut matches: Vec<MemoryMatch> = candidates
+                .iter()
+                .zip(&candidate_chars)
+                .filter(|(_, chars)| chars.as_slice() != source.as_slice())
+                .filter_map(|(candidate, chars)| {
+                    similarity(source, chars, MIN_FUZZY_SCORE).map(|score| MemoryMatch {
+                        score,
+                        ..candidate.clone()
+                    })
+                })
+                .collect();
+            // 排序稳定，分数相同时保留候选的优先顺序
+            matches.sort_by(|a, b| b.score.total_cmp(&a.score));
+            matches.truncate(limit);
+            matches
+        })
+        .collect())
+}
+
+/// 保存模型翻译的句段对，原文已存在时更新译文，审校过的记录不覆盖
+pub(crate) fn store(
+    conn: &mut Connection,
+    scope: &MemoryScope,
+    pairs: &[(String, String)],
+) -> AppResult<()> {
+    store_reviewed(conn, scope, pairs, false)
+}
+
+/// 保存句段对，`reviewed` 表示经过人工审校；未审校的译文不会覆盖审校过的记录
+pub(crate) fn store_reviewed(
+    conn: &mut Connection,
+    scope: &MemoryScope,
+    pairs: &[(String, String)],
+    reviewed: bool,
+) -> AppResult<()> {
+    if pairs.is_empty() {
+        return Ok(());
+    }
+    let now = chrono::Utc::now().to_rfc3339();
+    let tx = conn.transaction()?;
+    {
+        let mut stmt = tx.prepare(
+            "INSERT INTO translation_memory
+                (id, source_language, target_language, project, source_text, target_text,
+                 created_at, updated_at, reviewed)
+             VALUES (:id, :source, :target, :project, :source_text, :target_text, :now, :now,
+                     :reviewed)
+             ON CONFLICT(source_language, target_language, project, source_text)
+             DO UPDATE SET target_text = excluded.target_text, updated_at = excluded.updated_at,
+                           reviewed = excluded.reviewed
+             WHERE excluded.reviewed OR NOT translation_memory.reviewed",
+        )?;
+        for (source_text, target_text) in pairs {
+            if source_text.trim().is_empty() || target_text.trim().is_empty() {
+                continue;
+            }
+            stmt.execute(named_params! {
+                ":id": Uuid::new_v4().to_string(),
+                ":source": scope.source_language,
+                ":target": scope.target_language,
+                ":project": scope.project,
+                ":source_text": source_text,
+                ":target_text": target_text,
+                ":now": now,
+                ":reviewed": reviewed,
+            })?;
+        }
+    }
+    tx.commit()?;
+    Ok(())
+}
+
+/// 查询一段原文的翻译记忆，完全匹配排在最前
+#[tauri::command]
+pub async fn lookup_translation_memory(
+    db: State<'_, Database>,
+    text: String,
+    target_language: String,
+    source_language: Option<String>,
+    project: Option<String>,
+    limit: Option<u32>,
+) -> AppResult<Vec<MemoryMatch>> {
+    if target_language.trim().is_empty() {
+        return Err(AppError::invalid_input("请指定目标语言"));
+    }
+    let scope = MemoryScope::new(
+        source_language.as_deref(),
+        &target_language,
+        project.as_deref(),
+    );
+    let limit = limit
+        .unwrap_or(DEFAULT_LOOKUP_LIMIT)
+        .clamp(1, MAX_LOOKUP_LIMIT) as usize;
+    let text = text.trim().to_string();
+
+    db.run(move |conn| {
+        let mut stmt = conn.prepare(&format!(
+            "SELECT {} FROM translation_memory
+             WHERE source_text = :text AND {} ORDER BY {} LIMIT :limit",
+            MATCH_COLUMNS, SCOPE_FILTER, SCOPE_ORDER
+        ))?;
+        let mut matches = stmt
+            .query_map(
+                named_params! {
+                    ":text": text,
+                    ":target": scope.target_language,
+                    ":source": scope.source_language,
+                    ":project": scope.project,
+                    ":limit": limit as i64,
+                },
+                map_match,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        if matches.len() < limit {
+            let fuzzy = fuzzy_matches(conn, &scope, std::slice::from_ref(&text), limit)?;
+            matches.extend(fuzzy.into_iter().flatten());
+            matches.truncate(limit);
+        }
+        Ok(matches)
+    })
+    .await
+}
+
+/// 手动添加或更新一条翻译记忆，视为审校过
+#[tauri::command]
+pub async fn add_translation_memory_entry(
+    db: State<'_, Database>,
+    source_text: String,
+    target_text: String,
+    target_language: String,
+    source_language: Option<String>,
+    project: Option<String>,
+) -> AppResult<()> {
+    if source_text.trim().is_empty() || target_text.trim().is_empty() {
+        return Err(AppError::invalid_input("原文和译文不能为空"));
+    }
+    if target_language.trim().is_empty() {
+        return Err(AppError::invalid_input("请指定目标语言"));
+    }
+    let scope = MemoryScope::new(
+        source_language.as_deref(),
+        &target_language,
+        project.as_deref(),
+    );
+    let pair = (
+        source_text.trim().to_string(),
+        target_text.trim().to_string(),
+    );
+    db.run(move |conn| store_reviewed(conn, &scope, &[pair], true))
+        .await
+}
+
+#[tauri::command]
+pub async fn delete_translation_memory_entry(
+    db: State<'_, Database>,
+    entry_id: String,
+) -> AppResult<()> {
+    db.run(move |conn| {
+        let deleted = conn.execute("DELETE FROM translation_memory WHERE id = ?1", [&entry_id])?;
+        if deleted == 0 {
+            return Err(AppError::not_found(format!("翻译记忆不存在: {}", entry_id)));
+        }
+        Ok(())
+    })
+    .await
+}
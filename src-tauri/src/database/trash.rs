@@ -0,0 +1,141 @@
+//! 回收站：删除会话时只标记 `deleted_at`，超过保留天数后自动彻底删除
+//!
+//! 保留天数每次清理时从 `Settings` 的 `trash.retention_days` 读取，为 0 时不自动清理。
+
+use super::{ensure_column, get_meta, map_conversation, Conversation, Database};
+use crate::error::{AppError, AppResult};
+use crate::settings::{Settings, SETTINGS_CHANGED_EVENT};
+use rusqlite::{params, Connection};
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+const PURGE_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// 旧版本保存在 app_meta 中的保留天数
+const LEGACY_RETENTION_META_KEY: &str = "trash_retention_days";
+/// 自动清理回收站出错时发送，载荷为错误信息
+pub const PURGE_FAILED_EVENT: &str = "database://trash-purge-failed";
+
+#[derive(Serialize, Debug)]
+pub struct TrashedConversation {
+    #[serde(flatten)]
+    pub conversation: Conversation,
+    pub deleted_at: String,
+}
+
+pub(crate) fn create_tables(conn: &Connection) -> AppResult<()> {
+    ensure_column(conn, "conversations", "deleted_at", "TEXT")?;
+    conn.execute(
+        "CREATE INDEX IF NOT EXISTS idx_conversations_deleted ON conversations(deleted_at)",
+        [],
+    )?;
+    Ok(())
+}
+
+/// 彻底删除超过保留天数的会话，返回删除数量；保留天数为 0 时不清理
+fn purge_expired(conn: &Connection, days: u32) -> AppResult<usize> {
+    if days == 0 {
+        return Ok(0);
+    }
+    let cutoff = (chrono::Utc::now() - chrono::Duration::days(days as i64)).to_rfc3339();
+    conn.execute(
+        "DELETE FROM conversations WHERE deleted_at IS NOT NULL AND deleted_at < ?1",
+        params![cutoff],
+    )
+    .map_err(AppError::from)
+}
+
+/// 设置文件还不存在时，沿用旧版本保存在数据库中的保留天数，
+/// 避免用户选择的“从不清理”被默认值取代
+async fn migrate_legacy_retention<R: Runtime>(app: &AppHandle<R>, db: &Database) -> AppResult<()> {
+    let settings = app.state::<Settings>();
+    if settings.file_exists() {
+        return Ok(());
+    }
+    let legacy = db
+        .run(|conn| Ok(get_meta(conn, LEGACY_RETENTION_META_KEY)?))
+        .await?;
+    let Some(days) = legacy.and_then(|value| value.parse().ok()) else {
+        return Ok(());
+    };
+
+    let mut updated = settings.get();
+    updated.trash.retention_days = days;
+    settings.replace(updated.clone())?;
+    app.emit(SETTINGS_CHANGED_EVENT, &updated)?;
+    Ok(())
+}
+
+/// 后台定期按设置中的保留天数清理回收站，数据库未解锁时跳过
+pub fn start_purge_task<R: Runtime>(app: AppHandle<R>) {
+    tauri::async_runtime::spawn(async move {
+        loop {
+            let db = app.state::<Database>();
+            if db.is_unlocked() {
+                // 迁移失败时不清理，以免按默认天数删除用户想保留的会话
+                let result = match migrate_legacy_retention(&app, &db).await {
+                    Ok(()) => {
+                        let days = app.state::<Settings>().get().trash.retention_days;
+                        db.run(move |conn| purge_expired(conn, days)).await
+                    }
+                    Err(e) => Err(e),
+                };
+                if let Err(e) = result {
+                    let _ = app.emit(PURGE_FAILED_EVENT, e);
+                }
+            }
+            tokio::time::sleep(PURGE_INTERVAL).await;
+        }
+    });
+}
+
+#[tauri::command]
+pub async fn list_trash(db: State<'_, Database>) -> AppResult<Vec<TrashedConversation>> {
+    db.run(|conn| {
+        let mut stmt = conn.prepare(
+            "SELECT id, title, summary, created_at, updated_at, is_pinned, folder_id, deleted_at
+                 FROM conversations WHERE deleted_at IS NOT NULL ORDER BY deleted_at DESC",
+        )?;
+        let trashed = stmt
+            .query_map([], |row| {
+                Ok(TrashedConversation {
+                    conversation: map_conversation(row)?,
+                    deleted_at: row.get(7)?,
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(trashed)
+    })
+    .await
+}
+
+#[tauri::command]
+pub async fn restore_conversation(
+    db: State<'_, Database>,
+    conversation_id: String,
+) -> AppResult<()> {
+    db.run(move |conn| {
+        let restored = conn.execute(
+            "UPDATE conversations SET deleted_at = NULL WHERE id = ?1 AND deleted_at IS NOT NULL",
+            params![conversation_id],
+        )?;
+        if restored == 0 {
+            return Err(AppError::not_found(format!(
+                "回收站中没有该会话: {}",
+                conversation_id
+            )));
+        }
+        Ok(())
+    })
+    .await
+}
+
+/// 清空回收站，返回彻底删除的会话数量
+#[tauri::command]
+pub async fn empty_trash(db: State<'_, Database>) -> AppResult<usize> {
+    db.run(|conn| {
+        conn.execute("DELETE FROM conversations WHERE deleted_at IS NOT NULL", [])
+            .map_err(AppError::from)
+    })
+    .await
+}
@@ -0,0 +1,184 @@
+//! 所有 Tauri 命令共用的错误类型
+//!
+//! 序列化为 `{ code, message, details }`：前端根据稳定的 `code` 判断错误类别，
+//! `message` 可以直接展示给用户，`details` 保留底层错误便于排查。
+
+use serde::Serialize;
+use std::fmt;
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "SCREAMING_SNAKE_CASE")]
+pub enum ErrorCode {
+    /// 请求的会话、消息、文件等不存在
+    NotFound,
+    /// 参数不合法或文件格式无法识别
+    InvalidInput,
+    /// 与已有数据冲突，例如重名
+    Conflict,
+    /// 数据库或密钥库尚未解锁，或密码错误
+    Locked,
+    /// 模型服务商拒绝了 API Key
+    ProviderAuth,
+    /// 模型服务商限流
+    RateLimited,
+    /// 模型服务商或 ChromaDB 等外部服务返回的其他错误
+    Provider,
+    /// 网络连接失败或超时
+    Network,
+    Io,
+    Database,
+    Internal,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AppError {
+    pub code: ErrorCode,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub details: Option<String>,
+}
+
+pub type AppResult<T> = Result<T, AppError>;
+
+impl AppError {
+    pub fn new(code: ErrorCode, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+            details: None,
+        }
+    }
+
+    pub fn with_details(mut self, details: impl fmt::Display) -> Self {
+        self.details = Some(details.to_string());
+        self
+    }
+
+    pub fn not_found(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::NotFound, message)
+    }
+
+    pub fn invalid_input(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::InvalidInput, message)
+    }
+
+    pub fn conflict(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Conflict, message)
+    }
+
+    pub fn locked(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Locked, message)
+    }
+
+    pub fn network(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Network, message)
+    }
+
+    pub fn io(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Io, message)
+    }
+
+    pub fn database(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Database, message)
+    }
+
+    pub fn internal(message: impl Into<String>) -> Self {
+        Self::new(ErrorCode::Internal, message)
+    }
+
+    /// 根据 HTTP 状态码归类服务端返回的错误，`body` 作为详情保留
+    pub fn from_status(status: reqwest::StatusCode, body: impl fmt::Display) -> Self {
+        let error = match status.as_u16() {
+            401 | 403 => Self::new(ErrorCode::ProviderAuth, "认证失败，请检查 API Key"),
+            404 => Self::not_found("请求的接口或资源不存在"),
+            429 => Self::new(ErrorCode::RateLimited, "请求过于频繁，请稍后再试"),
+            _ => Self::new(ErrorCode::Provider, format!("服务返回错误: {}", status)),
+        };
+        error.with_details(body)
+    }
+}
+
+impl fmt::Display for AppError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.details {
+            Some(details) => write!(f, "{}: {}", self.message, details),
+            None => f.write_str(&self.message),
+        }
+    }
+}
+
+impl std::error::Error for AppError {}
+
+impl From<rusqlite::Error> for AppError {
+    fn from(e: rusqlite::Error) -> Self {
+        match &e {
+            rusqlite::Error::QueryReturnedNoRows => Self::not_found("记录不存在"),
+            rusqlite::Error::SqliteFailure(err, _) => match err.code {
+                rusqlite::ErrorCode::ConstraintViolation => Self::conflict("数据冲突"),
+                rusqlite::ErrorCode::NotADatabase => Self::locked("数据库密码错误或文件已损坏"),
+                _ => Self::database("数据库操作失败"),
+            },
+            _ => Self::database("数据库操作失败"),
+        }
+        .with_details(e)
+    }
+}
+
+impl From<r2d2::Error> for AppError {
+    fn from(e: r2d2::Error) -> Self {
+        Self::database("无法获取数据库连接").with_details(e)
+    }
+}
+
+impl From<std::io::Error> for AppError {
+    fn from(e: std::io::Error) -> Self {
+        match e.kind() {
+            std::io::ErrorKind::NotFound => Self::not_found("文件不存在"),
+            std::io::ErrorKind::PermissionDenied => Self::io("没有文件访问权限"),
+            _ => Self::io("文件读写失败"),
+        }
+        .with_details(e)
+    }
+}
+
+impl From<reqwest::Error> for AppError {
+    fn from(e: reqwest::Error) -> Self {
+        match e.status() {
+            Some(status) => Self::from_status(status, e),
+            None if e.is_decode() => {
+                Self::new(ErrorCode::Provider, "无法解析服务返回的数据").with_details(e)
+            }
+            None => Self::new(ErrorCode::Network, "网络请求失败").with_details(e),
+        }
+    }
+}
+
+impl From<serde_json::Error> for AppError {
+    fn from(e: serde_json::Error) -> Self {
+        Self::invalid_input("JSON 格式错误").with_details(e)
+    }
+}
+
+impl From<zip::result::ZipError> for AppError {
+    fn from(e: zip::result::ZipError) -> Self {
+        Self::invalid_input("无法读取压缩文件").with_details(e)
+    }
+}
+
+impl From<quick_xml::Error> for AppError {
+    fn from(e: quick_xml::Error) -> Self {
+        Self::invalid_input("无法解析文档内容").with_details(e)
+    }
+}
+
+impl From<base64::DecodeError> for AppError {
+    fn from(e: base64::DecodeError) -> Self {
+        Self::invalid_input("Base64 数据无效").with_details(e)
+    }
+}
+
+impl From<tauri::Error> for AppError {
+    fn from(e: tauri::Error) -> Self {
+        Self::internal("应用内部错误").with_details(e)
+    }
+}
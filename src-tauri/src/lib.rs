@@ -1,44 +1,57 @@
 mod ai;
 mod database;
+mod error;
+mod settings;
 mod translate;
+mod workspace;
 
+use error::{AppError, AppResult};
 use std::sync::Arc;
 use tauri::menu::{Menu, MenuItem, PredefinedMenuItem, Submenu};
 use tauri::Manager;
-use translate::excel::process_excel;
+use translate::docx::translate_docx;
+use translate::excel::{import_excel_review, process_excel, translate_excel};
 use translate::image::convert_to_ico;
+use translate::markup::{translate_html, translate_markdown};
+use translate::pptx::translate_pptx;
+use translate::subtitles::translate_subtitles;
+use translate::text::translate_text;
+use translate::xliff::{export_xliff, import_xliff};
 
 type ChromaServerState = Arc<tokio::sync::Mutex<Option<Arc<ai::chromadb_server::ChromaServer>>>>;
 
+// 启动外部程序并在后台线程等待其退出，避免留下僵尸进程
+fn spawn_detached(command: &mut std::process::Command) -> AppResult<()> {
+    let mut child = command
+        .spawn()
+        .map_err(|e| AppError::io("打开文件失败").with_details(e))?;
+    std::thread::spawn(move || {
+        let _ = child.wait();
+    });
+    Ok(())
+}
+
 // 打开文件函数
 #[tauri::command]
-fn open_file(path: String) {
+fn open_file(path: String) -> AppResult<()> {
     #[cfg(target_os = "windows")]
     {
         use std::process::Command;
-        Command::new("explorer")
-            .args(["/select,", &path])
-            .spawn()
-            .expect("打开文件失败");
+        spawn_detached(Command::new("explorer").args(["/select,", &path]))?;
     }
     #[cfg(target_os = "macos")]
     {
         use std::process::Command;
-        Command::new("open")
-            .args(["--reveal", &path])
-            .spawn()
-            .expect("打开文件失败");
+        spawn_detached(Command::new("open").args(["--reveal", &path]))?;
     }
     #[cfg(target_os = "linux")]
     {
         use std::process::Command;
         if let Some(parent) = std::path::PathBuf::from(&path).parent() {
-            Command::new("xdg-open")
-                .arg(parent)
-                .spawn()
-                .expect("打开文件失败");
+            spawn_detached(Command::new("xdg-open").arg(parent))?;
         }
     }
+    Ok(())
 }
 
 fn create_chinese_menu(app: &tauri::App) -> Result<Menu<tauri::Wry>, tauri::Error> {
@@ -141,8 +154,22 @@ pub fn run() {
             let menu = create_chinese_menu(app)?;
             app.set_menu(menu)?;
 
+            // 初始化工作区，数据库和设置都保存在当前工作区目录下
+            let workspaces = workspace::init_workspaces(app.handle()).expect("初始化工作区失败");
+            app.manage(workspaces);
+
+            // 初始化设置
+            let settings = settings::init_settings(app.handle()).expect("初始化设置失败");
+            app.manage(settings);
+
             // 初始化数据库
-            database::init_db(app.handle()).expect("初始化数据库失败");
+            let db = database::init_db(app.handle()).expect("初始化数据库失败");
+            let vault = database::secrets::init_vault(app.handle(), &db).expect("初始化密钥库失败");
+            app.manage(db);
+            app.manage(vault);
+            app.manage(database::backup::IntegrityState::default());
+            database::backup::start_snapshot_scheduler(app.handle().clone());
+            database::trash::start_purge_task(app.handle().clone());
 
             // 初始化 ChromaDB 服务器状态
             app.manage::<ChromaServerState>(Arc::new(tokio::sync::Mutex::new(None)));
@@ -157,7 +184,17 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             process_excel,
+            translate_excel,
+            import_excel_review,
+            translate_docx,
+            translate_pptx,
+            translate_html,
+            translate_markdown,
+            translate_subtitles,
+            export_xliff,
+            import_xliff,
             convert_to_ico,
+            translate_text,
             open_file,
             open_settings_window,
             ai::chroma_start_server,
@@ -174,6 +211,33 @@ pub fn run() {
             database::delete_conversation,
             database::update_conversation_title,
             database::toggle_pin_conversation,
+            database::messages::edit_message,
+            database::messages::delete_message,
+            database::messages::get_message_revisions,
+            database::pagination::get_conversation_page,
+            database::pagination::get_conversation_summaries,
+            database::pagination::get_history_page,
+            // Folders & tags
+            database::folders::create_folder,
+            database::folders::get_folders,
+            database::folders::rename_folder,
+            database::folders::move_folder,
+            database::folders::delete_folder,
+            database::folders::move_conversation_to_folder,
+            database::tags::create_tag,
+            database::tags::get_tags,
+            database::tags::update_tag,
+            database::tags::delete_tag,
+            database::tags::tag_conversation,
+            database::tags::untag_conversation,
+            database::tags::get_conversation_tags,
+            // Trash
+            database::trash::list_trash,
+            database::trash::restore_conversation,
+            database::trash::empty_trash,
+            database::export::export_conversation,
+            database::export::export_conversations,
+            database::import::import_conversations,
             // Provider & Model commands
             database::create_provider,
             database::get_providers,
@@ -183,10 +247,47 @@ pub fn run() {
             database::get_all_models,
             database::delete_model,
             database::set_active_model,
-            database::get_all_models,
-            database::delete_model,
-            database::set_active_model,
             database::get_active_model,
+            database::providers::update_provider,
+            database::providers::update_model,
+            database::providers::duplicate_provider,
+            database::analytics::get_usage_stats,
+            database::translation_memory::lookup_translation_memory,
+            database::translation_memory::add_translation_memory_entry,
+            database::translation_memory::delete_translation_memory_entry,
+            database::glossary::get_glossary_terms,
+            database::glossary::create_glossary_term,
+            database::glossary::update_glossary_term,
+            database::glossary::delete_glossary_term,
+            database::glossary::import_glossary,
+            // Workspaces
+            workspace::get_workspaces,
+            workspace::get_workspaces_load_error,
+            workspace::create_workspace,
+            workspace::rename_workspace,
+            workspace::delete_workspace,
+            workspace::switch_workspace,
+            // Database encryption
+            database::encryption::get_database_status,
+            database::encryption::unlock_database,
+            database::encryption::enable_database_encryption,
+            database::encryption::disable_database_encryption,
+            // API key encryption
+            database::secrets::get_secrets_status,
+            database::secrets::unlock_secrets,
+            database::secrets::lock_secrets,
+            database::secrets::rotate_secret_key,
+            // Backup & restore
+            database::backup::backup_database,
+            database::backup::restore_database,
+            database::backup::list_snapshots,
+            database::backup::check_database_integrity,
+            database::backup::get_integrity_report,
+            // Settings
+            settings::get_settings,
+            settings::get_settings_load_error,
+            settings::update_settings,
+            settings::reset_settings,
             // LLM
             ai::llm::chat
         ])
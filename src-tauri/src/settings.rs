@@ -0,0 +1,294 @@
+//! 应用设置
+//!
+//! 设置保存在当前工作区目录下的 `settings.toml`，不放在数据库中，
+//! 这样加密数据库解锁之前也能读取。缺失的字段使用默认值，
+//! 每次修改都会先校验，再写入文件并发送 `settings://changed` 事件。
+
+use crate::error::{AppError, AppResult};
+use crate::workspace::Workspaces;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+
+const SETTINGS_FILE: &str = "settings.toml";
+pub const SETTINGS_CHANGED_EVENT: &str = "settings://changed";
+/// 切换工作区后新工作区的设置文件无效时发送，载荷为错误信息
+pub const SETTINGS_INVALID_EVENT: &str = "settings://invalid";
+const ICO_SIZES: [u32; 7] = [16, 24, 32, 48, 64, 128, 256];
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ChromaSettings {
+    /// 内置 ChromaDB 服务器监听的端口
+    pub port: u16,
+}
+
+impl Default for ChromaSettings {
+    fn default() -> Self {
+        Self { port: 8000 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ExcelSettings {
+    /// 处理后的文件名为 `<原文件名><后缀>.xlsx`
+    pub output_suffix: String,
+}
+
+impl Default for ExcelSettings {
+    fn default() -> Self {
+        Self {
+            output_suffix: "-processed".to_string(),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct ImageSettings {
+    /// 未指定尺寸时生成的 ICO 边长
+    pub default_ico_size: u32,
+}
+
+impl Default for ImageSettings {
+    fn default() -> Self {
+        Self {
+            default_ico_size: 32,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct TrashSettings {
+    /// 回收站保留天数，0 表示从不自动清理
+    pub retention_days: u32,
+}
+
+impl Default for TrashSettings {
+    fn default() -> Self {
+        Self { retention_days: 30 }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(default)]
+pub struct SnapshotSettings {
+    pub enabled: bool,
+    pub interval_hours: u32,
+    /// 最多保留的自动快照数量
+    pub keep: usize,
+}
+
+impl Default for SnapshotSettings {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            interval_hours: 24,
+            keep: 7,
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone, Default, PartialEq)]
+#[serde(default)]
+pub struct AppSettings {
+    pub chroma: ChromaSettings,
+    pub excel: ExcelSettings,
+    pub image: ImageSettings,
+    pub trash: TrashSettings,
+    pub snapshots: SnapshotSettings,
+}
+
+impl AppSettings {
+    pub fn validate(&self) -> AppResult<()> {
+        if self.chroma.port < 1024 {
+            return Err(AppError::invalid_input(
+                "ChromaDB 端口必须在 1024-65535 之间",
+            ));
+        }
+
+        let suffix = &self.excel.output_suffix;
+        if suffix.trim().is_empty() {
+            return Err(AppError::invalid_input("Excel 输出文件后缀不能为空"));
+        }
+        if suffix
+            .chars()
+            .any(|c| matches!(c, '/' | '\\' | ':' | '*' | '?' | '"' | '<' | '>' | '|'))
+        {
+            return Err(AppError::invalid_input(format!(
+                "Excel 输出文件后缀包含非法字符: {}",
+                suffix
+            )));
+        }
+
+        if !ICO_SIZES.contains(&self.image.default_ico_size) {
+            return Err(AppError::invalid_input(format!(
+                "ICO 尺寸必须是以下之一: {:?}",
+                ICO_SIZES
+            )));
+        }
+
+        if self.trash.retention_days > 3650 {
+            return Err(AppError::invalid_input("回收站保留天数不能超过 3650 天"));
+        }
+
+        if !(1..=24 * 30).contains(&self.snapshots.interval_hours) {
+            return Err(AppError::invalid_input("快照间隔必须在 1-720 小时之间"));
+        }
+        if !(1..=100).contains(&self.snapshots.keep) {
+            return Err(AppError::invalid_input("快照保留数量必须在 1-100 之间"));
+        }
+
+        Ok(())
+    }
+}
+
+/// 作为 Tauri 状态管理的设置存储
+pub struct Settings {
+    path: RwLock<PathBuf>,
+    current: RwLock<AppSettings>,
+    /// 设置文件无效、改用默认值的原因，保存新设置后清除
+    load_error: RwLock<Option<AppError>>,
+}
+
+impl Settings {
+    /// 读取设置文件，文件不存在或无法解析时使用默认值
+    pub fn load(path: PathBuf) -> Self {
+        let parsed = match fs::read_to_string(&path) {
+            Ok(content) => toml::from_str::<AppSettings>(&content)
+                .map_err(|e| e.to_string())
+                .and_then(|settings| match settings.validate() {
+                    Ok(()) => Ok(settings),
+                    Err(e) => Err(e.message),
+                })
+                .map_err(|reason| {
+                    AppError::invalid_input("设置文件无效，已使用默认设置").with_details(reason)
+                }),
+            Err(_) => Ok(AppSettings::default()),
+        };
+        let (current, load_error) = match parsed {
+            Ok(settings) => (settings, None),
+            Err(e) => (AppSettings::default(), Some(e)),
+        };
+
+        Self {
+            path: RwLock::new(path),
+            current: RwLock::new(current),
+            load_error: RwLock::new(load_error),
+        }
+    }
+
+    /// 切换工作区时改为使用新工作区的设置文件
+    pub(crate) fn reload(&self, workspace_dir: &Path) -> AppSettings {
+        let Settings {
+            path,
+            current,
+            load_error,
+        } = Self::load(workspace_dir.join(SETTINGS_FILE));
+        let current = current.into_inner().unwrap();
+        *self.path.write().unwrap() = path.into_inner().unwrap();
+        *self.current.write().unwrap() = current.clone();
+        *self.load_error.write().unwrap() = load_error.into_inner().unwrap();
+        current
+    }
+
+    pub(crate) fn load_error(&self) -> Option<AppError> {
+        self.load_error.read().unwrap().clone()
+    }
+
+    pub fn get(&self) -> AppSettings {
+        self.current.read().unwrap().clone()
+    }
+
+    /// 设置文件是否已经保存过
+    pub(crate) fn file_exists(&self) -> bool {
+        self.path.read().unwrap().exists()
+    }
+
+    /// 校验并保存新设置，写入成功后才替换内存中的值
+    pub fn replace(&self, settings: AppSettings) -> AppResult<()> {
+        settings.validate()?;
+        write_settings(&self.path.read().unwrap(), &settings)?;
+        *self.current.write().unwrap() = settings;
+        *self.load_error.write().unwrap() = None;
+        Ok(())
+    }
+}
+
+/// 先写临时文件再改名，避免写到一半时崩溃导致设置文件损坏
+fn write_settings(path: &Path, settings: &AppSettings) -> AppResult<()> {
+    let content = toml::to_string_pretty(settings)
+        .map_err(|e| AppError::internal("序列化设置失败").with_details(e))?;
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp = path.with_extension("toml.tmp");
+    fs::write(&temp, content).map_err(|e| AppError::io("写入设置文件失败").with_details(e))?;
+    fs::rename(&temp, path).map_err(|e| AppError::io("写入设置文件失败").with_details(e))?;
+    Ok(())
+}
+
+/// 将 `patch` 中的字段递归合并到 `target`，未出现的字段保持不变
+fn merge_json(target: &mut Value, patch: Value) {
+    match (target, patch) {
+        (Value::Object(target), Value::Object(patch)) => {
+            for (key, value) in patch {
+                merge_json(target.entry(key).or_insert(Value::Null), value);
+            }
+        }
+        (target, patch) => *target = patch,
+    }
+}
+
+pub fn init_settings<R: Runtime>(app: &AppHandle<R>) -> AppResult<Settings> {
+    let workspace_dir = app.state::<Workspaces>().active_dir();
+    Ok(Settings::load(workspace_dir.join(SETTINGS_FILE)))
+}
+
+fn save_and_notify<R: Runtime>(
+    app: &AppHandle<R>,
+    settings: &Settings,
+    updated: AppSettings,
+) -> AppResult<AppSettings> {
+    settings.replace(updated.clone())?;
+    app.emit(SETTINGS_CHANGED_EVENT, &updated)?;
+    Ok(updated)
+}
+
+#[tauri::command]
+pub fn get_settings(settings: State<'_, Settings>) -> AppSettings {
+    settings.get()
+}
+
+/// 设置文件无效时返回原因，启动时前端据此提示用户当前使用的是默认设置
+#[tauri::command]
+pub fn get_settings_load_error(settings: State<'_, Settings>) -> Option<AppError> {
+    settings.load_error()
+}
+
+/// 部分更新，只需要传入要修改的字段，例如 `{ "chroma": { "port": 8001 } }`
+#[tauri::command]
+pub fn update_settings<R: Runtime>(
+    app: AppHandle<R>,
+    settings: State<'_, Settings>,
+    patch: Value,
+) -> AppResult<AppSettings> {
+    let mut merged = serde_json::to_value(settings.get())?;
+    merge_json(&mut merged, patch);
+    let updated: AppSettings = serde_json::from_value(merged)
+        .map_err(|e| AppError::invalid_input("设置格式错误").with_details(e))?;
+    save_and_notify(&app, &settings, updated)
+}
+
+#[tauri::command]
+pub fn reset_settings<R: Runtime>(
+    app: AppHandle<R>,
+    settings: State<'_, Settings>,
+) -> AppResult<AppSettings> {
+    save_and_notify(&app, &settings, AppSettings::default())
+}
@@ -0,0 +1,112 @@
+//! 可分段的文档
+//!
+//! 按扩展名识别文档格式，列出需要翻译的文字，并按原文到译文的对应关系生成译文文件。
+//! 不经过模型翻译的流程（如 XLIFF 导出和导入）通过它支持所有格式。
+
+use super::excel::{apply_workbook_translations, collect_workbook_texts};
+use super::markup::{
+    apply_html_translations, apply_markdown_translations, collect_html_texts,
+    collect_markdown_texts,
+};
+use super::office::Package;
+use super::segments::{check_input_file, read_text_file};
+use super::subtitles::{apply_subtitle_translations, collect_subtitle_texts, is_vtt};
+use super::{docx, pptx};
+use crate::error::AppResult;
+use std::path::Path;
+
+const SUPPORTED_EXTENSIONS: &[&str] = &[
+    "xlsx", "xlsm", "docx", "docm", "pptx", "pptm", "html", "htm", "xhtml", "md", "markdown",
+    "srt", "vtt",
+];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum DocumentKind {
+    Workbook,
+    Word,
+    Presentation,
+    Html,
+    Markdown,
+    Subtitles,
+}
+
+impl DocumentKind {
+    /// 检查文件存在且格式受支持
+    pub(crate) fn from_path(path: &Path) -> AppResult<Self> {
+        check_input_file(path, SUPPORTED_EXTENSIONS)?;
+        let extension = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase())
+            .unwrap_or_default();
+        Ok(match extension.as_str() {
+            "xlsx" | "xlsm" => Self::Workbook,
+            "docx" | "docm" => Self::Word,
+            "pptx" | "pptm" => Self::Presentation,
+            "md" | "markdown" => Self::Markdown,
+            "srt" | "vtt" => Self::Subtitles,
+            _ => Self::Html,
+        })
+    }
+
+    /// 按文档中的顺序列出需要翻译的文字，可能有重复
+    pub(crate) fn collect_texts(self, path: &Path) -> AppResult<Vec<String>> {
+        match self {
+            Self::Workbook => collect_workbook_texts(&Package::open(path)?),
+            Self::Word => docx::collect_document_texts(&Package::open(path)?),
+            Self::Presentation => pptx::collect_presentation_texts(&Package::open(path)?),
+            Self::Html => Ok(collect_html_texts(&read_text_file(path)?)),
+            Self::Markdown => Ok(collect_markdown_texts(&read_text_file(path)?)),
+            Self::Subtitles => Ok(collect_subtitle_texts(&read_text_file(path)?, is_vtt(path))),
+        }
+    }
+
+    /// 生成译文文件，`translate` 返回 `None` 的文字保持原文；返回写回时的提示
+    pub(crate) fn write_translated(
+        self,
+        input: &Path,
+        output: &Path,
+        translate: impl Fn(&str) -> Option<String>,
+    ) -> AppResult<Vec<String>> {
+        let mut warnings = Vec::new();
+        match self {
+            Self::Workbook => {
+                let mut package = Package::open(input)?;
+                apply_workbook_translations(&mut package, translate)?;
+                package.save(output)?;
+            }
+            Self::Word => {
+                let mut package = Package::open(input)?;
+                docx::apply_document_translations(&mut package, translate)?;
+                package.save(output)?;
+            }
+            Self::Presentation => {
+                let mut package = Package::open(input)?;
+                let (_, overflow) = pptx::apply_presentation_translations(&mut package, translate)?;
+                package.save(output)?;
+                warnings.extend(overflow.into_iter().map(|shape| {
+                    format!(
+                        "第 {} 张幻灯片的“{}”中译文可能放不下",
+                        shape.slide, shape.shape
+                    )
+                }));
+            }
+            Self::Html => {
+                let source = read_text_file(input)?;
+                let html = apply_html_translations(&source, translate, &mut warnings);
+                std::fs::write(output, html)?;
+            }
+            Self::Markdown => {
+                let source = read_text_file(input)?;
+                let markdown = apply_markdown_translations(&source, translate, &mut warnings);
+                std::fs::write(output, markdown)?;
+            }
+            Self::Subtitles => {
+                let source = read_text_file(input)?;
+                let subtitles =
+                    apply_subtitle_translations(&source, is_vtt(input), translate, None);
+                std::fs::write(output, subtitles)?;
+            }
+        }
+        Ok(warnings)
+    }
+}
@@ -0,0 +1,96 @@
+//! Word 文档翻译
+//!
+//! 翻译正文、页眉、页脚、脚注和尾注中的段落。段落内不同格式的文字按 run 拆分，
+//! 译文写回后各自保留原来的格式；样式、编号、表格和图片不变。
+
+use super::office::{Package, RunLayout, TextUnits};
+use super::segments::{
+    check_input_file, translated_output_path, DocumentTranslationResult, Translator,
+};
+use super::text::TranslationOptions;
+use crate::database::secrets::Vault;
+use crate::database::Database;
+use crate::error::AppResult;
+use std::path::Path;
+use tauri::State;
+
+const PARAGRAPHS: TextUnits = TextUnits {
+    container: b"w:p",
+    text: b"w:t",
+    skip: &[],
+    runs: Some(RunLayout {
+        run: b"w:r",
+        properties: b"w:rPr",
+        ignorable: &[
+            b"w:proofErr",
+            b"w:bookmarkStart",
+            b"w:bookmarkEnd",
+            b"w:lastRenderedPageBreak",
+            b"w:permStart",
+            b"w:permEnd",
+        ],
+    }),
+};
+
+fn is_story_part(name: &str) -> bool {
+    let Some(file) = name.strip_prefix("word/") else {
+        return false;
+    };
+    !file.contains('/')
+        && file.ends_with(".xml")
+        && (file == "document.xml"
+            || file == "footnotes.xml"
+            || file == "endnotes.xml"
+            || file.starts_with("header")
+            || file.starts_with("footer"))
+}
+
+/// 收集文档中所有需要翻译的段落
+pub(crate) fn collect_document_texts(package: &Package) -> AppResult<Vec<String>> {
+    let mut texts = Vec::new();
+    for name in package.names(is_story_part) {
+        if let Some(xml) = package.get(&name) {
+            texts.extend(PARAGRAPHS.collect(xml)?);
+        }
+    }
+    Ok(texts)
+}
+
+/// 把译文写回文档，`translate` 返回 `None` 的段落保持不变
+pub(crate) fn apply_document_translations(
+    package: &mut Package,
+    translate: impl Fn(&str) -> Option<String>,
+) -> AppResult<()> {
+    for name in package.names(is_story_part) {
+        if let Some(xml) = package.get(&name) {
+            let xml = PARAGRAPHS.transform(xml, &translate)?;
+            package.set(&name, xml);
+        }
+    }
+    Ok(())
+}
+
+/// 翻译 Word 文档，输出为 `<原文件名>-<语言>.docx`
+#[tauri::command]
+pub async fn translate_docx(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    input_path: String,
+    options: TranslationOptions,
+) -> AppResult<DocumentTranslationResult> {
+    let path = Path::new(&input_path);
+    check_input_file(path, &["docx", "docm"])?;
+    let output_path = translated_output_path(path, &options.target_language)?;
+
+    let translator = Translator::new(&db, &vault, options).await?;
+    let mut package = Package::open(path)?;
+    let translations = translator
+        .translate_all(collect_document_texts(&package)?)
+        .await?;
+    apply_document_translations(&mut package, |text| {
+        translations.get(text).map(str::to_string)
+    })?;
+    package.save(&output_path)?;
+
+    Ok(DocumentTranslationResult::new(&output_path, translations))
+}
@@ -1,8 +1,11 @@
+use crate::error::{AppError, AppResult};
+use crate::settings::Settings;
 use calamine::{open_workbook, Reader, Xlsx};
 use rust_xlsxwriter::{Workbook};
 use scraper::Html;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
+use tauri::State;
 use chrono::{NaiveDate, NaiveDateTime, NaiveTime};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -49,91 +52,86 @@ fn convert_to_date_string(cell: &str) -> String {
 }
 
 #[tauri::command]
-pub async fn process_excel(input_path: String) -> ProcessResult {
+pub async fn process_excel(
+    settings: State<'_, Settings>,
+    input_path: String,
+) -> AppResult<ProcessResult> {
     let path = Path::new(&input_path);
+    let output_suffix = settings.get().excel.output_suffix;
 
     // 检查文件是否存在
     if !path.exists() {
-        return ProcessResult {
-            success: false,
-            message: String::from("文件不存在"),
-            output_path: None,
-        };
+        return Err(AppError::not_found("文件不存在"));
     }
 
     // 尝试打开 Excel 文件
-    let mut workbook: Xlsx<_> = match open_workbook(path) {
-        Ok(wb) => wb,
-        Err(e) => {
-            return ProcessResult {
-                success: false,
-                message: format!("无法打开 Excel 文件: {}", e),
-                output_path: None,
-            };
-        }
-    };
+    let mut workbook: Xlsx<_> = open_workbook(path)
+        .map_err(|e| AppError::invalid_input("无法打开 Excel 文件").with_details(e))?;
 
     // 获取第一个工作表
-    if let Some(Ok(range)) = workbook.worksheet_range_at(0) {
-        let mut processed_data: Vec<Vec<String>> = Vec::new();
-
-        // 处理每一行
-        for (_row_idx, row) in range.rows().enumerate() {
-            let mut processed_row: Vec<String> = Vec::new();
-            for (_col_idx, cell) in row.iter().enumerate() {
-                let cell_str = cell.to_string();
-                let processed_text = convert_html_to_text(&cell_str);
-
-                // 分析单元格值是否可能是日期
-                let is_date = is_likely_date_value(&processed_text, cell);
-
-                // 只对可能是日期的值进行转换
-                let processed_cell = if is_date {
-                    convert_to_date_string(&processed_text)
-                } else {
-                    processed_text
-                };
-
-                processed_row.push(processed_cell);
-            }
-            processed_data.push(processed_row);
+    let range = match workbook.worksheet_range_at(0) {
+        Some(Ok(range)) => range,
+        Some(Err(e)) => {
+            return Err(AppError::invalid_input("无法读取工作表").with_details(e));
         }
+        None => return Err(AppError::invalid_input("无法读取工作表")),
+    };
 
-        // 构建输出文件路径
-        let output_path = path.with_file_name(format!(
-            "{}-processed.xlsx",
-            path.file_stem().unwrap().to_string_lossy()
-        ));
+    let mut processed_data: Vec<Vec<String>> = Vec::new();
 
-        // 创建新的工作簿并保存
-        let mut workbook = Workbook::new();
-        let sheet = workbook.add_worksheet();
+    // 处理每一行
+    for row in range.rows() {
+        let mut processed_row: Vec<String> = Vec::new();
+        for cell in row.iter() {
+            let cell_str = cell.to_string();
+            let processed_text = convert_html_to_text(&cell_str);
 
-        for (row_idx, row) in processed_data.iter().enumerate() {
-            for (col_idx, cell) in row.iter().enumerate() {
-                sheet.write_string(row_idx as u32, col_idx as u16, cell).unwrap();
-            }
-        }
+            // 分析单元格值是否可能是日期
+            let is_date = is_likely_date_value(&processed_text, cell);
 
-        match workbook.save(output_path.to_str().unwrap()) {
-            Ok(_) => ProcessResult {
-                success: true,
-                message: String::from("处理成功"),
-                output_path: Some(output_path.to_string_lossy().into_owned()),
-            },
-            Err(e) => ProcessResult {
-                success: false,
-                message: format!("保存文件失败: {}", e),
-                output_path: None,
-            },
+            // 只对可能是日期的值进行转换
+            let processed_cell = if is_date {
+                convert_to_date_string(&processed_text)
+            } else {
+                processed_text
+            };
+
+            processed_row.push(processed_cell);
         }
-    } else {
-        ProcessResult {
-            success: false,
-            message: String::from("无法读取工作表"),
-            output_path: None,
+        processed_data.push(processed_row);
+    }
+
+    // 构建输出文件路径
+    let file_stem = path
+        .file_stem()
+        .ok_or_else(|| AppError::invalid_input("无效的文件路径"))?;
+    let output_path = path.with_file_name(format!(
+        "{}{}.xlsx",
+        file_stem.to_string_lossy(),
+        output_suffix
+    ));
+
+    // 创建新的工作簿并保存
+    let mut workbook = Workbook::new();
+    let sheet = workbook.add_worksheet();
+
+    for (row_idx, row) in processed_data.iter().enumerate() {
+        for (col_idx, cell) in row.iter().enumerate() {
+            sheet
+                .write_string(row_idx as u32, col_idx as u16, cell)
+                .map_err(|e| AppError::invalid_input("写入单元格失败").with_details(e))?;
         }
     }
+
+    workbook
+        .save(&output_path)
+        .map_err(|e| AppError::io("保存文件失败").with_details(e))?;
+
+    Ok(ProcessResult {
+        success: true,
+        message: String::from("处理成功"),
+        output_path: Some(output_path.to_string_lossy().into_owned()),
+    })
 }
 
 fn convert_html_to_text(html_string: &str) -> String {
@@ -172,7 +170,7 @@ fn is_likely_date_value(cell_str: &str, cell_value: &calamine::Data) -> bool {
             // 典型日期范围检查
             // 排除太小的数字，它们更可能是普通数值而非日期
             // 这里使用一个合理的下限，比如15000（约1941年）
-            if int_part >= 15000.0 && int_part <= EXCEL_DATE_MAX {
+            if (15000.0..=EXCEL_DATE_MAX).contains(&int_part) {
                 // 可能是日期
                 return true;
             }
@@ -182,7 +180,7 @@ fn is_likely_date_value(cell_str: &str, cell_value: &calamine::Data) -> bool {
             let frac_part = num - int_part;
             
             // 2. 如果是一个合理的日期（1900年至今）但不是很小的数
-            if int_part >= EXCEL_DATE_MIN && int_part <= EXCEL_DATE_MAX && int_part >= 365.0 {
+            if (EXCEL_DATE_MIN..=EXCEL_DATE_MAX).contains(&int_part) && int_part >= 365.0 {
                 // 小数部分如果表示时间，应该在0-0.99999之间
                 // 常见时间对应的小数：0.25(6小时), 0.5(12小时), 0.75(18小时), 0.33333(8小时)等
                 // 检查小数部分是否符合时间模式
@@ -192,12 +190,12 @@ fn is_likely_date_value(cell_str: &str, cell_value: &calamine::Data) -> bool {
                     let minutes = (seconds % 3600) / 60;
                     
                     // 如果小数部分恰好对应整点、整半小时或整分钟，更可能是日期时间
-                    if minutes % 5 == 0 || hours * 60 + minutes <= 10 || hours * 60 + minutes >= 23 * 60 {
+                    if minutes.is_multiple_of(5) || hours * 60 + minutes <= 10 || hours * 60 + minutes >= 23 * 60 {
                         return true;
                     }
                 } 
                 // 整数且处于更可能是日期的范围(Excel日期通常在36000-45000之间，约1998-2023年)
-                else if int_part >= 36000.0 && int_part <= 45000.0 {
+                else if (36000.0..=45000.0).contains(&int_part) {
                     return true;
                 }
             }
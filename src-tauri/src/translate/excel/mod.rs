@@ -1,2 +1,7 @@
+#[allow(clippy::module_inception)]
 pub mod excel;
-pub use excel::*;
\ No newline at end of file
+pub mod review;
+pub mod translation;
+pub use excel::*;
+pub use review::*;
+pub use translation::*;
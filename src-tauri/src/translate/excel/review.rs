@@ -0,0 +1,296 @@
+//! Excel 双语审校
+//!
+//! 审校文件按“工作表、位置、类型、原文、译文”逐行列出工作簿中的文字。
+//! 审校人员修改译文列后导入，按原文重新生成译文工作簿。
+
+use super::translation::{
+    apply_workbook_translations, check_workbook_path, part, ExcelTranslationResult, COMMENT_UNITS,
+    INLINE_STRING_UNITS, SHARED_STRINGS, SHARED_STRING_UNITS, WORKBOOK,
+};
+use crate::error::{AppError, AppResult};
+use crate::translate::office::{self, Package};
+use crate::translate::segments::{needs_translation, TranslationMap};
+use calamine::{open_workbook, Reader, Xlsx};
+use quick_xml::events::Event;
+use rust_xlsxwriter::{Format, Workbook, Worksheet, XlsxError};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+
+const REVIEW_SUFFIX: &str = "-review";
+const SOURCE_HEADER: &str = "原文";
+const TARGET_HEADER: &str = "译文";
+const HEADERS: [&str; 5] = ["工作表", "位置", "类型", SOURCE_HEADER, TARGET_HEADER];
+const COLUMN_WIDTHS: [f64; 5] = [18.0, 10.0, 12.0, 60.0, 60.0];
+
+const KIND_SHEET_NAME: &str = "工作表名称";
+const KIND_CELL: &str = "单元格";
+const KIND_COMMENT: &str = "批注";
+
+/// 审校文件中的一行
+pub(crate) struct ReviewEntry {
+    sheet: String,
+    location: String,
+    kind: &'static str,
+    source: String,
+}
+
+enum CellText {
+    Shared(usize),
+    Inline,
+}
+
+/// 按出现顺序返回工作表中文字单元格的位置
+fn sheet_cells(xml: &[u8]) -> AppResult<Vec<(String, CellText)>> {
+    let mut reader = office::reader(xml);
+    let mut cells = Vec::new();
+    let mut current: Option<(String, String)> = None;
+    let mut in_value = false;
+
+    loop {
+        match reader.read_event()? {
+            Event::Eof => break,
+            Event::Start(start) if start.name().as_ref() == b"c" => {
+                let mut reference = String::new();
+                let mut cell_type = String::new();
+                for attr in start.attributes().flatten() {
+                    match attr.key.as_ref() {
+                        b"r" => reference = String::from_utf8_lossy(&attr.value).into_owned(),
+                        b"t" => cell_type = String::from_utf8_lossy(&attr.value).into_owned(),
+                        _ => {}
+                    }
+                }
+                current = Some((reference, cell_type));
+            }
+            Event::End(end) if end.name().as_ref() == b"c" => current = None,
+            Event::Start(start) if start.name().as_ref() == b"v" => in_value = true,
+            Event::End(end) if end.name().as_ref() == b"v" => in_value = false,
+            Event::Start(start) if start.name().as_ref() == b"is" => {
+                if let Some((reference, _)) = &current {
+                    cells.push((reference.clone(), CellText::Inline));
+                }
+            }
+            Event::Text(text) if in_value => {
+                if let Some((reference, cell_type)) = &current {
+                    if cell_type == "s" {
+                        if let Ok(index) = text.unescape()?.trim().parse() {
+                            cells.push((reference.clone(), CellText::Shared(index)));
+                        }
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(cells)
+}
+
+/// 按工作表列出所有需要翻译的文字及其位置
+pub(crate) fn review_entries(package: &Package) -> AppResult<Vec<ReviewEntry>> {
+    let workbook = part(package, WORKBOOK)?;
+    let names = office::collect_attribute(workbook, b"sheet", b"name")?;
+    let ids = office::collect_attribute(workbook, b"sheet", b"r:id")?;
+    let targets: HashMap<String, String> = office::relationships(package, WORKBOOK)?
+        .into_iter()
+        .map(|(id, _, target)| (id, target))
+        .collect();
+    let shared_strings = match package.get(SHARED_STRINGS) {
+        Some(xml) => SHARED_STRING_UNITS.collect(xml)?,
+        None => Vec::new(),
+    };
+
+    let mut entries = Vec::new();
+    let mut push = |sheet: &str, location: String, kind: &'static str, source: &str| {
+        if needs_translation(source) {
+            entries.push(ReviewEntry {
+                sheet: sheet.to_string(),
+                location,
+                kind,
+                source: source.to_string(),
+            });
+        }
+    };
+
+    for (name, id) in names.iter().zip(&ids) {
+        push(name, String::new(), KIND_SHEET_NAME, name);
+        let Some(sheet_part) = targets.get(id) else {
+            continue;
+        };
+        let Some(xml) = package.get(sheet_part) else {
+            continue;
+        };
+
+        let mut inline_strings = INLINE_STRING_UNITS.collect(xml)?.into_iter();
+        for (reference, text) in sheet_cells(xml)? {
+            let source = match text {
+                CellText::Shared(index) => shared_strings.get(index).cloned(),
+                CellText::Inline => inline_strings.next(),
+            };
+            if let Some(source) = source {
+                push(name, reference, KIND_CELL, &source);
+            }
+        }
+
+        for (_, kind, target) in office::relationships(package, sheet_part)? {
+            if !kind.ends_with("/comments") {
+                continue;
+            }
+            let Some(comments) = package.get(&target) else {
+                continue;
+            };
+            let references = office::collect_attribute(comments, b"comment", b"ref")?;
+            for (reference, source) in references.into_iter().zip(COMMENT_UNITS.collect(comments)?)
+            {
+                push(name, reference, KIND_COMMENT, &source);
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// 审校文件与译文放在同一目录，命名为 `<译文文件名>-review.xlsx`
+pub(crate) fn review_path(output_path: &Path) -> PathBuf {
+    let stem = output_path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    output_path.with_file_name(format!("{}{}.xlsx", stem, REVIEW_SUFFIX))
+}
+
+fn fill_review_sheet(
+    sheet: &mut Worksheet,
+    entries: &[ReviewEntry],
+    translations: &TranslationMap,
+) -> Result<(), XlsxError> {
+    let header = Format::new().set_bold();
+    let wrap = Format::new().set_text_wrap();
+
+    sheet.set_name("审校")?;
+    for (col, (title, width)) in HEADERS.iter().zip(COLUMN_WIDTHS).enumerate() {
+        sheet.write_string_with_format(0, col as u16, *title, &header)?;
+        sheet.set_column_width(col as u16, width)?;
+    }
+
+    let mut row = 0u32;
+    for entry in entries {
+        let Some(target) = translations.get(&entry.source) else {
+            continue;
+        };
+        row += 1;
+        sheet.write_string(row, 0, &entry.sheet)?;
+        sheet.write_string(row, 1, &entry.location)?;
+        sheet.write_string(row, 2, entry.kind)?;
+        sheet.write_string_with_format(row, 3, &entry.source, &wrap)?;
+        sheet.write_string_with_format(row, 4, target, &wrap)?;
+    }
+
+    sheet.set_freeze_panes(1, 0)?;
+    sheet.autofilter(0, 0, row, HEADERS.len() as u16 - 1)?;
+    Ok(())
+}
+
+/// 生成审校文件，没有译文的文字不列出
+pub(crate) fn write_review_workbook(
+    path: &Path,
+    entries: &[ReviewEntry],
+    translations: &TranslationMap,
+) -> AppResult<()> {
+    let mut workbook = Workbook::new();
+    fill_review_sheet(workbook.add_worksheet(), entries, translations)
+        .map_err(|e| AppError::internal("生成审校文件失败").with_details(e))?;
+    workbook
+        .save(path)
+        .map_err(|e| AppError::io("保存文件失败").with_details(e))?;
+    Ok(())
+}
+
+/// 从审校文件读取原文到译文的对应关系，同一原文的译文不一致时使用第一处并给出提示
+fn read_review(path: &Path) -> AppResult<(HashMap<String, String>, Vec<String>)> {
+    let mut workbook: Xlsx<_> = open_workbook(path)
+        .map_err(|e| AppError::invalid_input("无法打开审校文件").with_details(e))?;
+    let range = match workbook.worksheet_range_at(0) {
+        Some(Ok(range)) => range,
+        Some(Err(e)) => return Err(AppError::invalid_input("无法读取审校文件").with_details(e)),
+        None => return Err(AppError::invalid_input("审校文件中没有工作表")),
+    };
+
+    let mut rows = range.rows();
+    let header: Vec<String> = rows
+        .next()
+        .map(|row| row.iter().map(|cell| cell.to_string()).collect())
+        .unwrap_or_default();
+    let column = |title: &str| header.iter().position(|cell| cell.trim() == title);
+    let (Some(source_col), Some(target_col)) = (column(SOURCE_HEADER), column(TARGET_HEADER))
+    else {
+        return Err(AppError::invalid_input(format!(
+            "审校文件缺少“{}”或“{}”列",
+            SOURCE_HEADER, TARGET_HEADER
+        )));
+    };
+
+    let mut reviewed: HashMap<String, String> = HashMap::new();
+    let mut warnings = Vec::new();
+    for row in rows {
+        let cell = |col: usize| row.get(col).map(|c| c.to_string()).unwrap_or_default();
+        let (source, target) = (cell(source_col), cell(target_col));
+        if source.is_empty() || target.trim().is_empty() {
+            continue;
+        }
+        match reviewed.get(&source) {
+            Some(existing) if *existing != target => warnings.push(format!(
+                "“{}”在多处的译文不一致，已使用第一处",
+                source.chars().take(40).collect::<String>()
+            )),
+            Some(_) => {}
+            None => {
+                reviewed.insert(source, target);
+            }
+        }
+    }
+    Ok((reviewed, warnings))
+}
+
+/// 导入审校后的译文，基于原工作簿重新生成译文工作簿
+#[tauri::command]
+pub async fn import_excel_review(
+    input_path: String,
+    review_path: String,
+    output_path: Option<String>,
+) -> AppResult<ExcelTranslationResult> {
+    let input = Path::new(&input_path);
+    check_workbook_path(input)?;
+    let review = Path::new(&review_path);
+    if !review.exists() {
+        return Err(AppError::not_found("审校文件不存在"));
+    }
+
+    let output = match output_path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let stem = review
+                .file_stem()
+                .map(|stem| stem.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let Some(stem) = stem.strip_suffix(REVIEW_SUFFIX) else {
+                return Err(AppError::invalid_input("无法确定输出文件，请指定输出路径"));
+            };
+            review.with_file_name(format!("{}.xlsx", stem))
+        }
+    };
+    if output == input || output == review {
+        return Err(AppError::invalid_input("输出文件不能覆盖原文件或审校文件"));
+    }
+
+    let (reviewed, warnings) = read_review(review)?;
+    let mut package = Package::open(input)?;
+    let sheets = apply_workbook_translations(&mut package, |text| reviewed.get(text).cloned())?;
+    package.save(&output)?;
+
+    Ok(ExcelTranslationResult {
+        output_path: output.to_string_lossy().into_owned(),
+        review_path: Some(review_path),
+        sheets,
+        segments: reviewed.len(),
+        from_memory: 0,
+        warnings,
+    })
+}
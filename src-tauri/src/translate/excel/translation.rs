@@ -0,0 +1,295 @@
+//! Excel 翻译
+//!
+//! 直接改写 xlsx 包中的文字：共享字符串、内联字符串、批注和工作表名称。
+//! 数字、日期和公式不是文字，原样保留；样式、合并单元格、列宽等也都不变。
+
+use super::review;
+use crate::database::secrets::Vault;
+use crate::database::Database;
+use crate::error::{AppError, AppResult};
+use crate::translate::office::{self, Package, TextUnits};
+use crate::translate::segments::{check_input_file, translated_output_path, Translator};
+use crate::translate::text::TranslationOptions;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use tauri::State;
+
+pub(crate) const WORKBOOK: &str = "xl/workbook.xml";
+pub(crate) const SHARED_STRINGS: &str = "xl/sharedStrings.xml";
+const MAX_SHEET_NAME_CHARS: usize = 31;
+
+pub(crate) const SHARED_STRING_UNITS: TextUnits = TextUnits {
+    container: b"si",
+    text: b"t",
+    skip: &[b"rPh"],
+    runs: None,
+};
+pub(crate) const INLINE_STRING_UNITS: TextUnits = TextUnits {
+    container: b"is",
+    text: b"t",
+    skip: &[b"rPh"],
+    runs: None,
+};
+pub(crate) const COMMENT_UNITS: TextUnits = TextUnits {
+    container: b"text",
+    text: b"t",
+    skip: &[b"rPh"],
+    runs: None,
+};
+
+// 公式中的工作表引用：'Sheet Name'!A1 或 Sheet1!A1
+static SHEET_REFERENCE: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"'((?:[^']|'')+)'!|(^|[^A-Za-z0-9_.'!])([A-Za-z_][A-Za-z0-9_.]*)!").unwrap()
+});
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ExcelOutputMode {
+    /// 只输出译文工作簿
+    #[default]
+    Translated,
+    /// 另外输出双语审校文件，列出每处文字的原文和译文
+    Bilingual,
+}
+
+#[derive(Serialize, Debug)]
+pub struct ExcelTranslationResult {
+    pub output_path: String,
+    /// 双语模式下的审校文件
+    pub review_path: Option<String>,
+    pub sheets: usize,
+    /// 去重后翻译的文字条数
+    pub segments: usize,
+    /// 其中直接取自翻译记忆的条数
+    pub from_memory: usize,
+    pub warnings: Vec<String>,
+}
+
+fn is_worksheet(name: &str) -> bool {
+    name.starts_with("xl/worksheets/") && name.ends_with(".xml")
+}
+
+fn is_comments(name: &str) -> bool {
+    name.starts_with("xl/comments") && name.ends_with(".xml")
+}
+
+fn is_chart(name: &str) -> bool {
+    name.starts_with("xl/charts/chart") && name.ends_with(".xml")
+}
+
+fn is_pivot_cache(name: &str) -> bool {
+    name.starts_with("xl/pivotCache/pivotCacheDefinition") && name.ends_with(".xml")
+}
+
+pub(crate) fn check_workbook_path(path: &Path) -> AppResult<()> {
+    check_input_file(path, &["xlsx", "xlsm"])
+}
+
+pub(crate) fn part<'a>(package: &'a Package, name: &str) -> AppResult<&'a [u8]> {
+    package.get(name).ok_or_else(|| {
+        AppError::invalid_input(format!("文件缺少 {}，不是有效的 Excel 工作簿", name))
+    })
+}
+
+/// 工作表名称不能包含 `[]:*?/\`，不能以单引号开头或结尾，最长 31 个字符
+fn sanitize_sheet_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .filter(|c| !matches!(c, '[' | ']' | ':' | '*' | '?' | '/' | '\\'))
+        .collect();
+    cleaned
+        .trim()
+        .trim_matches('\'')
+        .chars()
+        .take(MAX_SHEET_NAME_CHARS)
+        .collect::<String>()
+        .trim()
+        .to_string()
+}
+
+fn quote_sheet_name(name: &str) -> String {
+    let plain = name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+        && !name.starts_with(|c: char| c.is_ascii_digit());
+    if plain {
+        name.to_string()
+    } else {
+        format!("'{}'", name.replace('\'', "''"))
+    }
+}
+
+/// 更新公式中对改名工作表的引用
+fn rename_sheet_references(formula: &str, renames: &HashMap<String, String>) -> Option<String> {
+    let mut changed = false;
+    let result = SHEET_REFERENCE.replace_all(formula, |caps: &regex::Captures| {
+        if let Some(quoted) = caps.get(1) {
+            if let Some(new_name) = renames.get(&quoted.as_str().replace("''", "'")) {
+                changed = true;
+                return format!("{}!", quote_sheet_name(new_name));
+            }
+        } else if let Some(new_name) = renames.get(&caps[3]) {
+            changed = true;
+            return format!("{}{}!", &caps[2], quote_sheet_name(new_name));
+        }
+        caps[0].to_string()
+    });
+    changed.then(|| result.into_owned())
+}
+
+/// 根据译文确定新的工作表名称，译文不可用或与其他工作表重名时保留原名
+fn plan_sheet_renames(
+    sheet_names: &[String],
+    translate: impl Fn(&str) -> Option<String>,
+) -> HashMap<String, String> {
+    let mut taken: HashSet<String> = HashSet::new();
+    let mut planned = Vec::with_capacity(sheet_names.len());
+    for name in sheet_names {
+        let candidate = translate(name)
+            .map(|translated| sanitize_sheet_name(&translated))
+            .filter(|candidate| {
+                !candidate.is_empty() && !taken.contains(&candidate.to_lowercase())
+            });
+        let final_name = candidate.unwrap_or_else(|| name.clone());
+        taken.insert(final_name.to_lowercase());
+        planned.push((name.clone(), final_name));
+    }
+
+    planned
+        .into_iter()
+        .filter(|(old, new)| old != new)
+        .collect()
+}
+
+fn apply_sheet_renames(package: &mut Package, renames: &HashMap<String, String>) -> AppResult<()> {
+    if renames.is_empty() {
+        return Ok(());
+    }
+
+    let workbook = office::map_attribute(part(package, WORKBOOK)?, b"sheet", b"name", |name| {
+        renames.get(name).cloned()
+    })?;
+    let workbook = office::map_element_text(&workbook, &[b"definedName"], |formula| {
+        rename_sheet_references(formula, renames)
+    })?;
+    package.set(WORKBOOK, workbook);
+
+    for name in package.names(is_worksheet) {
+        let xml = office::map_element_text(
+            part(package, &name)?,
+            &[b"f", b"formula", b"formula1", b"formula2", b"xm:f"],
+            |formula| rename_sheet_references(formula, renames),
+        )?;
+        package.set(&name, xml);
+    }
+    for name in package.names(is_chart) {
+        let xml = office::map_element_text(part(package, &name)?, &[b"c:f"], |formula| {
+            rename_sheet_references(formula, renames)
+        })?;
+        package.set(&name, xml);
+    }
+    for name in package.names(is_pivot_cache) {
+        let xml = office::map_attribute(
+            part(package, &name)?,
+            b"worksheetSource",
+            b"sheet",
+            |sheet| renames.get(sheet).cloned(),
+        )?;
+        package.set(&name, xml);
+    }
+    Ok(())
+}
+
+/// 收集工作簿中所有需要翻译的文字
+pub(crate) fn collect_workbook_texts(package: &Package) -> AppResult<Vec<String>> {
+    let mut texts = Vec::new();
+    if let Some(xml) = package.get(SHARED_STRINGS) {
+        texts.extend(SHARED_STRING_UNITS.collect(xml)?);
+    }
+    for name in package.names(is_worksheet) {
+        texts.extend(INLINE_STRING_UNITS.collect(part(package, &name)?)?);
+    }
+    for name in package.names(is_comments) {
+        texts.extend(COMMENT_UNITS.collect(part(package, &name)?)?);
+    }
+    texts.extend(office::collect_attribute(
+        part(package, WORKBOOK)?,
+        b"sheet",
+        b"name",
+    )?);
+    Ok(texts)
+}
+
+/// 把译文写回工作簿，`translate` 返回 `None` 的文字保持不变；返回工作表数量
+pub(crate) fn apply_workbook_translations(
+    package: &mut Package,
+    translate: impl Fn(&str) -> Option<String>,
+) -> AppResult<usize> {
+    if let Some(xml) = package.get(SHARED_STRINGS) {
+        let xml = SHARED_STRING_UNITS.transform(xml, &translate)?;
+        package.set(SHARED_STRINGS, xml);
+    }
+    for name in package.names(is_worksheet) {
+        let xml = INLINE_STRING_UNITS.transform(part(package, &name)?, &translate)?;
+        package.set(&name, xml);
+    }
+    for name in package.names(is_comments) {
+        let xml = COMMENT_UNITS.transform(part(package, &name)?, &translate)?;
+        package.set(&name, xml);
+    }
+
+    let sheet_names = office::collect_attribute(part(package, WORKBOOK)?, b"sheet", b"name")?;
+    let renames = plan_sheet_renames(&sheet_names, &translate);
+    apply_sheet_renames(package, &renames)?;
+    Ok(sheet_names.len())
+}
+
+/// 翻译整个工作簿，输出为 `<原文件名>-<语言>.xlsx`；双语模式另外输出 `<原文件名>-<语言>-review.xlsx`
+#[tauri::command]
+pub async fn translate_excel(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    input_path: String,
+    options: TranslationOptions,
+    output_mode: Option<ExcelOutputMode>,
+) -> AppResult<ExcelTranslationResult> {
+    let path = Path::new(&input_path);
+    check_workbook_path(path)?;
+    let output_path = translated_output_path(path, &options.target_language)?;
+
+    let translator = Translator::new(&db, &vault, options).await?;
+    let mut package = Package::open(path)?;
+    let translations = translator
+        .translate_all(collect_workbook_texts(&package)?)
+        .await?;
+
+    // 审校文件需要原文位置，必须在写回译文之前收集
+    let review_entries = match output_mode.unwrap_or_default() {
+        ExcelOutputMode::Translated => None,
+        ExcelOutputMode::Bilingual => Some(review::review_entries(&package)?),
+    };
+
+    let sheets = apply_workbook_translations(&mut package, |text| {
+        translations.get(text).map(str::to_string)
+    })?;
+    package.save(&output_path)?;
+
+    let review_path = match review_entries {
+        Some(entries) => {
+            let path = review::review_path(&output_path);
+            review::write_review_workbook(&path, &entries, &translations)?;
+            Some(path.to_string_lossy().into_owned())
+        }
+        None => None,
+    };
+
+    Ok(ExcelTranslationResult {
+        output_path: output_path.to_string_lossy().into_owned(),
+        review_path,
+        sheets,
+        segments: translations.len(),
+        from_memory: translations.from_memory,
+        warnings: translations.warnings,
+    })
+}
@@ -1,9 +1,12 @@
+use crate::error::{AppError, AppResult};
+use crate::settings::Settings;
 use std::fs::File;
 use std::io::BufWriter;
 use image::DynamicImage;
 use base64::Engine;
 use serde::{Serialize, Deserialize};
 use ico::{IconDir, IconImage, IconDirEntry, ResourceType};
+use tauri::State;
 
 #[derive(Debug, Serialize)]
 pub struct ConversionResult {
@@ -24,9 +27,14 @@ pub struct ImageData {
 /// 
 /// 支持从文件路径或Base64编码的图片数据转换
 #[tauri::command]
-pub async fn convert_to_ico(image_data: ImageData) -> Result<ConversionResult, String> {
-    // 默认图标大小
-    let size = image_data.size.unwrap_or(32);
+pub async fn convert_to_ico(
+    settings: State<'_, Settings>,
+    image_data: ImageData,
+) -> AppResult<ConversionResult> {
+    // 未指定时使用设置中的默认图标大小
+    let size = image_data
+        .size
+        .unwrap_or_else(|| settings.get().image.default_ico_size);
     
     // 加载图像
     let img = if let Some(ref path) = image_data.path {
@@ -34,7 +42,7 @@ pub async fn convert_to_ico(image_data: ImageData) -> Result<ConversionResult, S
     } else if let Some(ref base64_str) = image_data.base64 {
         load_image_from_base64(base64_str)?
     } else {
-        return Err("必须提供图片路径或Base64编码的图片数据".to_string());
+        return Err(AppError::invalid_input("必须提供图片路径或Base64编码的图片数据"));
     };
     
     // 创建圆角图像
@@ -51,15 +59,15 @@ pub async fn convert_to_ico(image_data: ImageData) -> Result<ConversionResult, S
 }
 
 /// 从文件路径加载图片
-fn load_image_from_path(path: &str) -> Result<DynamicImage, String> {
+fn load_image_from_path(path: &str) -> AppResult<DynamicImage> {
     match image::open(path) {
         Ok(img) => Ok(img),
-        Err(e) => Err(format!("无法打开图片文件: {}", e))
+        Err(e) => Err(AppError::io("无法打开图片文件").with_details(e))
     }
 }
 
 /// 从 Base64 字符串加载图片
-fn load_image_from_base64(base64_str: &str) -> Result<DynamicImage, String> {
+fn load_image_from_base64(base64_str: &str) -> AppResult<DynamicImage> {
     // 如果字符串包含数据 URL 前缀，则删除它
     let base64_data = if base64_str.contains("base64,") {
         base64_str.split("base64,").nth(1).unwrap_or(base64_str)
@@ -72,20 +80,20 @@ fn load_image_from_base64(base64_str: &str) -> Result<DynamicImage, String> {
         Ok(data) => {
             match image::load_from_memory(&data) {
                 Ok(img) => Ok(img),
-                Err(e) => Err(format!("无法解析图像数据: {}", e))
+                Err(e) => Err(AppError::invalid_input("无法解析图像数据").with_details(e))
             }
         },
-        Err(e) => Err(format!("无法解码 Base64 数据: {}", e))
+        Err(e) => Err(AppError::invalid_input("无法解码 Base64 数据").with_details(e))
     }
 }
 
 /// 将图像转换为 .ico 格式并保存到指定路径
-fn save_as_ico(img: &DynamicImage, output_path: &str, size: u32) -> Result<(), String> {
+fn save_as_ico(img: &DynamicImage, output_path: &str, size: u32) -> AppResult<()> {
     let resized = img.resize_exact(size, size, image::imageops::FilterType::Lanczos3);
     
     // 创建输出文件
     let file = File::create(output_path)
-        .map_err(|e| format!("无法创建输出文件: {}", e))?;
+        .map_err(|e| AppError::io("无法创建输出文件").with_details(e))?;
     let buf_writer = BufWriter::new(file);
     
     // 创建 ICO 目录
@@ -101,18 +109,18 @@ fn save_as_ico(img: &DynamicImage, output_path: &str, size: u32) -> Result<(), S
     
     // 创建 IconDirEntry 并添加到 ICO 目录
     let entry = IconDirEntry::encode(&icon_image)
-        .map_err(|e| format!("无法创建 ICO 目录项: {}", e))?;
+        .map_err(|e| AppError::internal("无法创建 ICO 目录项").with_details(e))?;
     icon_dir.add_entry(entry);
     
     // 写入 ICO 文件
     icon_dir.write(buf_writer)
-        .map_err(|e| format!("无法写入 ICO 文件: {}", e))?;
+        .map_err(|e| AppError::io("无法写入 ICO 文件").with_details(e))?;
     
     Ok(())
 }
 
 /// 创建带圆角的图像
-fn create_rounded_image(img: &DynamicImage, size: u32) -> Result<DynamicImage, String> {
+fn create_rounded_image(img: &DynamicImage, size: u32) -> AppResult<DynamicImage> {
     use image::{Rgba, RgbaImage, GenericImageView, imageops};
     
     // 使用更高的分辨率处理以消除锯齿
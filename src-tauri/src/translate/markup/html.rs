@@ -0,0 +1,231 @@
+//! HTML 文档翻译
+//!
+//! 直接在源文件上替换文字节点，标签、属性、注释和代码原样保留。
+//! 块级元素之间的文字各为一段，段内的行内标签随译文一起移动。
+
+use super::inline::{apply, Segment, SegmentBuilder};
+use crate::database::secrets::Vault;
+use crate::database::Database;
+use crate::error::AppResult;
+use crate::translate::segments::{
+    check_input_file, read_text_file, translated_output_path, DocumentTranslationResult, Translator,
+};
+use crate::translate::text::TranslationOptions;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+use tauri::State;
+
+/// 不打断段落的行内元素
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "big", "br", "cite", "del", "dfn", "em", "font", "i", "img",
+    "ins", "label", "mark", "q", "s", "small", "span", "strong", "sub", "sup", "time", "u", "wbr",
+];
+/// 内容不翻译的行内元素，在段落中整体作为一个片段
+const INLINE_CODE_ELEMENTS: &[&str] = &["code", "kbd", "samp", "var"];
+/// 内容不翻译的块级元素
+const RAW_ELEMENTS: &[&str] = &[
+    "pre", "script", "style", "textarea", "svg", "math", "template",
+];
+/// 没有结束标签的元素
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+static NO_TRANSLATE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r#"(?i)\stranslate\s*=\s*["']?no\b"#).unwrap());
+
+struct Tag<'a> {
+    /// 小写的元素名
+    name: String,
+    closing: bool,
+    self_closing: bool,
+    raw: &'a str,
+}
+
+/// 解析 `start` 处的标签，不是标签时返回 `None`
+fn parse_tag(source: &str, start: usize) -> Option<Tag<'_>> {
+    let rest = &source[start..];
+    let closing = rest.starts_with("</");
+    let name_start = if closing { 2 } else { 1 };
+    if !rest[name_start..].starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+
+    // 属性值中可能有 `>`
+    let mut quote = None;
+    let mut end = None;
+    for (index, c) in rest.char_indices().skip(name_start) {
+        match (quote, c) {
+            (Some(q), c) if c == q => quote = None,
+            (Some(_), _) => {}
+            (None, '"' | '\'') => quote = Some(c),
+            (None, '>') => {
+                end = Some(index + 1);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let raw = &rest[..end?];
+    let name: String = raw[name_start..]
+        .chars()
+        .take_while(|c| c.is_ascii_alphanumeric() || *c == '-' || *c == ':')
+        .collect();
+    Some(Tag {
+        name: name.to_ascii_lowercase(),
+        closing,
+        self_closing: raw.ends_with("/>"),
+        raw,
+    })
+}
+
+/// 返回元素结束标签之后的位置，`from` 为开始标签之后的位置
+fn element_end(source: &str, lower: &str, name: &str, from: usize) -> usize {
+    let open = format!("<{}", name);
+    let close = format!("</{}", name);
+    let is_boundary = |at: usize| {
+        lower[at..]
+            .chars()
+            .next()
+            .is_none_or(|c| c.is_whitespace() || c == '>' || c == '/')
+    };
+
+    let mut depth = 1;
+    let mut position = from;
+    while let Some(offset) = lower[position..].find('<') {
+        let at = position + offset;
+        position = at + 1;
+        if lower[at..].starts_with(&close) && is_boundary(at + close.len()) {
+            depth -= 1;
+            if depth == 0 {
+                return parse_tag(source, at).map_or(source.len(), |tag| at + tag.raw.len());
+            }
+        } else if lower[at..].starts_with(&open) && is_boundary(at + open.len()) {
+            if let Some(tag) = parse_tag(source, at) {
+                if !tag.self_closing {
+                    depth += 1;
+                }
+            }
+        }
+    }
+    source.len()
+}
+
+/// 按源文件中的顺序列出需要翻译的段落
+fn html_segments(source: &str) -> Vec<Segment> {
+    let lower = source.to_ascii_lowercase();
+    let mut segments = Vec::new();
+    let mut builder = SegmentBuilder::default();
+    let mut position = 0;
+
+    while position < source.len() {
+        let rest = &source[position..];
+        if rest.starts_with("<!--") {
+            let end = rest.find("-->").map_or(source.len(), |i| position + i + 3);
+            builder.keep(position, &source[position..end]);
+            position = end;
+            continue;
+        }
+        if rest.starts_with("<!") || rest.starts_with("<?") {
+            // DOCTYPE、CDATA 和处理指令
+            builder.finish(&mut segments);
+            position = rest.find('>').map_or(source.len(), |i| position + i + 1);
+            continue;
+        }
+
+        let tag = if rest.starts_with('<') {
+            parse_tag(source, position)
+        } else {
+            None
+        };
+        let Some(tag) = tag else {
+            // 文字直到下一个 `<`，不构成标签的 `<` 也作为文字
+            let first = rest.chars().next().map_or(1, char::len_utf8);
+            let end = rest[first..]
+                .find('<')
+                .map_or(source.len(), |i| position + first + i);
+            builder.text(position, &source[position..end]);
+            position = end;
+            continue;
+        };
+        let name = tag.name.as_str();
+        let inline = INLINE_ELEMENTS.contains(&name) || INLINE_CODE_ELEMENTS.contains(&name);
+        let tag_end = position + tag.raw.len();
+
+        let skip_content = !tag.closing
+            && !tag.self_closing
+            && !VOID_ELEMENTS.contains(&name)
+            && (INLINE_CODE_ELEMENTS.contains(&name)
+                || RAW_ELEMENTS.contains(&name)
+                || NO_TRANSLATE.is_match(tag.raw));
+        if skip_content {
+            let end = element_end(source, &lower, name, tag_end);
+            if inline {
+                builder.keep(position, &source[position..end]);
+            } else {
+                builder.finish(&mut segments);
+            }
+            position = end;
+        } else {
+            if !inline {
+                builder.finish(&mut segments);
+            } else if tag.raw[1..tag.raw.len() - 1].contains(['<', '>']) {
+                // 占位符规则按 `<...>` 识别标签，属性值中有尖括号时整体保留
+                builder.keep(position, tag.raw);
+            } else {
+                builder.text(position, tag.raw);
+            }
+            position = tag_end;
+        }
+    }
+    builder.finish(&mut segments);
+    segments
+}
+
+/// 收集文档中所有需要翻译的段落
+pub(crate) fn collect_html_texts(source: &str) -> Vec<String> {
+    html_segments(source)
+        .into_iter()
+        .map(|segment| segment.text)
+        .collect()
+}
+
+/// 把译文写回文档，`translate` 返回 `None` 或标签不一致的段落保持不变
+pub(crate) fn apply_html_translations(
+    source: &str,
+    translate: impl Fn(&str) -> Option<String>,
+    warnings: &mut Vec<String>,
+) -> String {
+    apply(source, &html_segments(source), translate, warnings)
+}
+
+/// 翻译 HTML 文档，输出为 `<原文件名>-<语言>.html`
+#[tauri::command]
+pub async fn translate_html(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    input_path: String,
+    options: TranslationOptions,
+) -> AppResult<DocumentTranslationResult> {
+    let path = Path::new(&input_path);
+    check_input_file(path, &["html", "htm", "xhtml"])?;
+    let output_path = translated_output_path(path, &options.target_language)?;
+
+    let translator = Translator::new(&db, &vault, options).await?;
+    let source = read_text_file(path)?;
+    let mut translations = translator
+        .translate_all(collect_html_texts(&source))
+        .await?;
+
+    let mut warnings = Vec::new();
+    let output = apply_html_translations(
+        &source,
+        |text| translations.get(text).map(str::to_string),
+        &mut warnings,
+    );
+    translations.warnings.extend(warnings);
+    std::fs::write(&output_path, output)?;
+    Ok(DocumentTranslationResult::new(&output_path, translations))
+}
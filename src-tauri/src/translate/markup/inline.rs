@@ -0,0 +1,132 @@
+//! 标记文本中的段落
+//!
+//! 段落中的 HTML 标签原样交给翻译，由占位符规则保护；行内代码、链接地址、
+//! 强调符号等不翻译的片段替换为 `<xN/>`，同样作为占位符保护，写回时换回原文。
+
+use crate::translate::segments::{needs_translation, preview};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::ops::Range;
+
+static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"</?[A-Za-z][^<>]*>").unwrap());
+static KEPT: Lazy<Regex> = Lazy::new(|| Regex::new(r"<x(\d+)/>").unwrap());
+
+/// 源文件中的一段待翻译文字
+pub(crate) struct Segment {
+    range: Range<usize>,
+    /// 交给翻译的文字
+    pub text: String,
+    kept: Vec<String>,
+    /// 译文换行后需要补上的行首标记，如 Markdown 引用的 `> `
+    line_prefix: String,
+}
+
+#[derive(Default)]
+pub(crate) struct SegmentBuilder {
+    range: Option<Range<usize>>,
+    text: String,
+    kept: Vec<String>,
+    line_prefix: String,
+}
+
+impl SegmentBuilder {
+    fn extend(&mut self, start: usize, len: usize) {
+        let range = self.range.get_or_insert(start..start);
+        range.end = start + len;
+    }
+
+    /// 需要翻译的文字，HTML 标签和实体也原样放入
+    pub(crate) fn text(&mut self, start: usize, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.extend(start, text.len());
+        self.text.push_str(text);
+    }
+
+    /// 不翻译的片段
+    pub(crate) fn keep(&mut self, start: usize, text: &str) {
+        if text.is_empty() {
+            return;
+        }
+        self.extend(start, text.len());
+        self.text.push_str(&format!("<x{}/>", self.kept.len()));
+        self.kept.push(text.to_string());
+    }
+
+    /// 段落内换行，`raw` 包括换行符和下一行的行首标记
+    pub(crate) fn line_break(&mut self, start: usize, raw: &str) {
+        self.extend(start, raw.len());
+        self.text.push('\n');
+    }
+
+    pub(crate) fn is_open(&self) -> bool {
+        self.range.is_some()
+    }
+
+    pub(crate) fn set_line_prefix(&mut self, prefix: &str) {
+        self.line_prefix = prefix.to_string();
+    }
+
+    /// 结束当前段落，包含需要翻译的文字时加入 `segments`
+    pub(crate) fn finish(&mut self, segments: &mut Vec<Segment>) {
+        let builder = std::mem::take(self);
+        if let Some(range) = builder.range {
+            if needs_translation(&builder.text) {
+                segments.push(Segment {
+                    range,
+                    text: builder.text,
+                    kept: builder.kept,
+                    line_prefix: builder.line_prefix,
+                });
+            }
+        }
+    }
+}
+
+fn tags(text: &str) -> Vec<&str> {
+    let mut tags: Vec<&str> = TAG.find_iter(text).map(|m| m.as_str()).collect();
+    tags.sort_unstable();
+    tags
+}
+
+/// 把译文写回源文件；标签或不翻译的片段与原文不一致时保留原文，保证结构不变
+pub(crate) fn apply(
+    source: &str,
+    segments: &[Segment],
+    translate: impl Fn(&str) -> Option<String>,
+    warnings: &mut Vec<String>,
+) -> String {
+    let mut output = String::with_capacity(source.len());
+    let mut last = 0;
+    for segment in segments {
+        output.push_str(&source[last..segment.range.start]);
+        last = segment.range.end;
+
+        let original = &source[segment.range.clone()];
+        let Some(translated) = translate(&segment.text) else {
+            output.push_str(original);
+            continue;
+        };
+        if tags(&translated) != tags(&segment.text) {
+            let warning = format!("“{}”的译文标签与原文不一致，已保留原文", preview(original));
+            warnings.push(warning);
+            output.push_str(original);
+            continue;
+        }
+
+        // 先补行首标记，原样保留的片段中的换行不受影响
+        let translated = translated.replace('\n', &format!("\n{}", segment.line_prefix));
+        let restored = KEPT.replace_all(&translated, |caps: &regex::Captures| {
+            caps[1]
+                .parse::<usize>()
+                .ok()
+                .and_then(|index| segment.kept.get(index))
+                .cloned()
+                .unwrap_or_default()
+        });
+        output.push_str(&restored);
+    }
+    output.push_str(&source[last..]);
+    output
+}
@@ -0,0 +1,429 @@
+//! Markdown 文档翻译
+//!
+//! 按行识别块结构，标题、段落、列表项和表格单元格各为一段，直接在源文件上替换。
+//! 前置元数据、代码块、链接地址和 HTML 注释原样保留，强调符号随译文一起移动。
+
+use super::inline::{apply, Segment, SegmentBuilder};
+use crate::database::secrets::Vault;
+use crate::database::Database;
+use crate::error::AppResult;
+use crate::translate::segments::{
+    check_input_file, read_text_file, translated_output_path, DocumentTranslationResult, Translator,
+};
+use crate::translate::text::TranslationOptions;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::path::Path;
+use tauri::State;
+
+const BOM: char = '\u{feff}';
+
+static BLOCKQUOTE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^(?: {0,3}>[ \t]?)+").unwrap());
+static LIST_ITEM: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^[ \t]*(?:[-*+]|\d{1,9}[.)])[ \t]+").unwrap());
+static TASK_BOX: Lazy<Regex> = Lazy::new(|| Regex::new(r"^\[[ xX]\][ \t]+").unwrap());
+static HEADING: Lazy<Regex> = Lazy::new(|| Regex::new(r"^ {0,3}#{1,6}(?:[ \t]+|$)").unwrap());
+static CLOSING_HASHES: Lazy<Regex> = Lazy::new(|| Regex::new(r"[ \t]+#+[ \t]*$").unwrap());
+static SETEXT_UNDERLINE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^ {0,3}(?:=+|-+)[ \t]*$").unwrap());
+static THEMATIC_BREAK: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^ {0,3}(?:(?:\*[ \t]*){3,}|(?:-[ \t]*){3,}|(?:_[ \t]*){3,})$").unwrap()
+});
+static FENCE: Lazy<Regex> = Lazy::new(|| Regex::new(r"^[ \t]*(`{3,}|~{3,})").unwrap());
+static REFERENCE_DEFINITION: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"^ {0,3}\[[^\]]+\]:[ \t]*\S").unwrap());
+static TABLE_DELIMITER: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(r"^[ \t]*\|?[ \t]*:?-+:?[ \t]*(?:\|[ \t]*:?-+:?[ \t]*)*\|?[ \t]*$").unwrap()
+});
+
+struct Line<'a> {
+    start: usize,
+    /// 不含换行符
+    text: &'a str,
+}
+
+fn lines(source: &str) -> Vec<Line<'_>> {
+    let mut start = if source.starts_with(BOM) {
+        BOM.len_utf8()
+    } else {
+        0
+    };
+    let mut lines = Vec::new();
+    for piece in source[start..].split_inclusive('\n') {
+        lines.push(Line {
+            start,
+            text: piece.trim_end_matches(['\n', '\r']),
+        });
+        start += piece.len();
+    }
+    lines
+}
+
+/// 开头的 YAML 或 TOML 前置元数据所占的行数
+fn front_matter_lines(lines: &[Line]) -> usize {
+    let Some(marker) = lines.first().map(|line| line.text.trim_end()) else {
+        return 0;
+    };
+    if marker != "---" && marker != "+++" {
+        return 0;
+    }
+    lines
+        .iter()
+        .skip(1)
+        .position(|line| {
+            let text = line.text.trim_end();
+            text == marker || (marker == "---" && text == "...")
+        })
+        .map_or(0, |index| index + 2)
+}
+
+fn is_table_delimiter(text: &str) -> bool {
+    text.contains('|') && TABLE_DELIMITER.is_match(text)
+}
+
+/// 行内代码的长度，没有配对的反引号也原样保留
+fn code_span_len(text: &str) -> usize {
+    let run = text.bytes().take_while(|b| *b == b'`').count();
+    let mut position = run;
+    while let Some(offset) = text[position..].find('`') {
+        let start = position + offset;
+        let len = text[start..].bytes().take_while(|b| *b == b'`').count();
+        if len == run {
+            return start + len;
+        }
+        position = start + len;
+    }
+    run
+}
+
+/// 强调、加粗和删除线符号的长度，不构成强调时返回 `None`
+fn delimiter_len(text: &str, at: usize) -> Option<usize> {
+    let marker = text.as_bytes()[at];
+    let len = text[at..].bytes().take_while(|b| *b == marker).count();
+    if marker == b'~' && len < 2 {
+        return None;
+    }
+    let before = text[..at].chars().next_back();
+    let after = text[at + len..].chars().next();
+    let left_flanking = after.is_some_and(|c| !c.is_whitespace());
+    let right_flanking = before.is_some_and(|c| !c.is_whitespace());
+    if !left_flanking && !right_flanking {
+        return None;
+    }
+    // 单词内部的下划线不是强调，如 snake_case
+    if marker == b'_'
+        && before.is_some_and(char::is_alphanumeric)
+        && after.is_some_and(char::is_alphanumeric)
+    {
+        return None;
+    }
+    Some(len)
+}
+
+/// 从 `open` 处的括号开始找到配对的 `close` 之后的位置
+fn matching(text: &str, open: char, close: char) -> Option<usize> {
+    let mut depth = 0;
+    let mut escaped = false;
+    for (index, c) in text.char_indices() {
+        match c {
+            _ if escaped => escaped = false,
+            '\\' => escaped = true,
+            c if c == open => depth += 1,
+            c if c == close => {
+                depth -= 1;
+                if depth == 0 {
+                    return Some(index + c.len_utf8());
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// 链接和图片：返回开头标记、链接文字和结尾地址部分的长度
+fn link_parts(text: &str) -> Option<(usize, usize, usize)> {
+    let open = if text.starts_with("![") { 2 } else { 1 };
+    let label_end = open - 1 + matching(&text[open - 1..], '[', ']')?;
+    let rest = &text[label_end..];
+    let tail = match rest.chars().next() {
+        Some('(') => matching(rest, '(', ')')?,
+        Some('[') => matching(rest, '[', ']')?,
+        _ => return None,
+    };
+    Some((open, label_end - 1 - open, tail + 1))
+}
+
+/// 链接地址末尾的标点属于句子
+fn url_len(text: &str) -> usize {
+    let end = text
+        .find(|c: char| c.is_whitespace() || c == '<')
+        .unwrap_or(text.len());
+    text[..end]
+        .trim_end_matches(['.', ',', ';', ':', '!', '?', ')'])
+        .len()
+}
+
+/// 行内内容：代码、链接地址、转义和强调符号作为不翻译的片段
+fn inline(builder: &mut SegmentBuilder, start: usize, text: &str) {
+    let bytes = text.as_bytes();
+    let mut plain = 0;
+    let mut position = 0;
+    while position < text.len() {
+        let rest = &text[position..];
+        let kept = match bytes[position] {
+            b'\\' if rest[1..].starts_with(|c: char| c.is_ascii_punctuation()) => Some(2),
+            b'`' => Some(code_span_len(rest)),
+            b'*' | b'_' | b'~' => delimiter_len(text, position),
+            b'h' if rest.starts_with("http://") || rest.starts_with("https://") => {
+                Some(url_len(rest))
+            }
+            // 脚注引用
+            b'[' if rest.starts_with("[^") => matching(rest, '[', ']'),
+            b'[' | b'!' if rest.starts_with('[') || rest.starts_with("![") => {
+                if let Some((open, label, tail)) = link_parts(rest) {
+                    builder.text(start + plain, &text[plain..position]);
+                    builder.keep(start + position, &rest[..open]);
+                    inline(builder, start + position + open, &rest[open..open + label]);
+                    builder.keep(
+                        start + position + open + label,
+                        &rest[open + label..][..tail],
+                    );
+                    position += open + label + tail;
+                    plain = position;
+                    continue;
+                }
+                None
+            }
+            _ => None,
+        };
+
+        match kept {
+            Some(len) => {
+                builder.text(start + plain, &text[plain..position]);
+                builder.keep(start + position, &rest[..len]);
+                position += len;
+                plain = position;
+            }
+            None => position += rest.chars().next().map_or(1, char::len_utf8),
+        }
+    }
+    builder.text(start + plain, &text[plain..]);
+}
+
+/// 表格的一行，每个单元格为一段
+fn table_row(segments: &mut Vec<Segment>, start: usize, text: &str) {
+    let mut cells = Vec::new();
+    let mut cell_start = 0;
+    let mut position = 0;
+    while position < text.len() {
+        match text.as_bytes()[position] {
+            b'\\' => position += 1,
+            b'`' => position += code_span_len(&text[position..]) - 1,
+            b'|' => {
+                cells.push(cell_start..position);
+                cell_start = position + 1;
+            }
+            _ => {}
+        }
+        position += 1;
+    }
+    cells.push(cell_start..text.len());
+
+    for cell in cells {
+        let raw = &text[cell.clone()];
+        let content = raw.trim();
+        if content.is_empty() {
+            continue;
+        }
+        let leading = raw.len() - raw.trim_start().len();
+        let mut builder = SegmentBuilder::default();
+        inline(&mut builder, start + cell.start + leading, content);
+        builder.finish(segments);
+    }
+}
+
+/// 按源文件中的顺序列出需要翻译的段落
+fn markdown_segments(source: &str) -> Vec<Segment> {
+    let lines = lines(source);
+    let mut segments = Vec::new();
+    let mut paragraph = SegmentBuilder::default();
+    // 上一行段落内容结束的位置，段落内换行从这里开始
+    let mut paragraph_end = 0;
+    let mut fence: Option<(char, usize)> = None;
+    let mut previous_blank = true;
+    let mut in_code = false;
+    let mut in_list = false;
+
+    let mut index = front_matter_lines(&lines);
+    while index < lines.len() {
+        let line = &lines[index];
+        index += 1;
+        let text = line.text;
+
+        if let Some((marker, len)) = fence {
+            let body = text.trim_start_matches([' ', '\t', '>']);
+            let run = body.chars().take_while(|c| *c == marker).count();
+            if run >= len && body[run..].trim().is_empty() {
+                fence = None;
+            }
+            continue;
+        }
+        if text.trim().is_empty() {
+            paragraph.finish(&mut segments);
+            previous_blank = true;
+            continue;
+        }
+        let after_blank = std::mem::replace(&mut previous_blank, false);
+        if after_blank && !text.starts_with([' ', '\t']) && !LIST_ITEM.is_match(text) {
+            in_list = false;
+        }
+
+        // 缩进代码块，列表中的缩进是列表项的内容
+        let indented = text.starts_with("    ") || text.starts_with('\t');
+        if indented && !in_list && !paragraph.is_open() && (after_blank || in_code) {
+            in_code = true;
+            continue;
+        }
+        in_code = false;
+
+        let quote = BLOCKQUOTE.find(text).map_or(0, |m| m.end());
+        let body = &text[quote..];
+        let body_start = line.start + quote;
+
+        if let Some(caps) = FENCE.captures(body) {
+            paragraph.finish(&mut segments);
+            let marker = &caps[1];
+            fence = marker.chars().next().map(|c| (c, marker.len()));
+            continue;
+        }
+        if body.trim_start().starts_with("<!--") {
+            paragraph.finish(&mut segments);
+            let mut current = body;
+            while !current.contains("-->") && index < lines.len() {
+                current = lines[index].text;
+                index += 1;
+            }
+            continue;
+        }
+        if paragraph.is_open() && SETEXT_UNDERLINE.is_match(body) {
+            paragraph.finish(&mut segments);
+            continue;
+        }
+        if THEMATIC_BREAK.is_match(body) || REFERENCE_DEFINITION.is_match(body) {
+            paragraph.finish(&mut segments);
+            continue;
+        }
+
+        let next_is_delimiter = lines.get(index).is_some_and(|next| {
+            is_table_delimiter(&next.text[BLOCKQUOTE.find(next.text).map_or(0, |m| m.end())..])
+        });
+        if body.contains('|') && next_is_delimiter {
+            paragraph.finish(&mut segments);
+            table_row(&mut segments, body_start, body);
+            index += 1;
+            while let Some(row) = lines.get(index) {
+                let quote = BLOCKQUOTE.find(row.text).map_or(0, |m| m.end());
+                let body = &row.text[quote..];
+                if body.trim().is_empty() || !body.contains('|') {
+                    break;
+                }
+                table_row(&mut segments, row.start + quote, body);
+                index += 1;
+            }
+            continue;
+        }
+
+        if let Some(heading) = HEADING.find(body) {
+            paragraph.finish(&mut segments);
+            let content = &body[heading.end()..];
+            let content = match CLOSING_HASHES.find(content) {
+                Some(closing) => &content[..closing.start()],
+                None => content,
+            };
+            inline(&mut paragraph, body_start + heading.end(), content);
+            paragraph.finish(&mut segments);
+            continue;
+        }
+
+        let item = LIST_ITEM.find(body);
+        let mut task_len = 0;
+        if let Some(item) = item {
+            paragraph.finish(&mut segments);
+            in_list = true;
+            task_len = TASK_BOX.find(&body[item.end()..]).map_or(0, |m| m.end());
+        }
+        let marker_len = item.map_or(0, |m| m.end()) + task_len;
+        let offset =
+            marker_len + (body[marker_len..].len() - body[marker_len..].trim_start().len());
+        let content = body[offset..].trim_end();
+        let content_start = body_start + offset;
+
+        if paragraph.is_open() {
+            let raw = &source[paragraph_end..content_start];
+            let newline = raw.find('\n').unwrap_or(raw.len());
+            let trailing = raw[..newline].trim_end_matches('\r');
+            // 行尾两个空格是强制换行
+            if trailing.ends_with("  ") {
+                paragraph.keep(paragraph_end, trailing);
+                paragraph.line_break(paragraph_end + trailing.len(), &raw[trailing.len()..]);
+            } else {
+                paragraph.line_break(paragraph_end, raw);
+            }
+        } else {
+            // 后续行与列表项的文字对齐，任务框不计入
+            let indent = " ".repeat(offset - task_len);
+            paragraph.set_line_prefix(&format!("{}{}", &text[..quote], indent));
+        }
+        inline(&mut paragraph, content_start, content);
+        paragraph_end = content_start + content.len();
+    }
+    paragraph.finish(&mut segments);
+    segments
+}
+
+/// 收集文档中所有需要翻译的段落
+pub(crate) fn collect_markdown_texts(source: &str) -> Vec<String> {
+    markdown_segments(source)
+        .into_iter()
+        .map(|segment| segment.text)
+        .collect()
+}
+
+/// 把译文写回文档，`translate` 返回 `None` 或标签不一致的段落保持不变
+pub(crate) fn apply_markdown_translations(
+    source: &str,
+    translate: impl Fn(&str) -> Option<String>,
+    warnings: &mut Vec<String>,
+) -> String {
+    apply(source, &markdown_segments(source), translate, warnings)
+}
+
+/// 翻译 Markdown 文档，输出为 `<原文件名>-<语言>.md`
+#[tauri::command]
+pub async fn translate_markdown(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    input_path: String,
+    options: TranslationOptions,
+) -> AppResult<DocumentTranslationResult> {
+    let path = Path::new(&input_path);
+    check_input_file(path, &["md", "markdown"])?;
+    let output_path = translated_output_path(path, &options.target_language)?;
+
+    let translator = Translator::new(&db, &vault, options).await?;
+    let source = read_text_file(path)?;
+    let mut translations = translator
+        .translate_all(collect_markdown_texts(&source))
+        .await?;
+
+    let mut warnings = Vec::new();
+    let output = apply_markdown_translations(
+        &source,
+        |text| translations.get(text).map(str::to_string),
+        &mut warnings,
+    );
+    translations.warnings.extend(warnings);
+    std::fs::write(&output_path, output)?;
+    Ok(DocumentTranslationResult::new(&output_path, translations))
+}
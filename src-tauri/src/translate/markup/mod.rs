@@ -0,0 +1,5 @@
+pub mod html;
+mod inline;
+pub mod markdown;
+pub use html::*;
+pub use markdown::*;
@@ -1,2 +1,11 @@
+pub mod document;
+pub mod docx;
 pub mod excel;
-pub mod image;
\ No newline at end of file
+pub mod image;
+pub mod markup;
+pub mod office;
+pub mod pptx;
+pub mod segments;
+pub mod subtitles;
+pub mod text;
+pub mod xliff;
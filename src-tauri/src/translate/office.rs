@@ -0,0 +1,742 @@
+//! Office Open XML 文档（xlsx、docx、pptx）的读写
+//!
+//! 这些文档是由 XML 部件组成的 zip 包。翻译时只替换 XML 中的文本，
+//! 样式、公式、图片等其余内容原样写回，版式保持不变。
+
+use crate::error::{AppError, AppResult};
+use once_cell::sync::Lazy;
+use quick_xml::events::{BytesStart, BytesText, Event};
+use quick_xml::{Reader, Writer};
+use regex::Regex;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+
+/// 整个文档包读入内存，按原顺序写回
+pub(crate) struct Package {
+    parts: Vec<(String, Vec<u8>)>,
+}
+
+impl Package {
+    pub(crate) fn open(path: &Path) -> AppResult<Self> {
+        let file = File::open(path)?;
+        let mut archive = zip::ZipArchive::new(file)
+            .map_err(|e| AppError::invalid_input("无法读取文档，文件可能已损坏").with_details(e))?;
+
+        let mut parts = Vec::with_capacity(archive.len());
+        for index in 0..archive.len() {
+            let mut entry = archive.by_index(index)?;
+            let mut content = Vec::with_capacity(entry.size() as usize);
+            entry.read_to_end(&mut content)?;
+            parts.push((entry.name().to_string(), content));
+        }
+        Ok(Self { parts })
+    }
+
+    /// 按名称顺序返回满足条件的部件名
+    pub(crate) fn names(&self, filter: impl Fn(&str) -> bool) -> Vec<String> {
+        let mut names: Vec<String> = self
+            .parts
+            .iter()
+            .map(|(name, _)| name.clone())
+            .filter(|name| filter(name))
+            .collect();
+        names.sort();
+        names
+    }
+
+    pub(crate) fn get(&self, name: &str) -> Option<&[u8]> {
+        self.parts
+            .iter()
+            .find(|(part, _)| part == name)
+            .map(|(_, content)| content.as_slice())
+    }
+
+    pub(crate) fn set(&mut self, name: &str, content: Vec<u8>) {
+        match self.parts.iter_mut().find(|(part, _)| part == name) {
+            Some((_, existing)) => *existing = content,
+            None => self.parts.push((name.to_string(), content)),
+        }
+    }
+
+    pub(crate) fn save(&self, path: &Path) -> AppResult<()> {
+        let file = File::create(path)?;
+        let mut writer = zip::ZipWriter::new(file);
+        let options =
+            zip::write::FileOptions::default().compression_method(zip::CompressionMethod::Deflated);
+        for (name, content) in &self.parts {
+            writer.start_file(name.as_str(), options)?;
+            writer.write_all(content)?;
+        }
+        writer.finish()?;
+        Ok(())
+    }
+}
+
+/// 部件的关系 `(Id, Type, 目标部件名)`，相对路径已解析为包内的部件名
+pub(crate) fn relationships(
+    package: &Package,
+    part_name: &str,
+) -> AppResult<Vec<(String, String, String)>> {
+    let (dir, file) = part_name.rsplit_once('/').unwrap_or(("", part_name));
+    let Some(xml) = package.get(&format!("{}/_rels/{}.rels", dir, file)) else {
+        return Ok(Vec::new());
+    };
+    let ids = collect_attribute(xml, b"Relationship", b"Id")?;
+    let types = collect_attribute(xml, b"Relationship", b"Type")?;
+    let targets = collect_attribute(xml, b"Relationship", b"Target")?;
+    Ok(ids
+        .into_iter()
+        .zip(types)
+        .zip(targets)
+        .map(|((id, kind), target)| (id, kind, resolve_target(dir, &target)))
+        .collect())
+}
+
+fn resolve_target(base_dir: &str, target: &str) -> String {
+    if let Some(absolute) = target.strip_prefix('/') {
+        return absolute.to_string();
+    }
+    let mut segments: Vec<&str> = base_dir.split('/').filter(|s| !s.is_empty()).collect();
+    for segment in target.split('/') {
+        match segment {
+            ".." => {
+                segments.pop();
+            }
+            "." | "" => {}
+            _ => segments.push(segment),
+        }
+    }
+    segments.join("/")
+}
+
+/// 描述 XML 中的一个“文本单元”：容器元素（如段落）中所有文本元素拼接成一段原文
+pub(crate) struct TextUnits<'a> {
+    /// 容器元素，如 `si`、`w:p`、`a:p`
+    pub container: &'a [u8],
+    /// 保存文字的元素，如 `t`、`w:t`、`a:t`
+    pub text: &'a [u8],
+    /// 其中的文字不属于正文的元素，如 Excel 的注音 `rPh`
+    pub skip: &'a [&'a [u8]],
+    /// 设置后按 run 保留段落内的格式
+    pub runs: Option<RunLayout<'a>>,
+}
+
+/// 段落内 run 的结构。段落中有多种格式时，非主要格式的文字在原文中用 `<gN>…</gN>` 标出，
+/// 译文按标记拆回各自格式的 run，顺序以译文为准
+pub(crate) struct RunLayout<'a> {
+    /// run 元素，如 `w:r`、`a:r`
+    pub run: &'a [u8],
+    /// run 的格式，如 `w:rPr`、`a:rPr`
+    pub properties: &'a [u8],
+    /// 可以移到段落开头或丢弃的元素，如书签和拼写检查标记
+    pub ignorable: &'a [&'a [u8]],
+}
+
+static RUN_TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"</?g(\d+)>").unwrap());
+
+/// 单元中一个 run 的位置和文字
+struct TextRun {
+    start: usize,
+    end: usize,
+    properties: Option<(usize, usize)>,
+    text_element: usize,
+    text: String,
+}
+
+enum RunKind {
+    Text(TextRun),
+    /// 没有文字的 run，可以移动
+    Empty,
+    /// 含有制表符、换行、图片等内容，不能移动
+    Other,
+}
+
+/// 按格式拆分后的段落
+struct TaggedUnit {
+    /// 放在所有 run 之前的元素范围，如段落格式
+    prefix: Vec<(usize, usize)>,
+    suffix: Vec<(usize, usize)>,
+    runs: Vec<TextRun>,
+    /// 每个 run 的格式序号
+    run_formats: Vec<usize>,
+    /// 每种格式的模板 run
+    templates: Vec<usize>,
+    base: usize,
+}
+
+fn element_name<'e>(event: &'e Event) -> Option<&'e [u8]> {
+    match event {
+        Event::Start(start) | Event::Empty(start) => Some(start.name().into_inner()),
+        _ => None,
+    }
+}
+
+/// 从 `start` 开始的元素的结束位置，空元素的结束位置就是自身
+fn element_end(unit: &[Event<'static>], start: usize) -> usize {
+    if !matches!(unit[start], Event::Start(_)) {
+        return start;
+    }
+    let mut depth = 0usize;
+    for (position, event) in unit.iter().enumerate().skip(start) {
+        match event {
+            Event::Start(_) => depth += 1,
+            Event::End(_) => {
+                depth -= 1;
+                if depth == 0 {
+                    return position;
+                }
+            }
+            _ => {}
+        }
+    }
+    unit.len() - 1
+}
+
+fn is_blank(event: &Event) -> bool {
+    match event {
+        Event::Text(text) => text.iter().all(u8::is_ascii_whitespace),
+        Event::Comment(_) => true,
+        _ => false,
+    }
+}
+
+pub(crate) fn reader(xml: &[u8]) -> Reader<&[u8]> {
+    let mut reader = Reader::from_reader(xml);
+    reader.trim_text(false);
+    reader
+}
+
+fn text_of(event: &Event) -> AppResult<String> {
+    Ok(match event {
+        Event::Text(text) => text.unescape()?.into_owned(),
+        Event::CData(data) => String::from_utf8_lossy(data).into_owned(),
+        _ => String::new(),
+    })
+}
+
+impl TextUnits<'_> {
+    /// 按出现顺序返回所有文本单元的原文，空白单元也保留，序号与共享字符串索引一致
+    pub(crate) fn collect(&self, xml: &[u8]) -> AppResult<Vec<String>> {
+        let mut units = Vec::new();
+        self.transform(xml, |text| {
+            units.push(text.to_string());
+            None
+        })?;
+        Ok(units)
+    }
+
+    /// 对每个文本单元调用 `replace`，返回 `Some` 时把新文字写入第一个文本元素并清空其余文本元素，
+    /// 使译文沿用第一段文字的格式
+    pub(crate) fn transform(
+        &self,
+        xml: &[u8],
+        mut replace: impl FnMut(&str) -> Option<String>,
+    ) -> AppResult<Vec<u8>> {
+        let mut reader = reader(xml);
+        let mut writer = Writer::new(Vec::with_capacity(xml.len()));
+        // 容器可以嵌套（如 Word 文本框中的段落），内层先处理完再并入外层
+        let mut stack: Vec<Vec<Event<'static>>> = Vec::new();
+
+        loop {
+            let event = reader.read_event()?;
+            match &event {
+                Event::Eof => break,
+                Event::Start(start) if start.name().as_ref() == self.container => {
+                    stack.push(vec![event.into_owned()]);
+                    continue;
+                }
+                Event::Empty(empty) if empty.name().as_ref() == self.container => {
+                    replace("");
+                }
+                Event::End(end) if end.name().as_ref() == self.container && !stack.is_empty() => {
+                    let mut unit = stack.pop().unwrap_or_default();
+                    unit.push(event.into_owned());
+                    let unit = self.apply(unit, &mut replace)?;
+                    match stack.last_mut() {
+                        Some(parent) => parent.extend(unit),
+                        None => {
+                            for event in unit {
+                                writer.write_event(event)?;
+                            }
+                        }
+                    }
+                    continue;
+                }
+                _ => {}
+            }
+            match stack.last_mut() {
+                Some(unit) => unit.push(event.into_owned()),
+                None => writer.write_event(event)?,
+            }
+        }
+
+        Ok(writer.into_inner())
+    }
+
+    /// 遍历单元内属于本层的文本元素，跳过嵌套容器和 `skip` 中的元素
+    fn visit(
+        &self,
+        unit: &[Event<'static>],
+        mut visit: impl FnMut(usize) -> AppResult<()>,
+    ) -> AppResult<()> {
+        let mut depth = 0usize;
+        let mut skipping = 0usize;
+        let mut in_text = false;
+
+        for (position, event) in unit.iter().enumerate() {
+            match event {
+                Event::Start(start) => {
+                    let name = start.name();
+                    if name.as_ref() == self.container {
+                        depth += 1;
+                    } else if depth == 1 && self.skip.contains(&name.as_ref()) {
+                        skipping += 1;
+                    } else if depth == 1 && skipping == 0 && name.as_ref() == self.text {
+                        in_text = true;
+                    }
+                }
+                Event::End(end) => {
+                    let name = end.name();
+                    if name.as_ref() == self.container {
+                        depth -= 1;
+                    } else if depth == 1 && self.skip.contains(&name.as_ref()) {
+                        skipping -= 1;
+                    } else if in_text && name.as_ref() == self.text {
+                        in_text = false;
+                    }
+                }
+                Event::Text(_) | Event::CData(_) if in_text => visit(position)?,
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    fn apply(
+        &self,
+        unit: Vec<Event<'static>>,
+        replace: &mut impl FnMut(&str) -> Option<String>,
+    ) -> AppResult<Vec<Event<'static>>> {
+        if let Some(layout) = &self.runs {
+            if let Some(tagged) = self.tagged_unit(layout, &unit)? {
+                return self.apply_tagged(unit, tagged, replace);
+            }
+        }
+
+        let mut source = String::new();
+        self.visit(&unit, |position| {
+            source.push_str(&text_of(&unit[position])?);
+            Ok(())
+        })?;
+        let Some(translated) = replace(&source) else {
+            return Ok(unit);
+        };
+
+        // 去掉本层所有文字，再把译文写进第一个文本元素
+        let mut is_text = vec![false; unit.len()];
+        self.visit(&unit, |position| {
+            is_text[position] = true;
+            Ok(())
+        })?;
+        let first_text = self.first_text_element(&unit);
+
+        let mut result = Vec::with_capacity(unit.len() + 2);
+        for (position, event) in unit.into_iter().enumerate() {
+            if is_text[position] {
+                continue;
+            }
+            if Some(position) != first_text {
+                result.push(event);
+                continue;
+            }
+            match event {
+                Event::Start(start) => {
+                    result.push(Event::Start(preserve_space(start, &translated)));
+                    result.push(Event::Text(BytesText::new(&translated).into_owned()));
+                }
+                Event::Empty(empty) => {
+                    let start = preserve_space(empty, &translated);
+                    let end = start.to_end().into_owned();
+                    result.push(Event::Start(start));
+                    result.push(Event::Text(BytesText::new(&translated).into_owned()));
+                    result.push(Event::End(end));
+                }
+                other => result.push(other),
+            }
+        }
+        Ok(result)
+    }
+
+    fn run_kind(
+        &self,
+        layout: &RunLayout,
+        unit: &[Event<'static>],
+        start: usize,
+    ) -> AppResult<RunKind> {
+        let end = element_end(unit, start);
+        let mut properties = None;
+        let mut text_element = None;
+        let mut text = String::new();
+
+        let mut position = start + 1;
+        while position < end {
+            let child_end = element_end(unit, position);
+            match element_name(&unit[position]) {
+                Some(name) if name == layout.properties => properties = Some((position, child_end)),
+                Some(name) if name == self.text => {
+                    text_element.get_or_insert(position);
+                    for event in &unit[position + 1..child_end] {
+                        match event {
+                            Event::Text(_) | Event::CData(_) => text.push_str(&text_of(event)?),
+                            _ => return Ok(RunKind::Other),
+                        }
+                    }
+                }
+                Some(name) if layout.ignorable.contains(&name) => {}
+                None if is_blank(&unit[position]) => {}
+                _ => return Ok(RunKind::Other),
+            }
+            position = child_end + 1;
+        }
+
+        Ok(match text_element {
+            Some(text_element) => RunKind::Text(TextRun {
+                start,
+                end,
+                properties,
+                text_element,
+                text,
+            }),
+            None => RunKind::Empty,
+        })
+    }
+
+    /// 段落只包含文字 run 且有多种格式时按格式拆分，否则返回 `None` 按整段处理
+    fn tagged_unit(
+        &self,
+        layout: &RunLayout,
+        unit: &[Event<'static>],
+    ) -> AppResult<Option<TaggedUnit>> {
+        let last = unit.len() - 1;
+        let mut prefix = Vec::new();
+        // 最后一个文字 run 之后的元素，后面再出现文字 run 时必须能移到段落开头
+        let mut trailing: Vec<((usize, usize), bool)> = Vec::new();
+        let mut runs: Vec<TextRun> = Vec::new();
+
+        let mut position = 1;
+        while position < last {
+            let end = element_end(unit, position);
+            let movable = match element_name(&unit[position]) {
+                Some(name) if name == layout.run => match self.run_kind(layout, unit, position)? {
+                    RunKind::Text(run) => {
+                        for (range, movable) in trailing.drain(..) {
+                            if !movable {
+                                return Ok(None);
+                            }
+                            prefix.push(range);
+                        }
+                        runs.push(run);
+                        position = end + 1;
+                        continue;
+                    }
+                    RunKind::Empty => true,
+                    RunKind::Other => false,
+                },
+                Some(name) => layout.ignorable.contains(&name),
+                None => is_blank(&unit[position]),
+            };
+            if runs.is_empty() {
+                prefix.push((position, end));
+            } else {
+                trailing.push(((position, end), movable));
+            }
+            position = end + 1;
+        }
+
+        // 格式相同的 run 归为一类
+        let mut templates: Vec<usize> = Vec::new();
+        let mut run_formats = Vec::with_capacity(runs.len());
+        for (index, run) in runs.iter().enumerate() {
+            let properties = |run: &TextRun| run.properties.map(|(a, b)| &unit[a..=b]);
+            let format = templates
+                .iter()
+                .position(|&template| properties(&runs[template]) == properties(run))
+                .unwrap_or_else(|| {
+                    templates.push(index);
+                    templates.len() - 1
+                });
+            run_formats.push(format);
+        }
+        if templates.len() < 2 || runs.iter().any(|run| run.text.contains(['<', '>'])) {
+            return Ok(None);
+        }
+
+        // 文字最多的格式作为主要格式，不加标记
+        let mut chars = vec![0usize; templates.len()];
+        for (run, format) in runs.iter().zip(&run_formats) {
+            chars[*format] += run.text.chars().count();
+        }
+        let base = (0..templates.len())
+            .max_by_key(|format| chars[*format])
+            .unwrap_or(0);
+
+        Ok(Some(TaggedUnit {
+            prefix,
+            suffix: trailing.into_iter().map(|(range, _)| range).collect(),
+            runs,
+            run_formats,
+            templates,
+            base,
+        }))
+    }
+
+    fn apply_tagged(
+        &self,
+        unit: Vec<Event<'static>>,
+        tagged: TaggedUnit,
+        replace: &mut impl FnMut(&str) -> Option<String>,
+    ) -> AppResult<Vec<Event<'static>>> {
+        // 主要格式之外的格式按出现顺序编号，相邻的同格式 run 合并为一段
+        let mut next_tag = 0;
+        let tags: Vec<Option<usize>> = (0..tagged.templates.len())
+            .map(|format| {
+                (format != tagged.base).then(|| {
+                    next_tag += 1;
+                    next_tag
+                })
+            })
+            .collect();
+        let mut pieces: Vec<(Option<usize>, String)> = Vec::new();
+        for (run, format) in tagged.runs.iter().zip(&tagged.run_formats) {
+            let tag = tags[*format];
+            match pieces.last_mut() {
+                Some((last, text)) if *last == tag => text.push_str(&run.text),
+                _ => pieces.push((tag, run.text.clone())),
+            }
+        }
+        let source: String = pieces
+            .iter()
+            .map(|(tag, text)| match tag {
+                Some(tag) => format!("<g{}>{}</g{}>", tag, text, tag),
+                None => text.clone(),
+            })
+            .collect();
+        let Some(translated) = replace(&source) else {
+            return Ok(unit);
+        };
+
+        let mut result = vec![unit[0].clone()];
+        for &(start, end) in &tagged.prefix {
+            result.extend_from_slice(&unit[start..=end]);
+        }
+        for (tag, text) in split_tagged(&translated) {
+            let format = tags
+                .iter()
+                .position(|candidate| tag.is_some() && *candidate == tag)
+                .unwrap_or(tagged.base);
+            let template = &tagged.runs[tagged.templates[format]];
+            result.push(unit[template.start].clone());
+            if let Some((start, end)) = template.properties {
+                result.extend_from_slice(&unit[start..=end]);
+            }
+            let text_start = match &unit[template.text_element] {
+                Event::Start(start) | Event::Empty(start) => start.clone(),
+                _ => continue,
+            };
+            let text_start = preserve_space(text_start, &text);
+            let text_end = text_start.to_end().into_owned();
+            result.push(Event::Start(text_start));
+            result.push(Event::Text(BytesText::new(&text).into_owned()));
+            result.push(Event::End(text_end));
+            result.push(unit[template.end].clone());
+        }
+        for &(start, end) in &tagged.suffix {
+            result.extend_from_slice(&unit[start..=end]);
+        }
+        result.push(unit[unit.len() - 1].clone());
+        Ok(result)
+    }
+
+    /// 本层第一个文本元素的起始标签位置
+    fn first_text_element(&self, unit: &[Event<'static>]) -> Option<usize> {
+        let mut depth = 0usize;
+        let mut skipping = 0usize;
+        for (position, event) in unit.iter().enumerate() {
+            match event {
+                Event::Start(start) => {
+                    let name = start.name();
+                    if name.as_ref() == self.container {
+                        depth += 1;
+                    } else if depth == 1 && self.skip.contains(&name.as_ref()) {
+                        skipping += 1;
+                    } else if depth == 1 && skipping == 0 && name.as_ref() == self.text {
+                        return Some(position);
+                    }
+                }
+                Event::End(end) => {
+                    let name = end.name();
+                    if name.as_ref() == self.container {
+                        depth -= 1;
+                    } else if depth == 1 && self.skip.contains(&name.as_ref()) {
+                        skipping -= 1;
+                    }
+                }
+                Event::Empty(empty)
+                    if depth == 1 && skipping == 0 && empty.name().as_ref() == self.text =>
+                {
+                    return Some(position);
+                }
+                _ => {}
+            }
+        }
+        None
+    }
+}
+
+/// 按 `<gN>` 标记拆分译文，标记外的文字序号为 `None`；标记缺失或不成对时尽量按已有的标记拆分
+fn split_tagged(translated: &str) -> Vec<(Option<usize>, String)> {
+    let mut pieces: Vec<(Option<usize>, String)> = Vec::new();
+    let mut open: Vec<usize> = Vec::new();
+    let mut push = |tag: Option<usize>, text: &str| {
+        if text.is_empty() {
+            return;
+        }
+        match pieces.last_mut() {
+            Some((last, previous)) if *last == tag => previous.push_str(text),
+            _ => pieces.push((tag, text.to_string())),
+        }
+    };
+
+    let mut last = 0;
+    for caps in RUN_TAG.captures_iter(translated) {
+        let Some(whole) = caps.get(0) else {
+            continue;
+        };
+        push(open.last().copied(), &translated[last..whole.start()]);
+        last = whole.end();
+        let tag: usize = caps[1].parse().unwrap_or(0);
+        if whole.as_str().starts_with("</") {
+            if let Some(position) = open.iter().rposition(|open| *open == tag) {
+                open.truncate(position);
+            }
+        } else {
+            open.push(tag);
+        }
+    }
+    push(open.last().copied(), &translated[last..]);
+    pieces
+}
+
+/// 首尾有空白的文字需要 `xml:space="preserve"`，否则会被 Office 忽略。
+/// DrawingML 的 `a:t` 不允许该属性，其中的空白总会保留。
+fn preserve_space(start: BytesStart<'static>, text: &str) -> BytesStart<'static> {
+    let needs_preserve = (text.starts_with(char::is_whitespace)
+        || text.ends_with(char::is_whitespace))
+        && !start.name().as_ref().starts_with(b"a:");
+    let has_preserve = start
+        .attributes()
+        .flatten()
+        .any(|attr| attr.key.as_ref() == b"xml:space");
+    if !needs_preserve || has_preserve {
+        return start;
+    }
+    let mut start = start;
+    start.push_attribute(("xml:space", "preserve"));
+    start
+}
+
+/// 替换指定元素内的文字，例如公式 `f`
+pub(crate) fn map_element_text(
+    xml: &[u8],
+    elements: &[&[u8]],
+    mut map: impl FnMut(&str) -> Option<String>,
+) -> AppResult<Vec<u8>> {
+    let mut reader = reader(xml);
+    let mut writer = Writer::new(Vec::with_capacity(xml.len()));
+    let mut inside = 0usize;
+
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Eof => break,
+            Event::Start(start) if elements.contains(&start.name().as_ref()) => inside += 1,
+            Event::End(end) if elements.contains(&end.name().as_ref()) => {
+                inside = inside.saturating_sub(1)
+            }
+            Event::Text(text) if inside > 0 => {
+                if let Some(mapped) = map(&text.unescape()?) {
+                    writer.write_event(Event::Text(BytesText::new(&mapped)))?;
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        writer.write_event(event)?;
+    }
+
+    Ok(writer.into_inner())
+}
+
+/// 读取指定元素的某个属性，按出现顺序返回
+pub(crate) fn collect_attribute(
+    xml: &[u8],
+    element: &[u8],
+    attribute: &[u8],
+) -> AppResult<Vec<String>> {
+    let mut values = Vec::new();
+    map_attribute(xml, element, attribute, |value| {
+        values.push(value.to_string());
+        None
+    })?;
+    Ok(values)
+}
+
+/// 替换指定元素的某个属性，例如工作表名称
+pub(crate) fn map_attribute(
+    xml: &[u8],
+    element: &[u8],
+    attribute: &[u8],
+    mut map: impl FnMut(&str) -> Option<String>,
+) -> AppResult<Vec<u8>> {
+    let mut reader = reader(xml);
+    let mut writer = Writer::new(Vec::with_capacity(xml.len()));
+
+    loop {
+        let event = reader.read_event()?;
+        let (start, is_empty) = match &event {
+            Event::Eof => break,
+            Event::Start(start) if start.name().as_ref() == element => (start, false),
+            Event::Empty(start) if start.name().as_ref() == element => (start, true),
+            _ => {
+                writer.write_event(event)?;
+                continue;
+            }
+        };
+
+        let mut rewritten =
+            BytesStart::new(String::from_utf8_lossy(start.name().as_ref()).into_owned());
+        for attr in start.attributes() {
+            let attr = attr.map_err(quick_xml::Error::from)?;
+            let mapped = if attr.key.as_ref() == attribute {
+                map(&attr.decode_and_unescape_value(&reader)?)
+            } else {
+                None
+            };
+            match mapped {
+                Some(mapped) => {
+                    let key = String::from_utf8_lossy(attr.key.as_ref()).into_owned();
+                    rewritten.push_attribute((key.as_str(), mapped.as_str()));
+                }
+                None => rewritten.push_attribute(attr),
+            }
+        }
+        writer.write_event(if is_empty {
+            Event::Empty(rewritten)
+        } else {
+            Event::Start(rewritten)
+        })?;
+    }
+
+    Ok(writer.into_inner())
+}
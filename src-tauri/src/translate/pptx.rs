@@ -0,0 +1,291 @@
+//! PowerPoint 演示文稿翻译
+//!
+//! 翻译幻灯片、备注、图表和 SmartArt 中的文字，run 格式与 Word 翻译一样保留。
+//! 译文比原文长出较多的文本框可能放不下，按形状给出提示。
+
+use super::office::{self, Package, RunLayout, TextUnits};
+use super::segments::{check_input_file, translated_output_path, Translator};
+use super::text::TranslationOptions;
+use crate::database::secrets::Vault;
+use crate::database::Database;
+use crate::error::AppResult;
+use quick_xml::events::Event;
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use tauri::State;
+
+const PRESENTATION: &str = "ppt/presentation.xml";
+// 译文字符数超过原文的该倍数时提示可能溢出；太短的文字不提示
+const OVERFLOW_GROWTH: f32 = 1.2;
+const MIN_OVERFLOW_CHARS: usize = 8;
+
+const PARAGRAPHS: TextUnits = TextUnits {
+    container: b"a:p",
+    text: b"a:t",
+    skip: &[],
+    runs: Some(RunLayout {
+        run: b"a:r",
+        properties: b"a:rPr",
+        ignorable: &[],
+    }),
+};
+// 图表中缓存的分类名称等文字，数值缓存（如 `1.5E-3`）由 `is_chart_number` 跳过
+const CHART_VALUES: TextUnits = TextUnits {
+    container: b"c:pt",
+    text: b"c:v",
+    skip: &[],
+    runs: None,
+};
+
+#[derive(Serialize, Debug)]
+pub struct TextOverflow {
+    /// 从 1 开始的幻灯片序号
+    pub slide: usize,
+    pub shape: String,
+    pub source_chars: usize,
+    pub translated_chars: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct PresentationTranslationResult {
+    pub output_path: String,
+    pub slides: usize,
+    /// 去重后翻译的文字条数
+    pub segments: usize,
+    /// 其中直接取自翻译记忆的条数
+    pub from_memory: usize,
+    /// 可能放不下译文的文本框
+    pub overflow: Vec<TextOverflow>,
+    pub warnings: Vec<String>,
+}
+
+/// 形状中的文字字符数
+struct ShapeText {
+    name: String,
+    /// 形状会随文字调整大小
+    auto_fit: bool,
+    chars: usize,
+}
+
+fn shape_texts(xml: &[u8]) -> AppResult<Vec<ShapeText>> {
+    let mut reader = office::reader(xml);
+    let mut shapes = Vec::new();
+    let mut current: Option<ShapeText> = None;
+    let mut in_text = false;
+
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Eof => break,
+            Event::Start(start) if start.name().as_ref() == b"p:sp" => {
+                current = Some(ShapeText {
+                    name: String::new(),
+                    auto_fit: false,
+                    chars: 0,
+                });
+            }
+            Event::End(end) if end.name().as_ref() == b"p:sp" => {
+                shapes.extend(current.take());
+            }
+            Event::Start(start) | Event::Empty(start) => {
+                let Some(shape) = current.as_mut() else {
+                    continue;
+                };
+                match start.name().as_ref() {
+                    b"p:cNvPr" => {
+                        for attr in start.attributes().flatten() {
+                            if attr.key.as_ref() == b"name" {
+                                shape.name = attr.decode_and_unescape_value(&reader)?.into_owned();
+                            }
+                        }
+                    }
+                    b"a:spAutoFit" => shape.auto_fit = true,
+                    b"a:t" => in_text = matches!(event, Event::Start(_)),
+                    _ => {}
+                }
+            }
+            Event::End(end) if end.name().as_ref() == b"a:t" => in_text = false,
+            Event::Text(text) if in_text => {
+                if let Some(shape) = current.as_mut() {
+                    shape.chars += text.unescape()?.chars().count();
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(shapes)
+}
+
+/// 按演示文稿中的顺序返回幻灯片部件名
+fn slide_parts(package: &Package) -> AppResult<Vec<String>> {
+    let Some(presentation) = package.get(PRESENTATION) else {
+        return Ok(
+            package.names(|name| name.starts_with("ppt/slides/slide") && name.ends_with(".xml"))
+        );
+    };
+    let targets: HashMap<String, String> = office::relationships(package, PRESENTATION)?
+        .into_iter()
+        .map(|(id, _, target)| (id, target))
+        .collect();
+    Ok(
+        office::collect_attribute(presentation, b"p:sldId", b"r:id")?
+            .iter()
+            .filter_map(|id| targets.get(id).cloned())
+            .filter(|part| package.get(part).is_some())
+            .collect(),
+    )
+}
+
+fn is_chart(name: &str) -> bool {
+    name.starts_with("ppt/charts/chart") && name.ends_with(".xml")
+}
+
+fn is_diagram(name: &str) -> bool {
+    (name.starts_with("ppt/diagrams/data") || name.starts_with("ppt/diagrams/drawing"))
+        && name.ends_with(".xml")
+}
+
+fn translate_part(
+    package: &mut Package,
+    name: &str,
+    units: &TextUnits,
+    translate: &impl Fn(&str) -> Option<String>,
+) -> AppResult<()> {
+    if let Some(xml) = package.get(name) {
+        let xml = units.transform(xml, translate)?;
+        package.set(name, xml);
+    }
+    Ok(())
+}
+
+/// 需要翻译的部件
+struct PresentationParts {
+    /// 按演示文稿中的顺序排列
+    slides: Vec<String>,
+    /// 幻灯片、备注和 SmartArt
+    text: Vec<String>,
+    charts: Vec<String>,
+}
+
+fn presentation_parts(package: &Package) -> AppResult<PresentationParts> {
+    let slides = slide_parts(package)?;
+    let mut text = slides.clone();
+    for slide in &slides {
+        for (_, kind, target) in office::relationships(package, slide)? {
+            if kind.ends_with("/notesSlide") && package.get(&target).is_some() {
+                text.push(target);
+            }
+        }
+    }
+    text.extend(package.names(is_diagram));
+    Ok(PresentationParts {
+        slides,
+        text,
+        charts: package.names(is_chart),
+    })
+}
+
+/// 图表数值缓存中的数字可能含有字母（如 `1.5E-3`、`NaN`），不能交给模型改写
+fn is_chart_number(text: &str) -> bool {
+    text.trim().parse::<f64>().is_ok()
+}
+
+/// 收集演示文稿中所有需要翻译的文字
+pub(crate) fn collect_presentation_texts(package: &Package) -> AppResult<Vec<String>> {
+    let parts = presentation_parts(package)?;
+    let mut texts = Vec::new();
+    for name in parts.text.iter().chain(&parts.charts) {
+        if let Some(xml) = package.get(name) {
+            texts.extend(PARAGRAPHS.collect(xml)?);
+        }
+    }
+    for name in &parts.charts {
+        if let Some(xml) = package.get(name) {
+            texts.extend(
+                CHART_VALUES
+                    .collect(xml)?
+                    .into_iter()
+                    .filter(|text| !is_chart_number(text)),
+            );
+        }
+    }
+    Ok(texts)
+}
+
+/// 把译文写回演示文稿，`translate` 返回 `None` 的文字保持不变；返回幻灯片数量和可能放不下译文的文本框
+pub(crate) fn apply_presentation_translations(
+    package: &mut Package,
+    translate: impl Fn(&str) -> Option<String>,
+) -> AppResult<(usize, Vec<TextOverflow>)> {
+    let parts = presentation_parts(package)?;
+    let mut overflow = Vec::new();
+    for name in parts.text.iter().chain(&parts.charts) {
+        let before = match parts.slides.iter().position(|slide| slide == name) {
+            Some(index) => Some((index, shape_texts(package.get(name).unwrap_or_default())?)),
+            None => None,
+        };
+        translate_part(package, name, &PARAGRAPHS, &translate)?;
+
+        let Some((index, before)) = before else {
+            continue;
+        };
+        let after = shape_texts(package.get(name).unwrap_or_default())?;
+        for (source, translated) in before.into_iter().zip(after) {
+            if !source.auto_fit
+                && source.chars >= MIN_OVERFLOW_CHARS
+                && translated.chars as f32 > source.chars as f32 * OVERFLOW_GROWTH
+            {
+                overflow.push(TextOverflow {
+                    slide: index + 1,
+                    shape: source.name,
+                    source_chars: source.chars,
+                    translated_chars: translated.chars,
+                });
+            }
+        }
+    }
+    let translate_chart_value = |text: &str| {
+        if is_chart_number(text) {
+            None
+        } else {
+            translate(text)
+        }
+    };
+    for name in &parts.charts {
+        translate_part(package, name, &CHART_VALUES, &translate_chart_value)?;
+    }
+    Ok((parts.slides.len(), overflow))
+}
+
+/// 翻译演示文稿，输出为 `<原文件名>-<语言>.pptx`
+#[tauri::command]
+pub async fn translate_pptx(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    input_path: String,
+    options: TranslationOptions,
+) -> AppResult<PresentationTranslationResult> {
+    let path = Path::new(&input_path);
+    check_input_file(path, &["pptx", "pptm"])?;
+    let output_path = translated_output_path(path, &options.target_language)?;
+
+    let translator = Translator::new(&db, &vault, options).await?;
+    let mut package = Package::open(path)?;
+    let translations = translator
+        .translate_all(collect_presentation_texts(&package)?)
+        .await?;
+    let (slides, overflow) = apply_presentation_translations(&mut package, |text| {
+        translations.get(text).map(str::to_string)
+    })?;
+    package.save(&output_path)?;
+
+    Ok(PresentationTranslationResult {
+        output_path: output_path.to_string_lossy().into_owned(),
+        slides,
+        segments: translations.len(),
+        from_memory: translations.from_memory,
+        overflow,
+        warnings: translations.warnings,
+    })
+}
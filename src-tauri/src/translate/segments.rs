@@ -0,0 +1,385 @@
+//! 批量翻译
+//!
+//! 文档中的文字先去重，翻译记忆中已有的直接复用，其余按批次发送给模型。
+//! 每批是一个 JSON 字符串数组，模型按相同顺序返回译文；格式不对时逐条重试。
+//! 译文不符合术语表时给出提示，也不存入翻译记忆。
+
+use super::text::placeholders::{self, ProtectedText, Protector};
+use super::text::{
+    extract_json, references, TranslationOptions, MAX_MEMORY_EXAMPLES, TRANSLATION_TEMPERATURE,
+};
+use crate::ai::llm::{self, ChatRequestMessage};
+use crate::database::glossary::Glossary;
+use crate::database::secrets::Vault;
+use crate::database::translation_memory::{self, MemoryMatch, MemoryScope};
+use crate::database::Database;
+use crate::error::{AppError, AppResult, ErrorCode};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+
+const MAX_BATCH_ITEMS: usize = 40;
+const MAX_BATCH_CHARS: usize = 6000;
+const PREVIEW_CHARS: usize = 40;
+// 每批最多附带的翻译记忆参考条数
+const MAX_BATCH_EXAMPLES: usize = 20;
+
+/// 文档翻译的结果
+#[derive(Serialize, Debug)]
+pub struct DocumentTranslationResult {
+    pub output_path: String,
+    /// 去重后翻译的文字条数
+    pub segments: usize,
+    /// 其中直接取自翻译记忆的条数
+    pub from_memory: usize,
+    pub warnings: Vec<String>,
+}
+
+impl DocumentTranslationResult {
+    pub(crate) fn new(output_path: &Path, translations: TranslationMap) -> Self {
+        Self {
+            output_path: output_path.to_string_lossy().into_owned(),
+            segments: translations.len(),
+            from_memory: translations.from_memory,
+            warnings: translations.warnings,
+        }
+    }
+}
+
+/// 原文到译文的映射
+#[derive(Default)]
+pub(crate) struct TranslationMap {
+    entries: HashMap<String, String>,
+    /// 直接取自翻译记忆的条数
+    pub from_memory: usize,
+    pub warnings: Vec<String>,
+}
+
+impl TranslationMap {
+    pub(crate) fn get(&self, source: &str) -> Option<&str> {
+        self.entries.get(source).map(String::as_str)
+    }
+
+    /// 去重后实际翻译的条数
+    pub(crate) fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+/// 只有包含文字的内容需要翻译，纯数字、符号和占位符原样保留
+pub(crate) fn needs_translation(text: &str) -> bool {
+    text.chars().any(char::is_alphabetic)
+        && placeholders::protect(text, &[])
+            .text
+            .chars()
+            .any(char::is_alphabetic)
+}
+
+/// 检查待翻译的文件存在且扩展名受支持
+pub(crate) fn check_input_file(path: &Path, extensions: &[&str]) -> AppResult<()> {
+    if !path.exists() {
+        return Err(AppError::not_found("文件不存在"));
+    }
+    let extension = path
+        .extension()
+        .map(|ext| ext.to_string_lossy().to_lowercase())
+        .unwrap_or_default();
+    if !extensions.contains(&extension.as_str()) {
+        let supported: Vec<String> = extensions.iter().map(|ext| format!(".{}", ext)).collect();
+        return Err(AppError::invalid_input(format!(
+            "只支持 {} 文件",
+            supported.join(" 和 ")
+        )));
+    }
+    Ok(())
+}
+
+/// 读取 UTF-8 编码的文本文件
+pub(crate) fn read_text_file(path: &Path) -> AppResult<String> {
+    String::from_utf8(std::fs::read(path)?)
+        .map_err(|e| AppError::invalid_input("文件不是 UTF-8 编码").with_details(e))
+}
+
+/// 译文文件与原文件放在同一目录，命名为 `<原文件名>-<语言>.<扩展名>`
+pub(crate) fn translated_output_path(input: &Path, target_language: &str) -> AppResult<PathBuf> {
+    let stem = input
+        .file_stem()
+        .ok_or_else(|| AppError::invalid_input("无效的文件路径"))?
+        .to_string_lossy();
+    let language: String = target_language
+        .trim()
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() || c == '-' || c == '_' {
+                c
+            } else {
+                '_'
+            }
+        })
+        .collect();
+    let file_name = match input.extension() {
+        Some(ext) => format!("{}-{}.{}", stem, language, ext.to_string_lossy()),
+        None => format!("{}-{}", stem, language),
+    };
+    Ok(input.with_file_name(file_name))
+}
+
+pub(crate) fn preview(text: &str) -> String {
+    let preview: String = text.chars().take(PREVIEW_CHARS).collect();
+    if preview.len() < text.len() {
+        format!("{}…", preview)
+    } else {
+        preview
+    }
+}
+
+/// 原文首尾的空白不交给模型，翻译后按原样补回
+pub(crate) fn split_whitespace(text: &str) -> (&str, &str, &str) {
+    let trimmed_start = text.trim_start();
+    let leading = &text[..text.len() - trimmed_start.len()];
+    let core = trimmed_start.trim_end();
+    let trailing = &trimmed_start[core.len()..];
+    (leading, core, trailing)
+}
+
+/// 按条数和字符数切分批次
+fn batches(texts: &[String]) -> Vec<&[String]> {
+    let mut result = Vec::new();
+    let mut start = 0;
+    let mut chars = 0;
+    for (index, text) in texts.iter().enumerate() {
+        let len = text.chars().count();
+        if index > start && (index - start >= MAX_BATCH_ITEMS || chars + len > MAX_BATCH_CHARS) {
+            result.push(&texts[start..index]);
+            start = index;
+            chars = 0;
+        }
+        chars += len;
+    }
+    if start < texts.len() {
+        result.push(&texts[start..]);
+    }
+    result
+}
+
+pub(crate) struct Translator<'a> {
+    db: &'a Database,
+    vault: &'a Vault,
+    model_id: String,
+    options: TranslationOptions,
+    scope: MemoryScope,
+    glossary: Glossary,
+    /// 各条是同一段内容的连续部分，翻译时参考前后文
+    sequential: bool,
+}
+
+impl<'a> Translator<'a> {
+    pub(crate) async fn new(
+        db: &'a Database,
+        vault: &'a Vault,
+        options: TranslationOptions,
+    ) -> AppResult<Self> {
+        options.validate()?;
+        let model_id = llm::resolve_model_id(db, options.model_id.clone()).await?;
+        let scope = options.memory_scope();
+        let source_language = options.source_language.clone();
+        let target_language = options.target_language.clone();
+        let glossary = db
+            .run(move |conn| Glossary::load(conn, source_language.as_deref(), &target_language))
+            .await?;
+        Ok(Self {
+            db,
+            vault,
+            model_id,
+            options,
+            scope,
+            glossary,
+            sequential: false,
+        })
+    }
+
+    /// 按连续的内容翻译，如字幕，每条仍单独返回译文
+    pub(crate) fn sequential(mut self) -> Self {
+        self.sequential = true;
+        self
+    }
+
+    /// 翻译所有需要翻译的文字，重复的原文只翻译一次
+    pub(crate) async fn translate_all(
+        &self,
+        texts: impl IntoIterator<Item = String>,
+    ) -> AppResult<TranslationMap> {
+        let mut seen = HashSet::new();
+        let unique: Vec<String> = texts
+            .into_iter()
+            .filter(|text| needs_translation(text) && seen.insert(text.clone()))
+            .collect();
+
+        let mut map = TranslationMap::default();
+        let cores: Vec<String> = unique
+            .iter()
+            .map(|text| split_whitespace(text).1.to_string())
+            .collect();
+        let scope = self.scope.clone();
+        let remembered = self
+            .db
+            .run(move |conn| translation_memory::exact_matches(conn, &scope, &cores))
+            .await?;
+        let pending: Vec<String> = unique
+            .into_iter()
+            .filter(|text| {
+                let (leading, core, trailing) = split_whitespace(text);
+                match remembered.get(core) {
+                    Some(target) => {
+                        for violation in self.glossary.violations(core, target) {
+                            map.warnings
+                                .push(format!("“{}”: {}", preview(text), violation));
+                        }
+                        map.entries
+                            .insert(text.clone(), format!("{}{}{}", leading, target, trailing));
+                        map.from_memory += 1;
+                        false
+                    }
+                    None => true,
+                }
+            })
+            .collect();
+
+        // 不翻译的术语与用户指定的内容一样作为占位符保护
+        let mut preserve = self.options.preserve.clone();
+        preserve.extend(self.glossary.protected_terms());
+        let protector = Protector::new(&preserve);
+        for batch in batches(&pending) {
+            let cores: Vec<String> = batch
+                .iter()
+                .map(|text| split_whitespace(text).1.to_string())
+                .collect();
+            let examples = self.examples(cores.clone()).await?;
+            let core_refs: Vec<&str> = cores.iter().map(String::as_str).collect();
+            let context = references(&examples, &self.glossary.matching(&core_refs));
+            let protected: Vec<ProtectedText> =
+                cores.iter().map(|core| protector.protect(core)).collect();
+            let translations = self.translate_protected(&protected, &context).await?;
+
+            let mut learned = Vec::new();
+            for (((source, core), protected), translated) in
+                batch.iter().zip(cores).zip(&protected).zip(translations)
+            {
+                let (restored, missing) =
+                    placeholders::restore(translated.trim(), &protected.placeholders);
+                let violations = self.glossary.violations(&core, &restored);
+                if !missing.is_empty() {
+                    map.warnings.push(format!(
+                        "“{}”的译文缺少: {}",
+                        preview(source),
+                        missing.join(", ")
+                    ));
+                }
+                for violation in &violations {
+                    map.warnings
+                        .push(format!("“{}”: {}", preview(source), violation));
+                }
+                if missing.is_empty() && violations.is_empty() {
+                    learned.push((core, restored.clone()));
+                }
+                let (leading, _, trailing) = split_whitespace(source);
+                map.entries.insert(
+                    source.clone(),
+                    format!("{}{}{}", leading, restored, trailing),
+                );
+            }
+
+            let scope = self.scope.clone();
+            self.db
+                .run(move |conn| translation_memory::store(conn, &scope, &learned))
+                .await?;
+        }
+
+        Ok(map)
+    }
+
+    /// 本批原文在翻译记忆中的相似记录，去重后作为参考
+    async fn examples(&self, cores: Vec<String>) -> AppResult<Vec<MemoryMatch>> {
+        let scope = self.scope.clone();
+        let matches = self
+            .db
+            .run(move |conn| {
+                translation_memory::fuzzy_matches(conn, &scope, &cores, MAX_MEMORY_EXAMPLES)
+            })
+            .await?;
+
+        let mut seen = HashSet::new();
+        Ok(matches
+            .into_iter()
+            .flatten()
+            .filter(|example| seen.insert(example.id.clone()))
+            .take(MAX_BATCH_EXAMPLES)
+            .collect())
+    }
+
+    /// 整批翻译，模型返回的条数不对时逐条重试
+    async fn translate_protected(
+        &self,
+        batch: &[ProtectedText],
+        context: &str,
+    ) -> AppResult<Vec<String>> {
+        if let Some(translations) = self.request(batch, context).await? {
+            return Ok(translations);
+        }
+
+        let mut translations = Vec::with_capacity(batch.len());
+        for item in batch {
+            match self.request(std::slice::from_ref(item), context).await? {
+                Some(mut single) => translations.push(single.remove(0)),
+                None => {
+                    return Err(
+                        AppError::new(ErrorCode::Provider, "模型未按要求的格式返回译文")
+                            .with_details(preview(&item.text)),
+                    )
+                }
+            }
+        }
+        Ok(translations)
+    }
+
+    /// `context` 为翻译记忆和术语表的参考内容；返回 `None` 表示模型的回复无法解析或条数不一致
+    async fn request(
+        &self,
+        batch: &[ProtectedText],
+        context: &str,
+    ) -> AppResult<Option<Vec<String>>> {
+        let texts: Vec<&str> = batch.iter().map(|item| item.text.as_str()).collect();
+        let relation = if self.sequential {
+            "The strings are consecutive parts of one text: use the neighbouring strings as \
+             context, but keep the content of each string in its own translation,"
+        } else {
+            "Translate each string independently"
+        };
+        let system = format!(
+            "You are a professional translator.\n{}\n\
+             The user message is a JSON array of {} strings. {} \
+             and reply with only a JSON array of the {} translations in the same order.{}",
+            self.options.instructions(),
+            texts.len(),
+            relation,
+            texts.len(),
+            context
+        );
+
+        let content = llm::complete(
+            self.db,
+            self.vault,
+            &self.model_id,
+            vec![
+                ChatRequestMessage::system(system),
+                ChatRequestMessage::user(serde_json::to_string(&texts)?),
+            ],
+            Some(TRANSLATION_TEMPERATURE),
+        )
+        .await?;
+
+        Ok(extract_json(&content, '[', ']')
+            .and_then(|json| serde_json::from_str::<Vec<String>>(json).ok())
+            .filter(|translations| translations.len() == texts.len()))
+    }
+}
@@ -0,0 +1,266 @@
+//! 字幕翻译
+//!
+//! 支持 SRT 和 WebVTT。只替换字幕文字行，序号、时间轴、样式和注释原样保留。
+//! 相邻字幕按顺序成批翻译，模型可以参考前后文；可选按每行最大字数重新换行。
+
+use super::segments::{check_input_file, read_text_file, translated_output_path, Translator};
+use super::text::TranslationOptions;
+use crate::database::secrets::Vault;
+use crate::database::Database;
+use crate::error::{AppError, AppResult};
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use std::ops::Range;
+use std::path::Path;
+use tauri::State;
+
+const TIMING_ARROW: &str = "-->";
+/// WebVTT 中不是字幕的块
+const VTT_BLOCKS: &[&str] = &["WEBVTT", "NOTE", "STYLE", "REGION"];
+/// 不能出现在行首的标点
+const NO_LINE_START: &str = "，。、；：？！）」』】》〉”’…,.;:?!)]}%";
+
+static TAG: Lazy<Regex> = Lazy::new(|| Regex::new(r"</?[A-Za-z][^<>]*>").unwrap());
+
+#[derive(Serialize, Debug)]
+pub struct SubtitleTranslationResult {
+    pub output_path: String,
+    pub cues: usize,
+    /// 去重后翻译的文字条数
+    pub segments: usize,
+    /// 其中直接取自翻译记忆的条数
+    pub from_memory: usize,
+    pub warnings: Vec<String>,
+}
+
+/// 按顺序返回每条字幕文字所在的行
+fn cue_text_lines(lines: &[&str], vtt: bool) -> Vec<Range<usize>> {
+    let mut cues = Vec::new();
+    let mut start = 0;
+    while start < lines.len() {
+        if lines[start].trim().is_empty() {
+            start += 1;
+            continue;
+        }
+        let end = (start..lines.len())
+            .find(|&index| lines[index].trim().is_empty())
+            .unwrap_or(lines.len());
+        let skipped = vtt
+            && VTT_BLOCKS
+                .iter()
+                .any(|keyword| lines[start].starts_with(keyword));
+        let timing = (start..end).find(|&index| lines[index].contains(TIMING_ARROW));
+        if let Some(timing) = timing.filter(|_| !skipped) {
+            if timing + 1 < end {
+                cues.push(timing + 1..end);
+            }
+        }
+        start = end;
+    }
+    cues
+}
+
+fn is_cjk(c: char) -> bool {
+    matches!(c,
+        '\u{3000}'..='\u{30ff}'
+        | '\u{3400}'..='\u{4dbf}'
+        | '\u{4e00}'..='\u{9fff}'
+        | '\u{f900}'..='\u{faff}'
+        | '\u{ff00}'..='\u{ffef}')
+}
+
+/// 换行时不拆开的单位：一个 CJK 字符或一个其他文字的词，标签不计宽度
+struct WrapUnit {
+    space_before: bool,
+    text: String,
+    width: usize,
+    cjk: bool,
+}
+
+/// 把多行合并后按 `max_chars` 重新换行；CJK 文字可在两字之间换行，其他文字在空白处换行
+fn rewrap(text: &str, max_chars: usize) -> Vec<String> {
+    // CJK 文字之间的换行直接去掉，其他换行换成空格
+    let mut joined = String::new();
+    for line in text.lines().map(str::trim).filter(|line| !line.is_empty()) {
+        let cjk_boundary = joined.chars().next_back().is_some_and(is_cjk)
+            && line.chars().next().is_some_and(is_cjk);
+        if !joined.is_empty() && !cjk_boundary {
+            joined.push(' ');
+        }
+        joined.push_str(line);
+    }
+
+    let mut units: Vec<WrapUnit> = Vec::new();
+    let mut space = false;
+    let mut position = 0;
+    while let Some(c) = joined[position..].chars().next() {
+        let tag = match c {
+            '<' => TAG.find(&joined[position..]).filter(|tag| tag.start() == 0),
+            _ => None,
+        };
+        if let Some(tag) = tag {
+            match units.last_mut() {
+                Some(last) if !space => last.text.push_str(tag.as_str()),
+                _ => units.push(WrapUnit {
+                    space_before: space,
+                    text: tag.as_str().to_string(),
+                    width: 0,
+                    cjk: false,
+                }),
+            }
+            space = false;
+            position += tag.end();
+            continue;
+        }
+
+        position += c.len_utf8();
+        if c.is_whitespace() {
+            space = !units.is_empty();
+            continue;
+        }
+        let cjk = is_cjk(c);
+        match units.last_mut() {
+            Some(last) if !space && (last.width == 0 || (!cjk && !last.cjk)) => {
+                last.text.push(c);
+                last.width += 1;
+                last.cjk = cjk;
+            }
+            _ => units.push(WrapUnit {
+                space_before: space,
+                text: c.to_string(),
+                width: 1,
+                cjk,
+            }),
+        }
+        space = false;
+    }
+
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut width = 0;
+    for unit in units {
+        let gap = usize::from(unit.space_before && !line.is_empty());
+        let no_break = TAG
+            .replace_all(&unit.text, "")
+            .starts_with(|c| NO_LINE_START.contains(c));
+        if !line.is_empty() && width + gap + unit.width > max_chars && !no_break {
+            lines.push(std::mem::take(&mut line));
+            width = 0;
+        } else if gap == 1 {
+            line.push(' ');
+            width += 1;
+        }
+        line.push_str(&unit.text);
+        width += unit.width;
+    }
+    if !line.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// 按行拆分，保留原来的换行符
+fn split_lines(source: &str) -> (Vec<&str>, &'static str) {
+    let newline = if source.contains("\r\n") {
+        "\r\n"
+    } else {
+        "\n"
+    };
+    let lines = source
+        .split('\n')
+        .map(|line| line.strip_suffix('\r').unwrap_or(line))
+        .collect();
+    (lines, newline)
+}
+
+/// 按顺序收集每条字幕的文字，多行之间用换行分隔
+pub(crate) fn collect_subtitle_texts(source: &str, vtt: bool) -> Vec<String> {
+    let (lines, _) = split_lines(source);
+    cue_text_lines(&lines, vtt)
+        .into_iter()
+        .map(|cue| lines[cue].join("\n"))
+        .collect()
+}
+
+/// 把译文写回字幕，`translate` 返回 `None` 的字幕保持不变；指定 `max_line_chars` 时重新换行
+pub(crate) fn apply_subtitle_translations(
+    source: &str,
+    vtt: bool,
+    translate: impl Fn(&str) -> Option<String>,
+    max_line_chars: Option<usize>,
+) -> String {
+    let (lines, newline) = split_lines(source);
+    let mut output: Vec<String> = Vec::with_capacity(lines.len());
+    let mut last = 0;
+    for cue in cue_text_lines(&lines, vtt) {
+        output.extend(lines[last..cue.start].iter().map(|line| line.to_string()));
+        last = cue.end;
+
+        let text = lines[cue.clone()].join("\n");
+        let translated = translate(&text).unwrap_or(text);
+        let wrapped = match max_line_chars {
+            Some(max) => rewrap(&translated, max),
+            None => translated.lines().map(str::to_string).collect(),
+        };
+        // 空行会结束字幕，不能出现在文字中
+        let wrapped: Vec<String> = wrapped
+            .into_iter()
+            .filter(|line| !line.trim().is_empty())
+            .collect();
+        if wrapped.is_empty() {
+            output.extend(lines[cue].iter().map(|line| line.to_string()));
+        } else {
+            output.extend(wrapped);
+        }
+    }
+    output.extend(lines[last..].iter().map(|line| line.to_string()));
+    output.join(newline)
+}
+
+pub(crate) fn is_vtt(path: &Path) -> bool {
+    path.extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("vtt"))
+}
+
+/// 翻译字幕文件，输出为 `<原文件名>-<语言>.<扩展名>`
+///
+/// `max_line_chars` 为每行最大字数，不指定时保留译文中的换行
+#[tauri::command]
+pub async fn translate_subtitles(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    input_path: String,
+    options: TranslationOptions,
+    max_line_chars: Option<usize>,
+) -> AppResult<SubtitleTranslationResult> {
+    let path = Path::new(&input_path);
+    check_input_file(path, &["srt", "vtt"])?;
+    if max_line_chars == Some(0) {
+        return Err(AppError::invalid_input("每行最大字数必须大于 0"));
+    }
+    let vtt = is_vtt(path);
+    let output_path = translated_output_path(path, &options.target_language)?;
+
+    let translator = Translator::new(&db, &vault, options).await?.sequential();
+    let source = read_text_file(path)?;
+    let texts = collect_subtitle_texts(&source, vtt);
+    let cues = texts.len();
+    let translations = translator.translate_all(texts).await?;
+
+    let output = apply_subtitle_translations(
+        &source,
+        vtt,
+        |text| translations.get(text).map(str::to_string),
+        max_line_chars,
+    );
+    std::fs::write(&output_path, output)?;
+
+    Ok(SubtitleTranslationResult {
+        output_path: output_path.to_string_lossy().into_owned(),
+        cues,
+        segments: translations.len(),
+        from_memory: translations.from_memory,
+        warnings: translations.warnings,
+    })
+}
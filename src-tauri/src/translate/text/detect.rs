@@ -0,0 +1,42 @@
+//! 基于文字系统的语言检测
+//!
+//! 只识别能由字符集唯一确定的语言，拉丁字母等多种语言共用的文字返回 `None`，交给模型判断。
+
+/// 返回 ISO 639-1 语言代码
+pub fn detect_script_language(text: &str) -> Option<&'static str> {
+    let mut han = 0usize;
+    let mut kana = 0usize;
+    let mut hangul = 0usize;
+    let mut other: Option<&'static str> = None;
+    let mut letters = 0usize;
+
+    for c in text.chars().filter(|c| c.is_alphabetic()) {
+        letters += 1;
+        match c as u32 {
+            0x3040..=0x30FF | 0x31F0..=0x31FF => kana += 1,
+            0x4E00..=0x9FFF | 0x3400..=0x4DBF | 0xF900..=0xFAFF => han += 1,
+            0xAC00..=0xD7AF | 0x1100..=0x11FF | 0x3130..=0x318F => hangul += 1,
+            0x0E00..=0x0E7F => other = other.or(Some("th")),
+            0x0370..=0x03FF => other = other.or(Some("el")),
+            0x0590..=0x05FF => other = other.or(Some("he")),
+            0x10A0..=0x10FF => other = other.or(Some("ka")),
+            0x0530..=0x058F => other = other.or(Some("hy")),
+            _ => {}
+        }
+    }
+
+    if letters == 0 {
+        return None;
+    }
+    // 日文中通常夹杂汉字，出现假名即判断为日文
+    if kana > 0 {
+        return Some("ja");
+    }
+    if hangul > 0 {
+        return Some("ko");
+    }
+    if han * 2 >= letters {
+        return Some("zh");
+    }
+    other
+}
@@ -0,0 +1,5 @@
+pub mod detect;
+pub mod placeholders;
+#[allow(clippy::module_inception)]
+pub mod text;
+pub use text::*;
@@ -0,0 +1,103 @@
+//! 占位符保护
+//!
+//! 翻译前把变量、格式化符号、HTML 标签、链接等替换为 `⟦n⟧` 标记，
+//! 翻译后再换回原文，避免模型改写或翻译这些内容。
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+
+const BUILTIN_PATTERNS: &[&str] = &[
+    // {{name}}、${name}、{0}、{name}
+    r"\{\{[^{}]*\}\}",
+    r"\$\{[^{}]*\}",
+    r"\{[A-Za-z0-9_.:\-]*\}",
+    // printf 风格：%s、%1$s、%.2f、%d
+    r"%(?:\d+\$)?[-+#0]*\d*(?:\.\d+)?[sdfiuxXeEgGcp@]",
+    // HTML/XML 标签和实体
+    r"</?[A-Za-z][^<>]*>",
+    r"&(?:[A-Za-z]+|#\d+|#x[0-9A-Fa-f]+);",
+    r"https?://[^\s<>]+",
+];
+
+static BUILTIN: Lazy<String> = Lazy::new(|| BUILTIN_PATTERNS.join("|"));
+static TOKEN: Lazy<Regex> = Lazy::new(|| Regex::new(r"⟦(\d+)⟧").unwrap());
+static DEFAULT_PROTECTOR: Lazy<Protector> = Lazy::new(|| Protector::new(&[]));
+
+#[derive(Debug, Clone)]
+pub struct ProtectedText {
+    /// 替换后发送给模型的文本
+    pub text: String,
+    /// 按标记序号排列的原始内容
+    pub placeholders: Vec<String>,
+}
+
+/// 编译好的占位符规则，批量翻译时只需构建一次
+pub struct Protector {
+    pattern: Regex,
+}
+
+impl Protector {
+    /// 内置规则加上 `extra` 中指定的原文（如产品名、术语）
+    pub fn new(extra: &[String]) -> Self {
+        let mut literals: Vec<&str> = extra
+            .iter()
+            .map(|s| s.as_str())
+            .filter(|s| !s.is_empty())
+            .collect();
+        // 较长的原文优先匹配，避免被其中包含的较短原文截断
+        literals.sort_by_key(|s| std::cmp::Reverse(s.len()));
+
+        let mut alternatives: Vec<String> = literals.iter().map(|s| regex::escape(s)).collect();
+        alternatives.push(BUILTIN.clone());
+        let pattern = Regex::new(&alternatives.join("|")).expect("占位符规则无效");
+        Self { pattern }
+    }
+
+    pub fn protect(&self, text: &str) -> ProtectedText {
+        let mut placeholders = Vec::new();
+        let text = self
+            .pattern
+            .replace_all(text, |caps: &regex::Captures| {
+                placeholders.push(caps[0].to_string());
+                format!("⟦{}⟧", placeholders.len() - 1)
+            })
+            .into_owned();
+
+        ProtectedText { text, placeholders }
+    }
+}
+
+pub fn protect(text: &str, extra: &[String]) -> ProtectedText {
+    if extra.is_empty() {
+        return DEFAULT_PROTECTOR.protect(text);
+    }
+    Protector::new(extra).protect(text)
+}
+
+/// 把标记换回原文，返回结果和缺失的原始内容
+pub fn restore(translated: &str, placeholders: &[String]) -> (String, Vec<String>) {
+    let mut seen = vec![false; placeholders.len()];
+    let restored = TOKEN
+        .replace_all(translated, |caps: &regex::Captures| {
+            match caps[1]
+                .parse::<usize>()
+                .ok()
+                .filter(|i| *i < placeholders.len())
+            {
+                Some(index) => {
+                    seen[index] = true;
+                    placeholders[index].clone()
+                }
+                None => caps[0].to_string(),
+            }
+        })
+        .into_owned();
+
+    let missing = placeholders
+        .iter()
+        .zip(seen)
+        .filter(|(_, seen)| !seen)
+        .map(|(placeholder, _)| placeholder.clone())
+        .collect();
+    (restored, missing)
+}
@@ -0,0 +1,355 @@
+//! 文本翻译
+//!
+//! 通过已配置的模型翻译文本，模型按 JSON 返回译文、源语言、备选译文和置信度。
+
+use super::detect::detect_script_language;
+use super::placeholders;
+use crate::ai::llm::{self, ChatRequestMessage};
+use crate::database::glossary::{Glossary, GlossaryTerm};
+use crate::database::secrets::Vault;
+use crate::database::translation_memory::{self, MemoryMatch, MemoryScope};
+use crate::database::Database;
+use crate::error::{AppError, AppResult};
+use crate::translate::segments::split_whitespace;
+use serde::{Deserialize, Serialize};
+use tauri::State;
+
+const DEFAULT_ALTERNATIVES: u32 = 2;
+const MAX_ALTERNATIVES: u32 = 5;
+pub(crate) const TRANSLATION_TEMPERATURE: f32 = 0.3;
+/// 每段原文最多参考的翻译记忆条数
+pub(crate) const MAX_MEMORY_EXAMPLES: usize = 3;
+// 译文丢失占位符时置信度的上限
+const MISSING_PLACEHOLDER_CONFIDENCE: f32 = 0.3;
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum Tone {
+    /// 与原文保持一致
+    #[default]
+    Neutral,
+    Formal,
+    Casual,
+}
+
+#[derive(Deserialize, Debug, Clone, Default)]
+#[serde(default)]
+pub struct TranslationOptions {
+    /// 目标语言，例如 `en`、`zh-CN`、`Japanese`
+    pub target_language: String,
+    /// 为空时由模型检测
+    pub source_language: Option<String>,
+    pub tone: Tone,
+    /// 领域提示，例如“法律合同”“游戏界面”
+    pub domain: Option<String>,
+    /// 需要原样保留的内容，如产品名；变量、标签和链接会自动保留
+    pub preserve: Vec<String>,
+    /// 备选译文数量
+    pub alternatives: Option<u32>,
+    /// 为空时使用当前激活的模型
+    pub model_id: Option<String>,
+    /// 翻译记忆所属的项目，同一项目的记录优先复用
+    pub project: Option<String>,
+}
+
+impl TranslationOptions {
+    pub(crate) fn validate(&self) -> AppResult<()> {
+        if self.target_language.trim().is_empty() {
+            return Err(AppError::invalid_input("请指定目标语言"));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn memory_scope(&self) -> MemoryScope {
+        MemoryScope::new(
+            self.source_language.as_deref(),
+            &self.target_language,
+            self.project.as_deref(),
+        )
+    }
+
+    /// 语言、语气、领域和占位符的要求，批量翻译也使用同样的说明
+    pub(crate) fn instructions(&self) -> String {
+        let mut lines = vec![format!("Translate into {}.", self.target_language.trim())];
+        match self.source_language.as_deref().map(str::trim) {
+            Some(source) if !source.is_empty() => {
+                lines.push(format!("The source language is {}.", source))
+            }
+            _ => lines.push("Detect the source language yourself.".to_string()),
+        }
+        match self.tone {
+            Tone::Neutral => lines.push("Keep the tone and register of the source.".to_string()),
+            Tone::Formal => lines.push("Use a formal, polite register.".to_string()),
+            Tone::Casual => lines.push("Use a casual, conversational register.".to_string()),
+        }
+        if let Some(domain) = self
+            .domain
+            .as_deref()
+            .map(str::trim)
+            .filter(|d| !d.is_empty())
+        {
+            lines.push(format!(
+                "The text belongs to this domain: {}. Use its established terminology.",
+                domain
+            ));
+        }
+        lines.push(
+            "Tokens such as ⟦0⟧ are placeholders: copy every one unchanged into the translation exactly once, never translate or drop them."
+                .to_string(),
+        );
+        lines.join("\n")
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct TranslationResult {
+    pub translation: String,
+    /// 模型检测或用户指定的源语言
+    pub source_language: Option<String>,
+    pub target_language: String,
+    pub alternatives: Vec<String>,
+    /// 0 到 1，模型没有给出时为空
+    pub confidence: Option<f32>,
+    pub model_id: String,
+    /// 译文直接取自翻译记忆，没有调用模型
+    pub from_memory: bool,
+    /// 例如译文丢失了占位符
+    pub warnings: Vec<String>,
+}
+
+#[derive(Deserialize, Debug)]
+struct ModelTranslation {
+    #[serde(default)]
+    source_language: Option<String>,
+    translation: String,
+    #[serde(default)]
+    alternatives: Vec<String>,
+    #[serde(default)]
+    confidence: Option<f32>,
+}
+
+/// 从回复中取出 JSON 对象，兼容模型额外包裹的代码块或说明文字
+pub(crate) fn extract_json(content: &str, open: char, close: char) -> Option<&str> {
+    let start = content.find(open)?;
+    let end = content.rfind(close)?;
+    (end > start).then(|| &content[start..=end])
+}
+
+/// 翻译记忆中的相似译文和原文中出现的术语，追加在提示词末尾
+pub(crate) fn references(examples: &[MemoryMatch], terms: &[&GlossaryTerm]) -> String {
+    let quote = |text: &str| serde_json::Value::from(text).to_string();
+    let mut lines = Vec::new();
+    if !examples.is_empty() {
+        lines.push(
+            "Approved translations of similar text from the translation memory; reuse their terminology and style where they apply:"
+                .to_string(),
+        );
+        lines.extend(examples.iter().map(|example| {
+            format!(
+                "{} => {}",
+                quote(&example.source_text),
+                quote(&example.target_text)
+            )
+        }));
+    }
+
+    let (keep, translate): (Vec<&GlossaryTerm>, Vec<&GlossaryTerm>) = terms
+        .iter()
+        .copied()
+        .partition(|term| term.do_not_translate);
+    if !translate.is_empty() {
+        lines.push("Glossary: always translate these terms exactly as given:".to_string());
+        lines.extend(
+            translate
+                .iter()
+                .map(|term| format!("{} => {}", quote(&term.term), quote(&term.translation))),
+        );
+    }
+    if !keep.is_empty() {
+        let keep: Vec<String> = keep.iter().map(|term| quote(&term.term)).collect();
+        lines.push(format!(
+            "Never translate these terms, keep them exactly as written: {}",
+            keep.join(", ")
+        ));
+    }
+
+    lines.iter().map(|line| format!("\n{}", line)).collect()
+}
+
+fn system_prompt(options: &TranslationOptions, alternatives: u32, references: &str) -> String {
+    format!(
+        "You are a professional translator.\n{}\n\
+         Reply with a single JSON object and nothing else:\n\
+         {{\"source_language\": \"<ISO 639-1 code of the source>\", \
+         \"translation\": \"<best translation>\", \
+         \"alternatives\": [<up to {} other distinct translations>], \
+         \"confidence\": <number from 0 to 1>}}{}",
+        options.instructions(),
+        alternatives,
+        references
+    )
+}
+
+#[tauri::command]
+pub async fn translate_text(
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    text: String,
+    options: TranslationOptions,
+) -> AppResult<TranslationResult> {
+    options.validate()?;
+    let alternatives = options
+        .alternatives
+        .unwrap_or(DEFAULT_ALTERNATIVES)
+        .min(MAX_ALTERNATIVES);
+    let model_id = llm::resolve_model_id(&db, options.model_id.clone()).await?;
+
+    if text.trim().is_empty() {
+        return Ok(TranslationResult {
+            translation: text,
+            source_language: options.source_language,
+            target_language: options.target_language,
+            alternatives: Vec::new(),
+            confidence: None,
+            model_id,
+            from_memory: false,
+            warnings: Vec::new(),
+        });
+    }
+
+    // 完全相同的原文直接复用译文，否则取相似原文作为参考
+    let scope = options.memory_scope();
+    let (leading, source, trailing) = split_whitespace(&text);
+    let key = source.to_string();
+    let (exact, examples, glossary) = db
+        .run({
+            let scope = scope.clone();
+            let source_language = options.source_language.clone();
+            let target_language = options.target_language.clone();
+            move |conn| {
+                let glossary = Glossary::load(conn, source_language.as_deref(), &target_language)?;
+                let sources = std::slice::from_ref(&key);
+                let exact = translation_memory::exact_matches(conn, &scope, sources)?.remove(&key);
+                let examples = match exact {
+                    Some(_) => Vec::new(),
+                    None => translation_memory::fuzzy_matches(
+                        conn,
+                        &scope,
+                        sources,
+                        MAX_MEMORY_EXAMPLES,
+                    )?
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+                };
+                Ok((exact, examples, glossary))
+            }
+        })
+        .await?;
+
+    if let Some(target) = exact {
+        let warnings = glossary.violations(source, &target);
+        return Ok(TranslationResult {
+            translation: format!("{}{}{}", leading, target, trailing),
+            source_language: options
+                .source_language
+                .filter(|s| !s.trim().is_empty())
+                .or_else(|| detect_script_language(&text).map(str::to_string)),
+            target_language: options.target_language,
+            alternatives: Vec::new(),
+            confidence: Some(1.0),
+            model_id,
+            from_memory: true,
+            warnings,
+        });
+    }
+
+    // 不翻译的术语与用户指定的内容一样作为占位符保护
+    let mut preserve = options.preserve.clone();
+    preserve.extend(glossary.protected_terms());
+    let protected = placeholders::protect(&text, &preserve);
+    let context = references(&examples, &glossary.matching(&[source]));
+    let content = llm::complete(
+        &db,
+        &vault,
+        &model_id,
+        vec![
+            ChatRequestMessage::system(system_prompt(&options, alternatives, &context)),
+            ChatRequestMessage::user(protected.text.clone()),
+        ],
+        Some(TRANSLATION_TEMPERATURE),
+    )
+    .await?;
+
+    let mut warnings = Vec::new();
+    let parsed = extract_json(&content, '{', '}')
+        .and_then(|json| serde_json::from_str::<ModelTranslation>(json).ok());
+    let well_formed = parsed.is_some();
+    let parsed = parsed.unwrap_or_else(|| {
+        warnings.push("模型未按要求的格式返回，无法提供备选译文和置信度".to_string());
+        ModelTranslation {
+            source_language: None,
+            translation: content.trim().to_string(),
+            alternatives: Vec::new(),
+            confidence: None,
+        }
+    });
+
+    let (translation, missing) =
+        placeholders::restore(&parsed.translation, &protected.placeholders);
+    let mut confidence = parsed.confidence.map(|c| c.clamp(0.0, 1.0));
+    if !missing.is_empty() {
+        warnings.push(format!("译文缺少以下内容: {}", missing.join(", ")));
+        confidence = Some(
+            confidence
+                .unwrap_or(1.0)
+                .min(MISSING_PLACEHOLDER_CONFIDENCE),
+        );
+    }
+
+    // 丢失占位符或与主译文重复的备选译文不返回
+    let mut alternative_translations: Vec<String> = Vec::new();
+    for alternative in parsed.alternatives {
+        let (restored, missing) = placeholders::restore(&alternative, &protected.placeholders);
+        if missing.is_empty()
+            && restored != translation
+            && !alternative_translations.contains(&restored)
+        {
+            alternative_translations.push(restored);
+        }
+    }
+    alternative_translations.truncate(alternatives as usize);
+
+    let source_language = options
+        .source_language
+        .filter(|s| !s.trim().is_empty())
+        .or(parsed.source_language)
+        .or_else(|| detect_script_language(&text).map(str::to_string));
+
+    let violations = glossary.violations(source, &translation);
+    let compliant = violations.is_empty();
+    warnings.extend(violations);
+
+    // 只把格式正确、占位符完整且符合术语表的译文存入翻译记忆
+    if well_formed && missing.is_empty() && compliant {
+        let scope = scope.with_source(source_language.as_deref());
+        let pair = (source.to_string(), translation.trim().to_string());
+        if let Err(e) = db
+            .run(move |conn| translation_memory::store(conn, &scope, &[pair]))
+            .await
+        {
+            warnings.push(format!("保存翻译记忆失败: {}", e));
+        }
+    }
+
+    Ok(TranslationResult {
+        translation,
+        source_language,
+        target_language: options.target_language,
+        alternatives: alternative_translations,
+        confidence,
+        model_id,
+        from_memory: false,
+        warnings,
+    })
+}
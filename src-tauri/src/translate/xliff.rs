@@ -0,0 +1,613 @@
+//! XLIFF 导出和导入
+//!
+//! 把文档中需要翻译的文字导出为 XLIFF 1.2 或 2.0，交给外部翻译在 CAT 工具中处理；
+//! 标签等行内代码导出为 `<ph>`，翻译记忆中已有的译文预先填入。
+//! 导入译好的 XLIFF 时按原文生成译文文件，并按句段状态写入翻译记忆：
+//! 未翻译的忽略，已翻译的作为普通记录，已审校或定稿的标记为审校过。
+
+use super::document::DocumentKind;
+use super::office;
+use super::segments::{needs_translation, preview, split_whitespace, translated_output_path};
+use crate::database::translation_memory::{self, MemoryScope};
+use crate::database::Database;
+use crate::error::{AppError, AppResult};
+use once_cell::sync::Lazy;
+use quick_xml::escape::escape;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use tauri::State;
+
+const XLIFF_EXTENSIONS: &[&str] = &["xlf", "xliff"];
+const NAMESPACE_1_2: &str = "urn:oasis:names:tc:xliff:document:1.2";
+const NAMESPACE_2_0: &str = "urn:oasis:names:tc:xliff:document:2.0";
+
+/// 作为行内代码导出的内容：标签（包括 `<g1>`、`<x0/>` 等占位标签）和 HTML 实体
+static INLINE_CODE: Lazy<Regex> =
+    Lazy::new(|| Regex::new(r"</?[A-Za-z][^<>]*>|&(?:[A-Za-z]+|#\d+|#x[0-9A-Fa-f]+);").unwrap());
+
+/// 行内代码元素
+const CODE_ELEMENTS: &[&[u8]] = &[
+    b"ph", b"x", b"bx", b"ex", b"bpt", b"ept", b"it", b"sc", b"ec",
+];
+/// 其中的原文和译文不属于句段，如 1.2 的 `<alt-trans>` 和 2.0 的匹配模块
+const IGNORED_ELEMENTS: &[&[u8]] = &[b"seg-source", b"alt-trans", b"matches", b"notes"];
+
+#[derive(Deserialize, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum XliffVersion {
+    #[default]
+    #[serde(rename = "1.2")]
+    V1_2,
+    #[serde(rename = "2.0")]
+    V2_0,
+}
+
+/// 句段状态，按完成程度排序
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum SegmentState {
+    New,
+    Translated,
+    Reviewed,
+}
+
+impl SegmentState {
+    fn parse(version: XliffVersion, state: &str) -> Self {
+        match (version, state) {
+            (
+                XliffVersion::V1_2,
+                "new" | "needs-translation" | "needs-l10n" | "needs-adaptation",
+            )
+            | (XliffVersion::V2_0, "initial") => Self::New,
+            (XliffVersion::V1_2, "final" | "signed-off")
+            | (XliffVersion::V2_0, "reviewed" | "final") => Self::Reviewed,
+            _ => Self::Translated,
+        }
+    }
+}
+
+#[derive(Serialize, Debug)]
+pub struct XliffExportResult {
+    pub xliff_path: String,
+    /// 去重后的翻译单元数
+    pub units: usize,
+    /// 其中已从翻译记忆填入译文的单元数
+    pub from_memory: usize,
+}
+
+#[derive(Serialize, Debug)]
+pub struct XliffImportResult {
+    pub output_path: String,
+    pub units: usize,
+    /// 已翻译但未审校的单元数
+    pub translated: usize,
+    pub reviewed: usize,
+    /// 没有译文或状态为未翻译的单元数，保留原文
+    pub untranslated: usize,
+    pub warnings: Vec<String>,
+}
+
+/// 一个单元中的行内代码，ID 从 1 开始
+#[derive(Default)]
+struct InlineCodes {
+    codes: Vec<String>,
+}
+
+impl InlineCodes {
+    /// 转为 XLIFF 内容；译文中的代码按出现次序对应原文中相同的代码
+    fn encode(&mut self, text: &str, version: XliffVersion, is_target: bool) -> String {
+        let mut occurrences: HashMap<&str, usize> = HashMap::new();
+        let mut content = String::new();
+        let mut last = 0;
+        for code in INLINE_CODE.find_iter(text) {
+            content.push_str(&escape(&text[last..code.start()]));
+            last = code.end();
+
+            let code = code.as_str();
+            let existing = if is_target {
+                let nth = occurrences.entry(code).or_default();
+                *nth += 1;
+                self.codes
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, known)| *known == code)
+                    .nth(*nth - 1)
+                    .map(|(index, _)| index)
+            } else {
+                None
+            };
+            let id = match existing {
+                Some(index) => index + 1,
+                None => {
+                    self.codes.push(code.to_string());
+                    self.codes.len()
+                }
+            };
+            match version {
+                XliffVersion::V1_2 => {
+                    content.push_str(&format!(r#"<ph id="{}">{}</ph>"#, id, escape(code)))
+                }
+                XliffVersion::V2_0 => {
+                    content.push_str(&format!(r#"<ph id="{}" dataRef="d{}"/>"#, id, id))
+                }
+            }
+        }
+        content.push_str(&escape(&text[last..]));
+        content
+    }
+
+    /// XLIFF 2.0 中行内代码的原始内容
+    fn original_data(&self) -> String {
+        if self.codes.is_empty() {
+            return String::new();
+        }
+        let data: String = self
+            .codes
+            .iter()
+            .enumerate()
+            .map(|(index, code)| format!(r#"<data id="d{}">{}</data>"#, index + 1, escape(code)))
+            .collect();
+        format!("\n    <originalData>{}</originalData>", data)
+    }
+}
+
+/// 生成 XLIFF 文件内容，`remembered` 为翻译记忆中的译文及是否审校过
+fn write_xliff(
+    version: XliffVersion,
+    original: &str,
+    source_language: &str,
+    target_language: &str,
+    sources: &[String],
+    remembered: &HashMap<String, (String, bool)>,
+) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+    match version {
+        XliffVersion::V1_2 => xml.push_str(&format!(
+            "<xliff version=\"1.2\" xmlns=\"{}\">\n\
+             <file original=\"{}\" source-language=\"{}\" target-language=\"{}\" datatype=\"plaintext\">\n\
+             <body>\n",
+            NAMESPACE_1_2,
+            escape(original),
+            escape(source_language),
+            escape(target_language)
+        )),
+        XliffVersion::V2_0 => xml.push_str(&format!(
+            "<xliff version=\"2.0\" xmlns=\"{}\" srcLang=\"{}\" trgLang=\"{}\">\n\
+             <file id=\"f1\" original=\"{}\">\n",
+            NAMESPACE_2_0,
+            escape(source_language),
+            escape(target_language),
+            escape(original)
+        )),
+    }
+
+    for (index, source) in sources.iter().enumerate() {
+        let id = index + 1;
+        let mut codes = InlineCodes::default();
+        let source_content = codes.encode(source, version, false);
+        let target = remembered
+            .get(source)
+            .map(|(target, reviewed)| (codes.encode(target, version, true), *reviewed));
+
+        match version {
+            XliffVersion::V1_2 => {
+                xml.push_str(&format!(
+                    "  <trans-unit id=\"{}\" xml:space=\"preserve\">\n    <source>{}</source>\n",
+                    id, source_content
+                ));
+                if let Some((target, reviewed)) = target {
+                    let state = if reviewed { "final" } else { "translated" };
+                    xml.push_str(&format!(
+                        "    <target state=\"{}\">{}</target>\n",
+                        state, target
+                    ));
+                }
+                xml.push_str("  </trans-unit>\n");
+            }
+            XliffVersion::V2_0 => {
+                let state = match &target {
+                    Some((_, true)) => "reviewed",
+                    Some((_, false)) => "translated",
+                    None => "initial",
+                };
+                xml.push_str(&format!(
+                    "  <unit id=\"{}\" xml:space=\"preserve\">{}\n    <segment state=\"{}\">\n      <source>{}</source>\n",
+                    id,
+                    codes.original_data(),
+                    state,
+                    source_content
+                ));
+                if let Some((target, _)) = target {
+                    xml.push_str(&format!("      <target>{}</target>\n", target));
+                }
+                xml.push_str("    </segment>\n  </unit>\n");
+            }
+        }
+    }
+
+    match version {
+        XliffVersion::V1_2 => xml.push_str("</body>\n</file>\n</xliff>\n"),
+        XliffVersion::V2_0 => xml.push_str("</file>\n</xliff>\n"),
+    }
+    xml
+}
+
+/// XLIFF 文件与原文件放在同一目录，命名为 `<原文件名>-<语言>.<扩展名>.xlf`
+fn xliff_path(input: &Path, target_language: &str) -> AppResult<PathBuf> {
+    let mut path = translated_output_path(input, target_language)?.into_os_string();
+    path.push(".");
+    path.push(XLIFF_EXTENSIONS[0]);
+    Ok(PathBuf::from(path))
+}
+
+/// 导出文档中需要翻译的文字，原文相同的只导出一次
+#[tauri::command]
+pub async fn export_xliff(
+    db: State<'_, Database>,
+    input_path: String,
+    source_language: String,
+    target_language: String,
+    project: Option<String>,
+    version: Option<XliffVersion>,
+) -> AppResult<XliffExportResult> {
+    let source_language = source_language.trim().to_string();
+    let target_language = target_language.trim().to_string();
+    if source_language.is_empty() || target_language.is_empty() {
+        return Err(AppError::invalid_input("请指定源语言和目标语言"));
+    }
+    let path = Path::new(&input_path);
+    let kind = DocumentKind::from_path(path)?;
+    let output_path = xliff_path(path, &target_language)?;
+
+    let mut seen = HashSet::new();
+    let sources: Vec<String> = kind
+        .collect_texts(path)?
+        .iter()
+        .map(|text| split_whitespace(text).1.to_string())
+        .filter(|core| needs_translation(core) && seen.insert(core.clone()))
+        .collect();
+    let scope = MemoryScope::new(Some(&source_language), &target_language, project.as_deref());
+    let lookup = sources.clone();
+    let remembered = db
+        .run(move |conn| translation_memory::exact_entries(conn, &scope, &lookup))
+        .await?;
+
+    let original = path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let xml = write_xliff(
+        version.unwrap_or_default(),
+        &original,
+        &source_language,
+        &target_language,
+        &sources,
+        &remembered,
+    );
+    std::fs::write(&output_path, xml)?;
+
+    Ok(XliffExportResult {
+        xliff_path: output_path.to_string_lossy().into_owned(),
+        units: sources.len(),
+        from_memory: remembered.len(),
+    })
+}
+
+/// XLIFF 中的一个翻译单元，2.0 中多个句段已合并
+#[derive(Default)]
+struct XliffUnit {
+    source: String,
+    target: String,
+    /// 各句段中完成程度最低的状态
+    state: Option<SegmentState>,
+}
+
+impl XliffUnit {
+    fn state(&self) -> SegmentState {
+        if self.target.trim().is_empty() {
+            return SegmentState::New;
+        }
+        self.state.unwrap_or(SegmentState::Translated)
+    }
+}
+
+#[derive(Default)]
+struct XliffFile {
+    source_language: String,
+    target_language: String,
+    units: Vec<XliffUnit>,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum Part {
+    Source,
+    Target,
+}
+
+fn attribute(
+    reader: &Reader<&[u8]>,
+    element: &BytesStart,
+    name: &[u8],
+) -> AppResult<Option<String>> {
+    for attr in element.attributes().flatten() {
+        if attr.key.as_ref() == name {
+            return Ok(Some(attr.decode_and_unescape_value(reader)?.into_owned()));
+        }
+    }
+    Ok(None)
+}
+
+/// 读取 XLIFF 1.2 或 2.0 中的翻译单元，行内代码换回原始内容
+fn parse_xliff(xml: &[u8]) -> AppResult<XliffFile> {
+    let mut reader = office::reader(xml);
+    let mut file = XliffFile::default();
+    let mut version = XliffVersion::V1_2;
+    let mut unit: Option<XliffUnit> = None;
+    let mut part: Option<Part> = None;
+    let mut ignored_depth = 0;
+    // 2.0 的 originalData，以及 1.2 原文中行内代码的内容，按元素名和 ID 区分成对的代码
+    let mut codes: HashMap<String, String> = HashMap::new();
+    // 正在读取的 1.2 行内代码或 2.0 data 元素
+    let mut code: Option<(String, String)> = None;
+    let mut data: Option<(String, String)> = None;
+    // 2.0 中 ignorable 的原文，没有对应译文时原样补到译文中
+    let mut ignorable: Option<(String, bool)> = None;
+
+    loop {
+        let event = reader.read_event()?;
+        match &event {
+            Event::Eof => break,
+            Event::Start(element) | Event::Empty(element) => {
+                let empty = matches!(event, Event::Empty(_));
+                let name = element.local_name();
+                let name = name.as_ref();
+                if IGNORED_ELEMENTS.contains(&name) {
+                    ignored_depth += usize::from(!empty);
+                    continue;
+                }
+                if ignored_depth > 0 {
+                    continue;
+                }
+                match name {
+                    b"xliff" => {
+                        let declared = attribute(&reader, element, b"version")?;
+                        if declared.is_some_and(|v| v.starts_with('2')) {
+                            version = XliffVersion::V2_0;
+                            file.source_language =
+                                attribute(&reader, element, b"srcLang")?.unwrap_or_default();
+                            file.target_language =
+                                attribute(&reader, element, b"trgLang")?.unwrap_or_default();
+                        }
+                    }
+                    b"file" if version == XliffVersion::V1_2 => {
+                        file.source_language =
+                            attribute(&reader, element, b"source-language")?.unwrap_or_default();
+                        file.target_language =
+                            attribute(&reader, element, b"target-language")?.unwrap_or_default();
+                    }
+                    b"trans-unit" | b"unit" if !empty => {
+                        unit = Some(XliffUnit::default());
+                        codes.clear();
+                    }
+                    b"data" if !empty => {
+                        data = attribute(&reader, element, b"id")?.map(|id| (id, String::new()));
+                    }
+                    b"segment" => {
+                        if let (Some(unit), Some(state)) =
+                            (unit.as_mut(), attribute(&reader, element, b"state")?)
+                        {
+                            let state = SegmentState::parse(version, &state);
+                            unit.state = Some(unit.state.map_or(state, |s| s.min(state)));
+                        }
+                    }
+                    b"ignorable" if !empty => ignorable = Some((String::new(), false)),
+                    b"source" if unit.is_some() && !empty => part = Some(Part::Source),
+                    b"target" if unit.is_some() && !empty => {
+                        part = Some(Part::Target);
+                        if let Some((_, has_target)) = ignorable.as_mut() {
+                            *has_target = true;
+                        }
+                        if version == XliffVersion::V1_2 {
+                            if let (Some(unit), Some(state)) =
+                                (unit.as_mut(), attribute(&reader, element, b"state")?)
+                            {
+                                unit.state = Some(SegmentState::parse(version, &state));
+                            }
+                        }
+                    }
+                    _ if CODE_ELEMENTS.contains(&name) && part.is_some() => {
+                        let id = attribute(&reader, element, b"id")?.unwrap_or_default();
+                        let id = format!("{}:{}", String::from_utf8_lossy(name), id);
+                        let data_ref = attribute(&reader, element, b"dataRef")?;
+                        if version == XliffVersion::V2_0 || empty {
+                            // 2.0 按 dataRef 取原始内容，1.2 的空元素按原文中相同的代码
+                            let key = data_ref.unwrap_or(id);
+                            let content = codes.get(&key).cloned().unwrap_or_default();
+                            if let (Some(unit), Some(part)) = (unit.as_mut(), part) {
+                                push_text(unit, part, &mut ignorable, &content);
+                            }
+                        } else {
+                            code = Some((id, String::new()));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(text) if ignored_depth == 0 => {
+                let text = text.unescape()?;
+                if let Some((_, content)) = data.as_mut().or(code.as_mut()) {
+                    content.push_str(&text);
+                } else if let (Some(unit), Some(part)) = (unit.as_mut(), part) {
+                    push_text(unit, part, &mut ignorable, &text);
+                }
+            }
+            Event::End(element) => {
+                let name = element.local_name();
+                let name = name.as_ref();
+                if IGNORED_ELEMENTS.contains(&name) {
+                    ignored_depth = ignored_depth.saturating_sub(1);
+                    continue;
+                }
+                if ignored_depth > 0 {
+                    continue;
+                }
+                match name {
+                    b"data" => {
+                        if let Some((id, content)) = data.take() {
+                            codes.insert(id, content);
+                        }
+                    }
+                    b"source" | b"target" => part = None,
+                    b"ignorable" => {
+                        if let (Some(unit), Some((source, false))) = (unit.as_mut(), ignorable) {
+                            unit.target.push_str(&source);
+                        }
+                        ignorable = None;
+                    }
+                    b"trans-unit" | b"unit" => {
+                        if let Some(unit) = unit.take() {
+                            file.units.push(unit);
+                        }
+                    }
+                    _ if CODE_ELEMENTS.contains(&name) => {
+                        if let (Some((id, content)), Some(unit), Some(part)) =
+                            (code.take(), unit.as_mut(), part)
+                        {
+                            // 译文中的代码内容为空时使用原文中相同的代码
+                            let content = match part {
+                                Part::Source => {
+                                    codes.insert(id, content.clone());
+                                    content
+                                }
+                                Part::Target if content.is_empty() => {
+                                    codes.get(&id).cloned().unwrap_or_default()
+                                }
+                                Part::Target => content,
+                            };
+                            push_text(unit, part, &mut ignorable, &content);
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            _ => {}
+        }
+    }
+    Ok(file)
+}
+
+fn push_text(unit: &mut XliffUnit, part: Part, ignorable: &mut Option<(String, bool)>, text: &str) {
+    match part {
+        Part::Source => {
+            unit.source.push_str(text);
+            if let Some((source, _)) = ignorable.as_mut() {
+                source.push_str(text);
+            }
+        }
+        Part::Target => unit.target.push_str(text),
+    }
+}
+
+/// 导入译好的 XLIFF，基于原文件生成译文文件，并把译文写入翻译记忆
+///
+/// 不指定输出路径时，按 XLIFF 文件名去掉 `.xlf` 后的路径输出
+#[tauri::command]
+pub async fn import_xliff(
+    db: State<'_, Database>,
+    input_path: String,
+    xliff_path: String,
+    output_path: Option<String>,
+    project: Option<String>,
+) -> AppResult<XliffImportResult> {
+    let input = Path::new(&input_path);
+    let kind = DocumentKind::from_path(input)?;
+    let xliff = Path::new(&xliff_path);
+    if !xliff.exists() {
+        return Err(AppError::not_found("XLIFF 文件不存在"));
+    }
+    let file = parse_xliff(&std::fs::read(xliff)?)?;
+    if file.target_language.trim().is_empty() {
+        return Err(AppError::invalid_input("XLIFF 文件未指定目标语言"));
+    }
+
+    let output = match output_path.filter(|p| !p.trim().is_empty()) {
+        Some(path) => PathBuf::from(path),
+        None => {
+            let stripped = xliff
+                .extension()
+                .filter(|ext| {
+                    XLIFF_EXTENSIONS.contains(&ext.to_string_lossy().to_lowercase().as_str())
+                })
+                .map(|_| xliff.with_extension(""))
+                .filter(|path| path.extension() == input.extension());
+            match stripped {
+                Some(path) => path,
+                None => translated_output_path(input, &file.target_language)?,
+            }
+        }
+    };
+    if output == input || output == xliff {
+        return Err(AppError::invalid_input(
+            "输出文件不能覆盖原文件或 XLIFF 文件",
+        ));
+    }
+
+    let mut translations: HashMap<String, String> = HashMap::new();
+    let mut translated = Vec::new();
+    let mut reviewed = Vec::new();
+    let mut untranslated = 0;
+    let mut warnings = Vec::new();
+    for unit in &file.units {
+        let state = unit.state();
+        if state == SegmentState::New {
+            untranslated += 1;
+            continue;
+        }
+        let (source, target) = (unit.source.trim(), unit.target.trim());
+        match translations.get(source) {
+            Some(existing) if existing != target => warnings.push(format!(
+                "“{}”在多处的译文不一致，已使用第一处",
+                preview(source)
+            )),
+            Some(_) => {}
+            None => {
+                translations.insert(source.to_string(), target.to_string());
+                let pair = (source.to_string(), target.to_string());
+                match state {
+                    SegmentState::Reviewed => reviewed.push(pair),
+                    _ => translated.push(pair),
+                }
+            }
+        }
+    }
+
+    warnings.extend(kind.write_translated(input, &output, |text| {
+        let (leading, core, trailing) = split_whitespace(text);
+        translations
+            .get(core)
+            .map(|target| format!("{}{}{}", leading, target, trailing))
+    })?);
+
+    let scope = MemoryScope::new(
+        Some(&file.source_language),
+        &file.target_language,
+        project.as_deref(),
+    );
+    let counts = (translated.len(), reviewed.len());
+    db.run(move |conn| {
+        translation_memory::store_reviewed(conn, &scope, &translated, false)?;
+        translation_memory::store_reviewed(conn, &scope, &reviewed, true)
+    })
+    .await?;
+
+    Ok(XliffImportResult {
+        output_path: output.to_string_lossy().into_owned(),
+        units: file.units.len(),
+        translated: counts.0,
+        reviewed: counts.1,
+        untranslated,
+        warnings,
+    })
+}
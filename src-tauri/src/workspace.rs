@@ -0,0 +1,294 @@
+//! 工作区
+//!
+//! 每个工作区有独立的数据库、ChromaDB 数据目录和设置文件。默认工作区就是应用数据目录本身，
+//! 兼容旧版本的数据；新建的工作区保存在 `workspaces/<id>/` 下。
+//! 工作区列表和当前工作区记录在应用数据目录下的 `workspaces.json`。
+
+use crate::database::backup::IntegrityState;
+use crate::database::secrets::{self, Vault};
+use crate::database::{self, Database};
+use crate::error::{AppError, AppResult};
+use crate::settings::{Settings, SETTINGS_CHANGED_EVENT, SETTINGS_INVALID_EVENT};
+use crate::ChromaServerState;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+use tauri::{AppHandle, Emitter, Manager, Runtime, State};
+use uuid::Uuid;
+
+const REGISTRY_FILE: &str = "workspaces.json";
+const WORKSPACES_DIR: &str = "workspaces";
+const DEFAULT_WORKSPACE_ID: &str = "default";
+const DEFAULT_WORKSPACE_NAME: &str = "默认工作区";
+pub const WORKSPACE_SWITCHED_EVENT: &str = "workspace://switched";
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct Workspace {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct WorkspaceList {
+    pub active_id: String,
+    pub workspaces: Vec<Workspace>,
+}
+
+impl WorkspaceList {
+    fn find(&self, workspace_id: &str) -> AppResult<&Workspace> {
+        self.workspaces
+            .iter()
+            .find(|w| w.id == workspace_id)
+            .ok_or_else(|| AppError::not_found(format!("工作区不存在: {}", workspace_id)))
+    }
+
+    /// 工作区名称不区分大小写唯一
+    fn check_name(&self, name: &str, except_id: Option<&str>) -> AppResult<String> {
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(AppError::invalid_input("工作区名称不能为空"));
+        }
+        let taken = self.workspaces.iter().any(|w| {
+            Some(w.id.as_str()) != except_id && w.name.to_lowercase() == name.to_lowercase()
+        });
+        if taken {
+            return Err(AppError::conflict(format!("工作区已存在: {}", name)));
+        }
+        Ok(name.to_string())
+    }
+
+    /// 保证默认工作区存在，且当前工作区指向列表中的某一项
+    fn normalize(mut self) -> Self {
+        if !self.workspaces.iter().any(|w| w.id == DEFAULT_WORKSPACE_ID) {
+            self.workspaces.insert(0, default_workspace());
+        }
+        if self.find(&self.active_id).is_err() {
+            self.active_id = DEFAULT_WORKSPACE_ID.to_string();
+        }
+        self
+    }
+}
+
+impl Default for WorkspaceList {
+    fn default() -> Self {
+        Self {
+            active_id: DEFAULT_WORKSPACE_ID.to_string(),
+            workspaces: vec![default_workspace()],
+        }
+    }
+}
+
+fn default_workspace() -> Workspace {
+    Workspace {
+        id: DEFAULT_WORKSPACE_ID.to_string(),
+        name: DEFAULT_WORKSPACE_NAME.to_string(),
+        created_at: chrono::Utc::now().to_rfc3339(),
+    }
+}
+
+/// 作为 Tauri 状态管理的工作区列表
+pub struct Workspaces {
+    root: PathBuf,
+    list: RwLock<WorkspaceList>,
+    /// 启动时工作区列表无效的原因
+    load_error: Option<AppError>,
+}
+
+impl Workspaces {
+    /// 读取工作区列表，文件不存在或无法解析时只有默认工作区
+    pub fn load(root: PathBuf) -> Self {
+        let (list, load_error) = match fs::read_to_string(root.join(REGISTRY_FILE)) {
+            Ok(content) => match serde_json::from_str::<WorkspaceList>(&content) {
+                Ok(list) => (list, None),
+                Err(e) => (
+                    WorkspaceList::default(),
+                    Some(
+                        AppError::invalid_input("工作区列表无效，只保留默认工作区").with_details(e),
+                    ),
+                ),
+            },
+            Err(_) => (WorkspaceList::default(), None),
+        };
+
+        Self {
+            root,
+            list: RwLock::new(list.normalize()),
+            load_error,
+        }
+    }
+
+    pub fn list(&self) -> WorkspaceList {
+        self.list.read().unwrap().clone()
+    }
+
+    fn dir_of(&self, workspace_id: &str) -> PathBuf {
+        if workspace_id == DEFAULT_WORKSPACE_ID {
+            self.root.clone()
+        } else {
+            self.root.join(WORKSPACES_DIR).join(workspace_id)
+        }
+    }
+
+    /// 当前工作区的数据目录
+    pub fn active_dir(&self) -> PathBuf {
+        self.dir_of(&self.list.read().unwrap().active_id)
+    }
+
+    /// 当前工作区的 ChromaDB 数据目录，默认工作区沿用旧版本的位置
+    pub fn chroma_dir(&self) -> PathBuf {
+        let list = self.list.read().unwrap();
+        if list.active_id == DEFAULT_WORKSPACE_ID {
+            self.root.join("app_data").join("chromadb")
+        } else {
+            self.dir_of(&list.active_id).join("chromadb")
+        }
+    }
+
+    /// 在副本上修改并写入文件，成功后才替换内存中的列表
+    fn update<T>(&self, f: impl FnOnce(&mut WorkspaceList) -> AppResult<T>) -> AppResult<T> {
+        let mut list = self.list.write().unwrap();
+        let mut updated = list.clone();
+        let result = f(&mut updated)?;
+        write_registry(&self.root, &updated)?;
+        *list = updated;
+        Ok(result)
+    }
+}
+
+/// 先写临时文件再改名，避免写到一半时崩溃导致列表损坏
+fn write_registry(root: &Path, list: &WorkspaceList) -> AppResult<()> {
+    let content = serde_json::to_string_pretty(list)
+        .map_err(|e| AppError::internal("序列化工作区列表失败").with_details(e))?;
+    fs::create_dir_all(root)?;
+    let path = root.join(REGISTRY_FILE);
+    let temp = path.with_extension("json.tmp");
+    fs::write(&temp, content).map_err(|e| AppError::io("写入工作区列表失败").with_details(e))?;
+    fs::rename(&temp, &path).map_err(|e| AppError::io("写入工作区列表失败").with_details(e))?;
+    Ok(())
+}
+
+pub fn init_workspaces<R: Runtime>(app: &AppHandle<R>) -> AppResult<Workspaces> {
+    let root = app.path().app_data_dir()?;
+    fs::create_dir_all(&root)?;
+    Ok(Workspaces::load(root))
+}
+
+#[tauri::command]
+pub fn get_workspaces(workspaces: State<'_, Workspaces>) -> WorkspaceList {
+    workspaces.list()
+}
+
+/// 启动时工作区列表无效时返回原因，前端据此提示用户
+#[tauri::command]
+pub fn get_workspaces_load_error(workspaces: State<'_, Workspaces>) -> Option<AppError> {
+    workspaces.load_error.clone()
+}
+
+#[tauri::command]
+pub fn create_workspace(workspaces: State<'_, Workspaces>, name: String) -> AppResult<Workspace> {
+    workspaces.update(|list| {
+        let workspace = Workspace {
+            id: Uuid::new_v4().to_string(),
+            name: list.check_name(&name, None)?,
+            created_at: chrono::Utc::now().to_rfc3339(),
+        };
+        fs::create_dir_all(workspaces.dir_of(&workspace.id))
+            .map_err(|e| AppError::io("创建工作区目录失败").with_details(e))?;
+        list.workspaces.push(workspace.clone());
+        Ok(workspace)
+    })
+}
+
+#[tauri::command]
+pub fn rename_workspace(
+    workspaces: State<'_, Workspaces>,
+    workspace_id: String,
+    name: String,
+) -> AppResult<()> {
+    workspaces.update(|list| {
+        let name = list.check_name(&name, Some(&workspace_id))?;
+        let workspace = list
+            .workspaces
+            .iter_mut()
+            .find(|w| w.id == workspace_id)
+            .ok_or_else(|| AppError::not_found(format!("工作区不存在: {}", workspace_id)))?;
+        workspace.name = name;
+        Ok(())
+    })
+}
+
+/// 删除工作区及其目录下的全部数据，默认工作区和当前工作区不能删除
+#[tauri::command]
+pub fn delete_workspace(workspaces: State<'_, Workspaces>, workspace_id: String) -> AppResult<()> {
+    if workspace_id == DEFAULT_WORKSPACE_ID {
+        return Err(AppError::invalid_input("默认工作区不能删除"));
+    }
+    workspaces.update(|list| {
+        list.find(&workspace_id)?;
+        if list.active_id == workspace_id {
+            return Err(AppError::conflict(
+                "不能删除当前工作区，请先切换到其他工作区",
+            ));
+        }
+        list.workspaces.retain(|w| w.id != workspace_id);
+        Ok(())
+    })?;
+
+    match fs::remove_dir_all(workspaces.dir_of(&workspace_id)) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => {
+            Err(AppError::io("删除工作区目录失败").with_details(e))
+        }
+        _ => Ok(()),
+    }
+}
+
+/// 切换工作区：先打开新工作区的数据库并加载密钥库，都成功后再停止 ChromaDB 服务器并替换数据库、密钥库和设置。
+/// 新工作区的数据库已加密时保持锁定，需要再调用 `unlock_database`
+#[tauri::command]
+#[allow(clippy::too_many_arguments)]
+pub async fn switch_workspace<R: Runtime>(
+    app: AppHandle<R>,
+    workspaces: State<'_, Workspaces>,
+    db: State<'_, Database>,
+    vault: State<'_, Vault>,
+    settings: State<'_, Settings>,
+    integrity: State<'_, IntegrityState>,
+    chroma: State<'_, ChromaServerState>,
+    workspace_id: String,
+) -> AppResult<Workspace> {
+    let list = workspaces.list();
+    let workspace = list.find(&workspace_id)?.clone();
+    if list.active_id == workspace_id {
+        return Ok(workspace);
+    }
+
+    let workspace_dir = workspaces.dir_of(&workspace_id);
+    let next_db = {
+        let workspace_dir = workspace_dir.clone();
+        tauri::async_runtime::spawn_blocking(move || database::open_workspace_db(&workspace_dir))
+            .await??
+    };
+    let next_vault = secrets::prepare_vault(&next_db, &workspace_dir).await?;
+
+    if let Some(server) = chroma.lock().await.take() {
+        server.stop()?;
+    }
+
+    workspaces.update(|list| {
+        list.active_id = workspace_id.clone();
+        Ok(())
+    })?;
+    db.replace_with(next_db);
+    vault.replace_with(next_vault);
+    integrity.reset();
+
+    let current = settings.reload(&workspace_dir);
+    app.emit(SETTINGS_CHANGED_EVENT, &current)?;
+    if let Some(e) = settings.load_error() {
+        app.emit(SETTINGS_INVALID_EVENT, e)?;
+    }
+    app.emit(WORKSPACE_SWITCHED_EVENT, &workspace)?;
+    Ok(workspace)
+}